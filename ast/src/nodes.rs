@@ -434,6 +434,16 @@ pub enum ClassExpression {
     DefineVariant(Box<DefineVariant>),
 }
 
+impl Node for ClassExpression {
+    fn location(&self) -> &SourceLocation {
+        match self {
+            ClassExpression::DefineMethod(ref typ) => typ.location(),
+            ClassExpression::DefineField(ref typ) => typ.location(),
+            ClassExpression::DefineVariant(ref typ) => typ.location(),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct ClassExpressions {
     pub values: Vec<ClassExpression>,
@@ -478,6 +488,12 @@ pub struct DefineVariant {
     pub location: SourceLocation,
 }
 
+impl Node for DefineVariant {
+    fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct AssignInstanceLiteralField {
     pub field: Field,
@@ -1482,11 +1498,26 @@ impl Node for While {
     }
 }
 
+/// A diagnostic silenced using a comment such as `# inko:allow(unreachable)`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Suppression {
+    /// The ID of the diagnostic to silence.
+    pub id: String,
+
+    /// The location of the comment.
+    pub comment: SourceLocation,
+
+    /// The location of the definition or expression the diagnostic is
+    /// silenced for.
+    pub location: SourceLocation,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct Module {
     pub expressions: Vec<TopLevelExpression>,
     pub file: PathBuf,
     pub location: SourceLocation,
+    pub suppressions: Vec<Suppression>,
 }
 
 impl Node for Module {
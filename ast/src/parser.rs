@@ -7,6 +7,7 @@
 use crate::lexer::{Lexer, Token, TokenKind};
 use crate::nodes::*;
 use crate::source_location::SourceLocation;
+use std::mem::take;
 use std::path::PathBuf;
 
 /// Produces a parser error and returns from the surrounding function.
@@ -50,6 +51,51 @@ pub struct ParseError {
     pub location: SourceLocation,
 }
 
+/// The points at which the parser resumes parsing after a syntax error.
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Boundary {
+    /// The start of a top-level definition, such as a class or an import.
+    Definition,
+
+    /// The start of a field, method or variant in the body of a class.
+    ClassMember,
+
+    /// The start of a field in the body of an extern class.
+    Field,
+
+    /// The start of a method in the body of a trait or implementation.
+    Method,
+
+    /// The start of an expression on a new line.
+    Expression,
+}
+
+impl Boundary {
+    fn starts_at(self, token: &Token) -> bool {
+        match self {
+            Boundary::Definition => {
+                *token.location.column_range.start() == 1
+                    && matches!(
+                        token.kind,
+                        TokenKind::Import
+                            | TokenKind::Class
+                            | TokenKind::Implement
+                            | TokenKind::Trait
+                            | TokenKind::Fn
+                            | TokenKind::Let
+                    )
+            }
+            Boundary::ClassMember => matches!(
+                token.kind,
+                TokenKind::Fn | TokenKind::Let | TokenKind::Case
+            ),
+            Boundary::Field => token.kind == TokenKind::Let,
+            Boundary::Method => token.kind == TokenKind::Fn,
+            Boundary::Expression => true,
+        }
+    }
+}
+
 /// A recursive-descent parser that turns Inko source code into an AST.
 ///
 /// The AST is not a lossless AST. For example, whitespace and comments are not
@@ -64,16 +110,69 @@ pub struct Parser {
     ///
     /// When this value is 0, trailing blocks are allowed.
     trailing_block_allowed: u16,
+
+    /// The last line of the last consumed token that isn't a comment or
+    /// whitespace.
+    line: usize,
+
+    /// The last token produced by the lexer, if it's a token that may start a
+    /// definition or end a block.
+    ///
+    /// This is used to retain such tokens when recovering from syntax errors
+    /// produced for them.
+    last: Option<Token>,
+
+    /// The diagnostic IDs of the `# inko:allow(...)` comments that precede
+    /// the next token, along with the locations of these comments.
+    allow: Vec<(String, SourceLocation)>,
+
+    /// The diagnostic IDs to silence for the definition or expression that
+    /// starts with the token at the given location.
+    allow_token: Option<(SourceLocation, Vec<(String, SourceLocation)>)>,
+
+    suppressions: Vec<Suppression>,
+
+    /// The syntax errors produced thus far.
+    errors: Vec<ParseError>,
 }
 
 impl Parser {
     pub fn new(input: Vec<u8>, file: PathBuf) -> Self {
         let lexer = Lexer::new(input);
 
-        Self { file, lexer, peeked: None, trailing_block_allowed: 0 }
+        Self {
+            file,
+            lexer,
+            peeked: None,
+            trailing_block_allowed: 0,
+            line: 0,
+            last: None,
+            allow: Vec::new(),
+            allow_token: None,
+            suppressions: Vec::new(),
+            errors: Vec::new(),
+        }
     }
 
+    /// Parses the input into a module, returning the first syntax error if
+    /// the input is invalid.
     pub fn parse(&mut self) -> Result<Module, ParseError> {
+        let (module, mut errors) = self.parse_with_recovery();
+
+        if errors.is_empty() {
+            Ok(module)
+        } else {
+            Err(errors.swap_remove(0))
+        }
+    }
+
+    /// Parses the input into a module, recovering from syntax errors.
+    ///
+    /// When a syntax error is encountered, the error is recorded and the parser
+    /// skips ahead to the next definition or expression. The returned module
+    /// contains everything that could be parsed, and the errors are returned
+    /// in the order they're produced in.
+    pub fn parse_with_recovery(&mut self) -> (Module, Vec<ParseError>) {
         let start_loc = self.lexer.start_location();
         let mut expressions = Vec::new();
 
@@ -85,10 +184,23 @@ impl Parser {
                 let location =
                     SourceLocation::start_end(&start_loc, &token.location);
 
-                return Ok(Module { expressions, file, location });
+                let suppressions = take(&mut self.suppressions);
+                let module =
+                    Module { expressions, file, location, suppressions };
+
+                return (module, take(&mut self.errors));
             }
 
-            expressions.push(self.top_level_expression(token)?);
+            let start = token.location.clone();
+            let allow = self.allowed_diagnostics(&token);
+
+            match self.top_level_expression(token) {
+                Ok(expr) => {
+                    self.suppress(allow, expr.location());
+                    expressions.push(expr);
+                }
+                Err(err) => self.recover(err, &start, Boundary::Definition),
+            }
         }
     }
 
@@ -1100,7 +1212,7 @@ impl Parser {
         let mut values = Vec::new();
 
         loop {
-            let token = self.require()?;
+            let token = self.require_in_block()?;
 
             if token.kind == TokenKind::CurlyClose {
                 let location =
@@ -1109,7 +1221,16 @@ impl Parser {
                 return Ok(ClassExpressions { values, location });
             }
 
-            values.push(self.class_expression(token)?);
+            let start = token.location.clone();
+            let allow = self.allowed_diagnostics(&token);
+
+            match self.class_expression(token) {
+                Ok(value) => {
+                    self.suppress(allow, value.location());
+                    values.push(value);
+                }
+                Err(err) => self.recover(err, &start, Boundary::ClassMember),
+            }
         }
     }
 
@@ -1120,7 +1241,7 @@ impl Parser {
         let mut values = Vec::new();
 
         loop {
-            let token = self.require()?;
+            let token = self.require_in_block()?;
 
             if token.kind == TokenKind::CurlyClose {
                 let location =
@@ -1129,19 +1250,25 @@ impl Parser {
                 return Ok(ClassExpressions { values, location });
             }
 
-            let node = match token.kind {
-                TokenKind::Let => ClassExpression::DefineField(Box::new(
-                    self.define_field(token)?,
-                )),
-                _ => {
-                    error!(
-                        token.location,
-                        "Expected a 'let', found '{}' instead", token.value
-                    );
-                }
+            let start = token.location.clone();
+            let node = if token.kind == TokenKind::Let {
+                self.define_field(token)
+            } else {
+                Err(ParseError {
+                    message: format!(
+                        "Expected a 'let', found '{}' instead",
+                        token.value
+                    ),
+                    location: token.location,
+                })
             };
 
-            values.push(node);
+            match node {
+                Ok(node) => {
+                    values.push(ClassExpression::DefineField(Box::new(node)))
+                }
+                Err(err) => self.recover(err, &start, Boundary::Field),
+            }
         }
     }
 
@@ -1329,7 +1456,7 @@ impl Parser {
         let mut values = Vec::new();
 
         loop {
-            let token = self.require()?;
+            let token = self.require_in_block()?;
 
             if token.kind == TokenKind::CurlyClose {
                 let location =
@@ -1338,16 +1465,27 @@ impl Parser {
                 return Ok(ImplementationExpressions { values, location });
             }
 
+            let start = token.location.clone();
+            let allow = self.allowed_diagnostics(&token);
             let value = if token.kind == TokenKind::Fn {
-                self.define_method(token)?
+                self.define_method(token)
             } else {
-                error!(
-                    token.location,
-                    "Expected a method, found '{}' instead", token.value
-                );
+                Err(ParseError {
+                    message: format!(
+                        "Expected a method, found '{}' instead",
+                        token.value
+                    ),
+                    location: token.location,
+                })
             };
 
-            values.push(value);
+            match value {
+                Ok(value) => {
+                    self.suppress(allow, value.location());
+                    values.push(value);
+                }
+                Err(err) => self.recover(err, &start, Boundary::Method),
+            }
         }
     }
 
@@ -1358,7 +1496,7 @@ impl Parser {
         let mut values = Vec::new();
 
         loop {
-            let token = self.require()?;
+            let token = self.require_in_block()?;
 
             if token.kind == TokenKind::CurlyClose {
                 let location =
@@ -1367,16 +1505,27 @@ impl Parser {
                 return Ok(ImplementationExpressions { values, location });
             }
 
+            let start = token.location.clone();
+            let allow = self.allowed_diagnostics(&token);
             let value = if token.kind == TokenKind::Fn {
-                self.implement_method(token)?
+                self.implement_method(token)
             } else {
-                error!(
-                    token.location,
-                    "Expected a method, found '{}' instead", token.value
-                );
+                Err(ParseError {
+                    message: format!(
+                        "Expected a method, found '{}' instead",
+                        token.value
+                    ),
+                    location: token.location,
+                })
             };
 
-            values.push(value);
+            match value {
+                Ok(value) => {
+                    self.suppress(allow, value.location());
+                    values.push(value);
+                }
+                Err(err) => self.recover(err, &start, Boundary::Method),
+            }
         }
     }
 
@@ -1407,7 +1556,7 @@ impl Parser {
         let mut values = Vec::new();
 
         loop {
-            let token = self.require()?;
+            let token = self.require_in_block()?;
 
             if token.kind == TokenKind::CurlyClose {
                 let location =
@@ -1416,13 +1565,28 @@ impl Parser {
                 return Ok(TraitExpressions { values, location });
             }
 
-            if token.kind == TokenKind::Move || token.kind == TokenKind::Fn {
-                values.push(self.define_trait_method(token)?);
+            let start = token.location.clone();
+            let allow = self.allowed_diagnostics(&token);
+            let value = if token.kind == TokenKind::Move
+                || token.kind == TokenKind::Fn
+            {
+                self.define_trait_method(token)
             } else {
-                error!(
-                    token.location,
-                    "Expected a method, found '{}' instead", token.value
-                );
+                Err(ParseError {
+                    message: format!(
+                        "Expected a method, found '{}' instead",
+                        token.value
+                    ),
+                    location: token.location,
+                })
+            };
+
+            match value {
+                Ok(value) => {
+                    self.suppress(allow, value.location());
+                    values.push(value);
+                }
+                Err(err) => self.recover(err, &start, Boundary::Method),
             }
         }
     }
@@ -1479,7 +1643,7 @@ impl Parser {
         let mut values = Vec::new();
 
         loop {
-            let token = self.require()?;
+            let token = self.require_in_block()?;
 
             if token.kind == TokenKind::CurlyClose {
                 let location =
@@ -1488,7 +1652,16 @@ impl Parser {
                 return Ok(Expressions { values, location });
             }
 
-            values.push(self.expression(token)?);
+            let start = token.location.clone();
+            let allow = self.allowed_diagnostics(&token);
+
+            match self.expression(token) {
+                Ok(value) => {
+                    self.suppress(allow, value.location());
+                    values.push(value);
+                }
+                Err(err) => self.recover(err, &start, Boundary::Expression),
+            }
         }
     }
 
@@ -2947,20 +3120,182 @@ impl Parser {
     }
 
     fn next(&mut self) -> Token {
+        if let Some(token) = self.peeked.take() {
+            self.line = *token.location.line_range.end();
+            return token;
+        }
+
+        loop {
+            let token = self.lexer.next_token();
+
+            match token.kind {
+                TokenKind::Comment => self.allow_comment(token),
+                TokenKind::Whitespace => {}
+                _ => {
+                    if !self.allow.is_empty() {
+                        self.allow_token = Some((
+                            token.location.clone(),
+                            take(&mut self.allow),
+                        ));
+                    }
+
+                    self.last = match token.kind {
+                        TokenKind::CurlyClose
+                        | TokenKind::Import
+                        | TokenKind::Class
+                        | TokenKind::Implement
+                        | TokenKind::Trait
+                        | TokenKind::Fn
+                        | TokenKind::Let
+                        | TokenKind::Case => Some(token.clone()),
+                        _ => None,
+                    };
+
+                    self.line = *token.location.line_range.end();
+                    return token;
+                }
+            }
+        }
+    }
+
+    /// Processes a comment in the form `# inko:allow(ID, ...)`.
+    ///
+    /// Such comments must be placed on their own line, and silence the
+    /// diagnostics for the definition or expression that follows them.
+    fn allow_comment(&mut self, token: Token) {
+        if *token.location.line_range.start() == self.line {
+            return;
+        }
+
+        let Some(ids) = token
+            .value
+            .strip_prefix("inko:allow(")
+            .and_then(|v| v.trim_end().strip_suffix(')'))
+        else {
+            return;
+        };
+
+        for id in ids.split(',').map(|v| v.trim()).filter(|v| !v.is_empty()) {
+            self.allow.push((id.to_string(), token.location.clone()));
+        }
+    }
+
+    /// Records a syntax error, then skips tokens until reaching the start of
+    /// the next definition or expression, as indicated by `boundary`.
+    ///
+    /// Nested blocks are skipped entirely. The closing curly brace of the
+    /// surrounding block isn't consumed, such that the caller can still parse
+    /// the end of the block.
+    fn recover(
+        &mut self,
+        error: ParseError,
+        start: &SourceLocation,
+        boundary: Boundary,
+    ) {
+        let in_block = boundary != Boundary::Definition;
+
+        // If the error is produced for a token that ends the surrounding block
+        // or starts the next definition (e.g. because of a missing closing
+        // curly brace), we retain the token so we can continue parsing from
+        // it.
+        if self.peeked.is_none() && error.location != *start {
+            if let Some(token) = self.last.take() {
+                if token.location == error.location
+                    && ((in_block && token.kind == TokenKind::CurlyClose)
+                        || (boundary != Boundary::Expression
+                            && boundary.starts_at(&token)))
+                {
+                    self.peeked = Some(token);
+                    self.error(error);
+                    return;
+                }
+            }
+        }
+
+        let line = self.line;
+        let mut depth = 0_usize;
+
+        self.error(error);
+
         loop {
-            let token =
-                self.peeked.take().unwrap_or_else(|| self.lexer.next_token());
+            let token = self.peek();
+            let new_line = *token.location.line_range.start() > line;
 
             match token.kind {
-                TokenKind::Comment | TokenKind::Whitespace => {}
-                _ => return token,
+                TokenKind::Null => return,
+                TokenKind::CurlyClose if depth == 0 && in_block => return,
+                // Definitions start at the start of a line, so we don't need
+                // to track nested blocks for these. This ensures we can
+                // continue parsing even if a curly brace is never closed.
+                _ if boundary == Boundary::Definition
+                    && new_line
+                    && boundary.starts_at(token) =>
+                {
+                    return
+                }
+                _ if depth == 0 && new_line && boundary.starts_at(token) => {
+                    return
+                }
+                TokenKind::CurlyOpen
+                | TokenKind::ParenOpen
+                | TokenKind::BracketOpen => depth += 1,
+                TokenKind::CurlyClose
+                | TokenKind::ParenClose
+                | TokenKind::BracketClose => {
+                    depth = depth.saturating_sub(1);
+                }
+                _ => {}
+            }
+
+            self.next();
+        }
+    }
+
+    /// Records a syntax error, unless an error is already produced for the
+    /// same location.
+    fn error(&mut self, error: ParseError) {
+        if self.errors.last().map_or(true, |e| e.location != error.location) {
+            self.errors.push(error);
+        }
+    }
+
+    /// Returns the diagnostic IDs to silence for the definition or expression
+    /// starting with the given token.
+    fn allowed_diagnostics(
+        &mut self,
+        token: &Token,
+    ) -> Vec<(String, SourceLocation)> {
+        match self.allow_token.take() {
+            Some((loc, ids)) if loc == token.location => ids,
+            val => {
+                self.allow_token = val;
+                Vec::new()
             }
         }
     }
 
+    fn suppress(
+        &mut self,
+        ids: Vec<(String, SourceLocation)>,
+        location: &SourceLocation,
+    ) {
+        for (id, comment) in ids {
+            self.suppressions.push(Suppression {
+                id,
+                comment,
+                location: location.clone(),
+            });
+        }
+    }
+
     fn peek(&mut self) -> &Token {
         if self.peeked.is_none() {
+            // The line is only updated when the token is consumed, such that
+            // it always refers to the last consumed token.
+            let line = self.line;
+
             self.peeked = Some(self.next());
+            self.line = line;
         }
 
         self.peeked.as_ref().unwrap()
@@ -3010,6 +3345,43 @@ impl Parser {
         Ok(token)
     }
 
+    /// Returns the next token in the body of a block.
+    ///
+    /// Tokens that can only be used at the top-level (e.g. `class`) indicate
+    /// the block isn't closed. For such tokens we record an error and return a
+    /// closing curly brace instead, such that the block ends and parsing
+    /// continues from the token.
+    fn require_in_block(&mut self) -> Result<Token, ParseError> {
+        let token = self.require()?;
+
+        if !matches!(
+            token.kind,
+            TokenKind::Import
+                | TokenKind::Class
+                | TokenKind::Implement
+                | TokenKind::Trait
+        ) {
+            return Ok(token);
+        }
+
+        let location = token.location.clone();
+
+        self.error(ParseError {
+            message: format!(
+                "Expected a '}}', found '{}' instead",
+                token.value
+            ),
+            location: location.clone(),
+        });
+
+        self.peeked = Some(token);
+        Ok(Token {
+            kind: TokenKind::CurlyClose,
+            value: "}".to_string(),
+            location,
+        })
+    }
+
     fn expect(&mut self, kind: TokenKind) -> Result<Token, ParseError> {
         let token = self.require()?;
 
@@ -3172,7 +3544,8 @@ mod tests {
             Module {
                 expressions: Vec::new(),
                 file: PathBuf::from("test.inko"),
-                location: cols(1, 1)
+                location: cols(1, 1),
+                suppressions: Vec::new(),
             }
         );
 
@@ -3181,7 +3554,8 @@ mod tests {
             Module {
                 expressions: Vec::new(),
                 file: PathBuf::from("test.inko"),
-                location: cols(1, 2)
+                location: cols(1, 2),
+                suppressions: Vec::new(),
             }
         );
 
@@ -3190,11 +3564,117 @@ mod tests {
             Module {
                 expressions: Vec::new(),
                 file: PathBuf::from("test.inko"),
-                location: location(1..=2, 1..=2)
+                location: location(1..=2, 1..=2),
+                suppressions: Vec::new(),
             }
         );
     }
 
+    #[track_caller]
+    fn errors(input: &str) -> (Module, Vec<SourceLocation>) {
+        let (module, errors) = parser(input).parse_with_recovery();
+
+        (module, errors.into_iter().map(|e| e.location).collect())
+    }
+
+    #[test]
+    fn test_recover_from_invalid_definitions() {
+        let (module, errors) = errors("10\nfn a {}\nclass A {\nfn b(\n}");
+
+        assert_eq!(errors, vec![cols(1, 2), location(5..=5, 1..=1)]);
+        assert_eq!(module.expressions.len(), 2);
+        assert!(matches!(
+            module.expressions[0],
+            TopLevelExpression::DefineMethod(_)
+        ));
+        assert!(matches!(
+            module.expressions[1],
+            TopLevelExpression::DefineClass(_)
+        ));
+    }
+
+    #[test]
+    fn test_recover_from_invalid_class_members() {
+        let (module, errors) = errors("class A {\n  10\n  fn a {}\n}");
+
+        assert_eq!(errors, vec![location(2..=2, 3..=4)]);
+
+        let TopLevelExpression::DefineClass(node) = &module.expressions[0]
+        else {
+            panic!("expected a class");
+        };
+
+        assert_eq!(node.body.values.len(), 1);
+    }
+
+    #[test]
+    fn test_recover_from_invalid_expressions() {
+        let (module, errors) =
+            errors("fn a {\n  foo(1 2)\n  10\n  (20\n}\nfn b {}");
+
+        assert_eq!(
+            errors,
+            vec![location(2..=2, 9..=9), location(5..=5, 1..=1)]
+        );
+        assert_eq!(module.expressions.len(), 2);
+
+        let TopLevelExpression::DefineMethod(node) = &module.expressions[0]
+        else {
+            panic!("expected a method");
+        };
+
+        assert_eq!(node.body.as_ref().unwrap().values.len(), 1);
+    }
+
+    #[test]
+    fn test_recover_from_unclosed_blocks() {
+        let (module, errors) = errors("class A {\n  fn a {\nclass B {}");
+
+        assert_eq!(errors, vec![location(3..=3, 1..=5)]);
+        assert_eq!(module.expressions.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_returns_first_error() {
+        let error =
+            parser("10\n20\nfn a {\n  foo(1 2)\n}").parse().unwrap_err();
+
+        assert_eq!(error.location, cols(1, 2));
+    }
+
+    #[test]
+    fn test_suppressions() {
+        let input = "\
+# inko:allow(unreachable)
+fn a {}
+
+class A {
+  # The docs.
+  # inko:allow(foo, bar)
+  fn b {
+    # inko:allow(unreachable)
+    return
+
+    10 # inko:allow(unreachable)
+    20
+  }
+}
+
+# inko:allow(unreachable) is ignored
+fn c {}
+";
+        let suppressions = parse(input).suppressions;
+        let ids: Vec<_> = suppressions.iter().map(|s| s.id.as_str()).collect();
+
+        assert_eq!(ids, vec!["unreachable", "unreachable", "foo", "bar"]);
+        assert_eq!(suppressions[0].comment, location(1..=1, 1..=25));
+        assert_eq!(suppressions[0].location, location(2..=2, 1..=7));
+        assert_eq!(suppressions[1].location, location(9..=9, 5..=10));
+        assert_eq!(suppressions[2].comment, location(6..=6, 3..=24));
+        assert_eq!(suppressions[2].location, location(7..=13, 3..=3));
+        assert_eq!(suppressions[3].location, location(7..=13, 3..=3));
+    }
+
     #[test]
     fn test_imports() {
         assert_eq!(
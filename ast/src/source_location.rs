@@ -31,6 +31,14 @@ impl SourceLocation {
     pub fn line_column(&self) -> (usize, usize) {
         (*self.line_range.start(), *self.column_range.start())
     }
+
+    /// Returns `true` if the start of `other` is located within `self`.
+    pub fn contains(&self, other: &Self) -> bool {
+        let start = other.line_column();
+
+        start >= self.line_column()
+            && start <= (*self.line_range.end(), *self.column_range.end())
+    }
 }
 
 impl fmt::Debug for SourceLocation {
@@ -63,3 +71,23 @@ impl Ord for SourceLocation {
         ord
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contains() {
+        let loc = SourceLocation::start_end(
+            &SourceLocation::new(2..=2, 3..=3),
+            &SourceLocation::new(4..=4, 1..=1),
+        );
+
+        assert!(loc.contains(&SourceLocation::new(2..=2, 3..=4)));
+        assert!(loc.contains(&SourceLocation::new(3..=3, 1..=1)));
+        assert!(loc.contains(&SourceLocation::new(4..=4, 1..=1)));
+        assert!(!loc.contains(&SourceLocation::new(2..=2, 2..=2)));
+        assert!(!loc.contains(&SourceLocation::new(4..=4, 2..=2)));
+        assert!(!loc.contains(&SourceLocation::new(1..=1, 5..=5)));
+    }
+}
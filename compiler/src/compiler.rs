@@ -1,5 +1,8 @@
 use crate::config::{BuildDirectories, Output};
 use crate::config::{Config, SOURCE, SOURCE_EXT, TESTS};
+use crate::coverage::{map_path, source_lines, Counters, SourceLines};
+use crate::diagnostics::DiagnosticId;
+use crate::docs;
 use crate::hir;
 use crate::linker::link;
 use crate::llvm;
@@ -16,11 +19,14 @@ use crate::type_check::define_types::{
     InsertPrelude,
 };
 use crate::type_check::expressions::{DefineConstants, Expressions};
-use crate::type_check::imports::{CollectExternImports, DefineImportedTypes};
+use crate::type_check::imports::{
+    CheckUnusedImports, CollectExternImports, DefineImportedTypes,
+};
 use crate::type_check::methods::{
-    CheckMainMethod, DefineMethods, DefineModuleMethodNames,
-    ImplementTraitMethods,
+    CheckMainMethod, CheckUnusedMethods, DefineMethods,
+    DefineModuleMethodNames, ImplementTraitMethods,
 };
+use std::collections::HashSet;
 use std::env::current_dir;
 use std::ffi::OsStr;
 use std::fs::write;
@@ -36,7 +42,7 @@ pub enum CompileError {
 }
 
 pub struct Compiler {
-    state: State,
+    pub(crate) state: State,
 }
 
 impl Compiler {
@@ -55,7 +61,12 @@ impl Compiler {
 
             vec![(self.module_name_from_path(&file), file)]
         } else {
-            self.all_source_modules()?
+            let config = &self.state.config;
+
+            self.source_modules(vec![
+                config.source.clone(),
+                config.tests.clone(),
+            ])?
         };
 
         let ast = ModulesParser::new(&mut self.state).run(input);
@@ -64,17 +75,86 @@ impl Compiler {
         self.compile_mir(hir).map(|_| ())
     }
 
+    /// Parses and type-checks a source file and its dependencies, returning
+    /// the resulting HIR.
+    ///
+    /// Unlike `check()`, the HIR is returned even if syntax or type errors are
+    /// produced. This allows the language server to inspect the parts of the
+    /// program that are valid.
+    pub(crate) fn analyse(
+        &mut self,
+        file: PathBuf,
+    ) -> Result<Vec<hir::Module>, CompileError> {
+        let input = vec![(self.module_name_from_path(&file), file)];
+        let ast = ModulesParser::new(&mut self.state).run(input);
+        let parsed = self.state.diagnostics.iter().count();
+        let mut hir = hir::LowerToHir::run_all(&mut self.state, ast);
+
+        // The parser recovers from syntax errors, so the ASTs only contain
+        // valid code. Other errors (e.g. for missing modules) still prevent
+        // further analysis, as they do for `check()`.
+        if self.state.diagnostics.iter().enumerate().any(|(index, diag)| {
+            diag.is_error()
+                && (index >= parsed || diag.id() != DiagnosticId::InvalidSyntax)
+        }) {
+            return Err(CompileError::Invalid);
+        }
+
+        self.check_types(&mut hir);
+        Ok(hir)
+    }
+
+    /// Generates documentation for the public symbols of the project's source
+    /// modules, writing the results to the given directory.
+    pub fn document(&mut self, directory: &Path) -> Result<(), CompileError> {
+        let source = self.state.config.source.clone();
+        let input = self.source_modules(vec![source.clone()])?;
+        let names: HashSet<_> =
+            input.iter().map(|(name, _)| name.clone()).collect();
+        let ast = ModulesParser::new(&mut self.state).run(input);
+        let mut hir = self.compile_hir(ast)?;
+
+        if !self.check_types(&mut hir) {
+            return Err(CompileError::Invalid);
+        }
+
+        // Dependencies are type-checked as well, but we only document the
+        // modules of the project itself.
+        hir.retain(|m| names.contains(m.module_id.name(&self.state.db)));
+        docs::generate(&self.state.db, &hir, &source, directory)
+            .map_err(CompileError::Internal)
+    }
+
     pub fn build(
         &mut self,
         file: Option<PathBuf>,
     ) -> Result<PathBuf, CompileError> {
         let file = self.main_module_path(file)?;
         let main_mod = self.state.db.main_module().unwrap().clone();
-        let ast = ModulesParser::new(&mut self.state)
-            .run(vec![(main_mod, file.clone())]);
+        let mut input = vec![(main_mod.clone(), file.clone())];
+
+        // When collecting code coverage, we also process the source modules
+        // that aren't imported, such that their lines are included in the
+        // coverage report. A source module with the same name as the main
+        // module can't be imported, so we skip it.
+        if self.state.config.coverage {
+            let source = self.state.config.source.clone();
+
+            for (name, path) in self.source_modules(vec![source])? {
+                if name != main_mod && path != file {
+                    input.push((name, path));
+                }
+            }
+        }
 
+        let ast = ModulesParser::new(&mut self.state).run(input);
         let hir = self.compile_hir(ast)?;
         let mut mir = self.compile_mir(hir)?;
+        let lines = if self.state.config.coverage {
+            Some(source_lines(&self.state.db, &self.state.config, &mir))
+        } else {
+            None
+        };
 
         self.optimise_mir(&mut mir);
 
@@ -86,7 +166,7 @@ impl Compiler {
             self.write_dot(&dirs, &mir)?;
         }
 
-        self.compile_machine_code(&dirs, &mir, file)
+        self.compile_machine_code(&dirs, &mir, file, lines)
     }
 
     pub fn print_diagnostics(&self) {
@@ -188,6 +268,8 @@ impl Compiler {
             && ImplementTraitMethods::run_all(state, modules)
             && DefineConstants::run_all(state, modules)
             && Expressions::run_all(state, modules)
+            && CheckUnusedImports::run_all(state, modules)
+            && CheckUnusedMethods::run_all(state, modules)
     }
 
     fn optimise_mir(&mut self, mir: &mut Mir) {
@@ -227,6 +309,7 @@ impl Compiler {
         directories: &BuildDirectories,
         mir: &Mir,
         main_file: PathBuf,
+        coverage: Option<SourceLines>,
     ) -> Result<PathBuf, CompileError> {
         let exe = match &self.state.config.output {
             Output::Derive => {
@@ -241,9 +324,23 @@ impl Compiler {
             Output::Path(path) => path.clone(),
         };
 
-        let objects =
-            llvm::passes::Compile::run_all(&self.state, directories, mir)
-                .map_err(CompileError::Internal)?;
+        let counters = if let Some(lines) = coverage {
+            let counters =
+                Counters::new(&self.state.db, &self.state.config, mir, lines);
+
+            counters.write(&map_path(&exe)).map_err(CompileError::Internal)?;
+            Some(counters)
+        } else {
+            None
+        };
+
+        let objects = llvm::passes::Compile::run_all(
+            &self.state,
+            directories,
+            mir,
+            counters.as_ref(),
+        )
+        .map_err(CompileError::Internal)?;
 
         link(&self.state, &exe, &objects).map_err(CompileError::Internal)?;
         Ok(exe)
@@ -273,22 +370,14 @@ impl Compiler {
             .unwrap_or_else(ModuleName::main)
     }
 
-    fn all_source_modules(
+    fn source_modules(
         &self,
+        mut paths: Vec<PathBuf>,
     ) -> Result<Vec<(ModuleName, PathBuf)>, CompileError> {
         let mut modules = Vec::new();
-        let mut paths = Vec::new();
         let src_ext = OsStr::new(SOURCE_EXT);
-        let source = &self.state.config.source;
-        let tests = &self.state.config.tests;
 
-        if source.is_dir() {
-            paths.push(source.clone());
-        }
-
-        if tests.is_dir() {
-            paths.push(tests.clone());
-        }
+        paths.retain(|p| p.is_dir());
 
         while let Some(path) = paths.pop() {
             let iter = path.read_dir().map_err(|err| {
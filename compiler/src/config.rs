@@ -1,31 +1,40 @@
 //! Configuration for the compiler.
-use crate::presenters::{JSONPresenter, Presenter, TextPresenter};
+use crate::diagnostics::{DiagnosticId, Level};
+use crate::presenters::{
+    JSONPresenter, Presenter, SARIFPresenter, TextPresenter,
+};
+use crate::settings::{self, Setting};
 use crate::target::Target;
+use std::collections::HashMap;
 use std::env;
-use std::fs::create_dir_all;
+use std::fs::{create_dir_all, read_to_string};
 use std::path::{Path, PathBuf};
+use std::thread::available_parallelism;
 use types::module_name::ModuleName;
 
 /// The extension to use for source files.
 pub const SOURCE_EXT: &str = "inko";
 
 /// The name of the module to compile if no explicit file/module is provided.
-pub(crate) const MAIN_MODULE: &str = "main";
+pub const MAIN_MODULE: &str = "main";
 
 /// The name of the directory containing a project's source code.
-pub(crate) const SOURCE: &str = "src";
+pub const SOURCE: &str = "src";
 
 /// The name of the directory containing third-party dependencies.
 pub const DEP: &str = "dep";
 
 /// The name of the directory containing a project's unit tests.
-pub(crate) const TESTS: &str = "test";
+pub const TESTS: &str = "test";
 
 /// The name of the module that runs tests.
 const MAIN_TEST_MODULE: &str = "inko-tests";
 
 /// The name of the directory to store build files in.
-const BUILD: &str = "build";
+pub const BUILD: &str = "build";
+
+/// The name of the file containing a project's compiler settings.
+pub const PROJECT_FILE: &str = "inko.toml";
 
 fn create_directory(path: &Path) -> Result<(), String> {
     if path.is_dir() {
@@ -44,6 +53,10 @@ pub(crate) struct BuildDirectories {
     /// The directory to store object files in.
     pub(crate) objects: PathBuf,
 
+    /// The path to the manifest of object files that can be reused between
+    /// compilations.
+    pub(crate) object_cache: PathBuf,
+
     /// The directory to store LLVM IR files in.
     pub(crate) llvm_ir: PathBuf,
 
@@ -62,11 +75,12 @@ impl BuildDirectories {
             .map_or(config.build.clone(), |p| config.build.join(p));
 
         let objects = build.join("objects");
+        let object_cache = objects.join("cache");
         let llvm_ir = build.join("llvm");
         let dot = build.join("dot");
         let bin = build.clone();
 
-        BuildDirectories { build, objects, llvm_ir, bin, dot }
+        BuildDirectories { build, objects, object_cache, llvm_ir, bin, dot }
     }
 
     pub(crate) fn create(&self) -> Result<(), String> {
@@ -138,7 +152,7 @@ pub struct Config {
     pub runtime: PathBuf,
 
     /// The directory containing the project's source code.
-    pub(crate) source: PathBuf,
+    pub source: PathBuf,
 
     /// The directory containing the project's dependencies.
     pub dependencies: PathBuf,
@@ -178,6 +192,31 @@ pub struct Config {
 
     /// If C libraries should be linked statically or not.
     pub static_linking: bool,
+
+    /// If the generated code should collect code coverage data.
+    pub coverage: bool,
+
+    /// The program to use for linking executables.
+    ///
+    /// If no program is specified, `cc` is used when compiling for the native
+    /// target, and `clang` is used when compiling for any other target.
+    pub linker: Option<PathBuf>,
+
+    /// The directory containing the headers and libraries of the target
+    /// platform.
+    pub sysroot: Option<PathBuf>,
+
+    /// Extra arguments to pass to the linker.
+    pub linker_arguments: Vec<String>,
+
+    /// The number of threads to use when generating code.
+    pub jobs: usize,
+
+    /// The levels to report specific warnings at.
+    pub(crate) diagnostic_levels: HashMap<DiagnosticId, Level>,
+
+    /// If warnings without a specific level should be reported as errors.
+    pub deny_warnings: bool,
 }
 
 impl Config {
@@ -202,6 +241,13 @@ impl Config {
             verify_llvm: false,
             write_llvm: false,
             static_linking: false,
+            coverage: false,
+            linker: None,
+            sysroot: None,
+            linker_arguments: Vec::new(),
+            jobs: available_parallelism().map(|v| v.get()).unwrap_or(1),
+            diagnostic_levels: HashMap::new(),
+            deny_warnings: false,
         }
     }
 
@@ -219,11 +265,18 @@ impl Config {
         self.sources.push(path.canonicalize().unwrap_or(path));
     }
 
+    /// Returns the source directories added in addition to the standard
+    /// library, such as those included using the project file.
+    pub fn include_directories(&self) -> Vec<PathBuf> {
+        self.sources.iter().filter(|p| **p != self.std).cloned().collect()
+    }
+
     pub fn set_presenter(&mut self, format: &str) -> Result<(), String> {
         self.presenter = match format {
             "text" => Box::new(TextPresenter::with_colors()),
             "plain" => Box::new(TextPresenter::without_colors()),
             "json" => Box::new(JSONPresenter::new()),
+            "sarif" => Box::new(SARIFPresenter::new()),
             _ => return Err(format!("The presenter {:?} is invalid", format)),
         };
 
@@ -255,6 +308,117 @@ impl Config {
         Ok(())
     }
 
+    pub fn set_jobs(&mut self, value: &str) -> Result<(), String> {
+        self.jobs = match value.parse::<usize>() {
+            Ok(val) if val > 0 => val,
+            _ => {
+                return Err(format!(
+                    "The number of jobs '{}' is invalid, a value greater \
+                    than zero is expected",
+                    value
+                ))
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Sets the level to report the diagnostic with the given ID at.
+    pub fn set_diagnostic_level(
+        &mut self,
+        id: &str,
+        level: Level,
+    ) -> Result<(), String> {
+        let id = DiagnosticId::from_str(id)
+            .ok_or_else(|| format!("The diagnostic ID '{}' is invalid", id))?;
+
+        if !id.is_warning() {
+            return Err(format!(
+                "The diagnostic '{}' is an error, and only the level of \
+                warnings can be changed",
+                id
+            ));
+        }
+
+        self.diagnostic_levels.insert(id, level);
+        Ok(())
+    }
+
+    /// Loads the settings from the project file in the current working
+    /// directory, if there is such a file.
+    ///
+    /// This method should be called before applying any command-line options,
+    /// such that these options override the settings from the project file.
+    pub fn load_project_file(&mut self) -> Result<(), String> {
+        let root = env::current_dir().unwrap_or_else(|_| PathBuf::new());
+        let path = root.join(PROJECT_FILE);
+
+        if !path.is_file() {
+            return Ok(());
+        }
+
+        let input = read_to_string(&path).map_err(|err| {
+            format!("Failed to read {}: {}", path.display(), err)
+        })?;
+
+        self.apply_settings(&root, &input)
+            .map_err(|err| format!("{}:{}", path.display(), err))
+    }
+
+    fn apply_settings(
+        &mut self,
+        root: &Path,
+        input: &str,
+    ) -> Result<(), String> {
+        for setting in settings::parse(input)? {
+            self.apply_setting(root, &setting)
+                .map_err(|err| format!("{}: {}", setting.line, err))?;
+        }
+
+        Ok(())
+    }
+
+    fn apply_setting(
+        &mut self,
+        root: &Path,
+        setting: &Setting,
+    ) -> Result<(), String> {
+        let value = &setting.value;
+
+        match (setting.section.as_str(), setting.key.as_str()) {
+            ("build", "opt") => self.set_opt(value.as_str()?)?,
+            ("build", "target") => self.set_target(value.as_str()?)?,
+            ("build", "jobs") => self.set_jobs(&value.as_int()?.to_string())?,
+            ("build", "static") => self.static_linking = value.as_bool()?,
+            ("build", "output") => {
+                self.output = Output::File(value.as_str()?.to_string())
+            }
+            ("build", "linker") => {
+                self.linker = Some(PathBuf::from(value.as_str()?))
+            }
+            ("build", "sysroot") => {
+                self.sysroot = Some(root.join(value.as_str()?))
+            }
+            ("build", "linker-args") => {
+                self.linker_arguments =
+                    value.as_strings()?.into_iter().map(String::from).collect()
+            }
+            ("build", "include") => {
+                for path in value.as_strings()? {
+                    self.add_source_directory(root.join(path));
+                }
+            }
+            (section, key) => {
+                return Err(format!(
+                    "the setting '{}.{}' isn't supported",
+                    section, key
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn main_source_module(&self) -> PathBuf {
         let mut main_file = self.source.join(MAIN_MODULE);
 
@@ -279,3 +443,99 @@ impl Default for Config {
         cfg
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_settings() {
+        let mut config = Config::new();
+        let root = Path::new("/tmp");
+        let input = "\
+[build]
+opt = 'aggressive'
+static = true
+jobs = 3
+output = 'hello'
+include = ['vendor']
+linker = 'aarch64-linux-gnu-gcc'
+sysroot = 'sysroot'
+linker-args = ['-s']
+";
+
+        assert_eq!(config.apply_settings(root, input), Ok(()));
+        assert!(matches!(config.opt, Opt::Aggressive));
+        assert!(config.static_linking);
+        assert_eq!(config.jobs, 3);
+        assert!(matches!(config.output, Output::File(ref v) if v == "hello"));
+        assert_eq!(config.sources, vec![root.join("vendor")]);
+        assert_eq!(config.linker, Some(PathBuf::from("aarch64-linux-gnu-gcc")));
+        assert_eq!(config.sysroot, Some(root.join("sysroot")));
+        assert_eq!(config.linker_arguments, vec!["-s".to_string()]);
+    }
+
+    #[test]
+    fn test_include_directories() {
+        let mut config = Config::default();
+
+        config.add_source_directory(PathBuf::from("/foo/vendor"));
+        assert_eq!(
+            config.include_directories(),
+            vec![PathBuf::from("/foo/vendor")]
+        );
+    }
+
+    #[test]
+    fn test_set_diagnostic_level() {
+        let mut config = Config::new();
+
+        assert_eq!(
+            config.set_diagnostic_level("unreachable", Level::Deny),
+            Ok(())
+        );
+        assert_eq!(
+            config.diagnostic_levels.get(&DiagnosticId::Unreachable),
+            Some(&Level::Deny)
+        );
+        assert_eq!(
+            config.set_diagnostic_level("foo", Level::Allow),
+            Err("The diagnostic ID 'foo' is invalid".to_string())
+        );
+        assert_eq!(
+            config.set_diagnostic_level("invalid-type", Level::Allow),
+            Err("The diagnostic 'invalid-type' is an error, and only the \
+                level of warnings can be changed"
+                .to_string())
+        );
+    }
+
+    #[test]
+    fn test_apply_settings_with_invalid_settings() {
+        let mut config = Config::new();
+        let root = Path::new("/tmp");
+
+        assert_eq!(
+            config.apply_settings(root, "[build]\nopt = 'fast'"),
+            Err("2: The optimisation level 'fast' isn't supported".to_string())
+        );
+        assert_eq!(
+            config.apply_settings(root, "[build]\njobs = 0"),
+            Err("2: The number of jobs '0' is invalid, a value greater \
+                than zero is expected"
+                .to_string())
+        );
+        assert_eq!(
+            config.apply_settings(root, "[build]\nstatic = 'yes'"),
+            Err("2: expected a boolean".to_string())
+        );
+        assert_eq!(
+            config.apply_settings(root, "[run]\nfoo = 1"),
+            Err("2: the setting 'run.foo' isn't supported".to_string())
+        );
+        assert_eq!(
+            config.apply_settings(root, "[build"),
+            Err("1: expected ']'".to_string())
+        );
+    }
+}
@@ -0,0 +1,483 @@
+//! Collecting and reporting code coverage data.
+//!
+//! When code coverage is enabled, the generated code increments a counter
+//! whenever it starts executing code on a source line. Each module defines an
+//! array of such counters, and the compiler writes a map that describes which
+//! source line each counter belongs to.
+//!
+//! Upon termination, the runtime writes the values of these counters to a file
+//! in the directory specified by the `INKO_COVERAGE_DIR` environment variable,
+//! one file per OS process. These files are then combined with the map to
+//! produce a report in the lcov format.
+//!
+//! Unused methods are removed before generating code, and thus don't have any
+//! counters. To still report the lines of such methods as not executed, the
+//! map also includes the lines of all methods of the project's source modules,
+//! collected before unused methods are removed.
+use crate::config::Config;
+use crate::json::Json;
+use crate::mir::{BlockId, Method, Mir};
+use crate::symbol_names::SYMBOL_PREFIX;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fs::{read_dir, read_to_string, write};
+use std::path::{Path, PathBuf};
+use types::{Database, ModuleId};
+
+/// The name of the environment variable that specifies the directory to write
+/// the counters to.
+pub const DIRECTORY_VARIABLE: &str = "INKO_COVERAGE_DIR";
+
+/// Returns the path of the coverage map for the given executable.
+pub fn map_path(executable: &Path) -> PathBuf {
+    let mut path = executable.as_os_str().to_owned();
+
+    path.push(".coverage.json");
+    PathBuf::from(path)
+}
+
+/// Returns the blocks of a method, in the order in which they're lowered into
+/// machine code.
+pub(crate) fn blocks(method: &Method) -> Vec<BlockId> {
+    let mut queue = VecDeque::new();
+    let mut visited = HashSet::new();
+    let mut blocks = Vec::new();
+
+    queue.push_back(method.body.start_id);
+    visited.insert(method.body.start_id);
+
+    while let Some(id) = queue.pop_front() {
+        blocks.push(id);
+
+        for &child in &method.body.blocks[id.0].successors {
+            if visited.insert(child) {
+                queue.push_back(child);
+            }
+        }
+    }
+
+    blocks
+}
+
+/// The lines of the methods defined in each source file.
+pub(crate) type SourceLines = BTreeMap<PathBuf, BTreeSet<usize>>;
+
+/// Returns the lines of all methods that are defined in the project's source
+/// directory.
+///
+/// This function must be called before unused methods are removed, such that
+/// the lines of these methods are included.
+pub(crate) fn source_lines(
+    db: &Database,
+    config: &Config,
+    mir: &Mir,
+) -> SourceLines {
+    let mut files = SourceLines::new();
+
+    for method in mir.methods.values() {
+        let path = method.id.module(db).file(db);
+
+        if !path.starts_with(&config.source) {
+            continue;
+        }
+
+        let lines = files.entry(path).or_default();
+
+        for block in blocks(method) {
+            for ins in &method.body.blocks[block.0].instructions {
+                lines.insert(mir.location(ins.location()).line_column().0);
+            }
+        }
+    }
+
+    files
+}
+
+/// The counters of a single module.
+pub(crate) struct ModuleCounters {
+    /// The name of the global that stores the counters.
+    pub(crate) symbol: String,
+
+    /// The file index and line number of each counter.
+    lines: Vec<(usize, usize)>,
+
+    /// The counter indexes per file index and line number.
+    indexes: HashMap<(usize, usize), usize>,
+}
+
+impl ModuleCounters {
+    pub(crate) fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub(crate) fn index(&self, file: usize, line: usize) -> Option<usize> {
+        self.indexes.get(&(file, line)).cloned()
+    }
+}
+
+/// The coverage counters of all modules.
+pub(crate) struct Counters {
+    /// The source files that are instrumented.
+    files: Vec<PathBuf>,
+
+    /// The lines of the methods defined in each source file, including those
+    /// of methods that aren't used.
+    lines: SourceLines,
+
+    /// The counters per module, in the order in which they're registered with
+    /// the runtime.
+    pub(crate) modules: Vec<(ModuleId, ModuleCounters)>,
+}
+
+impl Counters {
+    /// Assigns counters to the lines of all methods that are defined in the
+    /// project's source directory.
+    ///
+    /// The `lines` argument contains the lines of all methods, as returned by
+    /// `source_lines()`.
+    pub(crate) fn new(
+        db: &Database,
+        config: &Config,
+        mir: &Mir,
+        lines: SourceLines,
+    ) -> Counters {
+        let mut files: Vec<_> = lines.keys().cloned().collect();
+        let mut file_indexes: HashMap<_, _> = files
+            .iter()
+            .enumerate()
+            .map(|(index, path)| (path.clone(), index))
+            .collect();
+        let mut modules = Vec::new();
+
+        for module in mir.modules.values() {
+            let mut counters = Vec::new();
+            let mut indexes = HashMap::new();
+
+            for id in &module.methods {
+                let method = &mir.methods[id];
+                let path = method.id.module(db).file(db);
+
+                if !path.starts_with(&config.source) {
+                    continue;
+                }
+
+                let file =
+                    *file_indexes.entry(path.clone()).or_insert_with(|| {
+                        files.push(path);
+                        files.len() - 1
+                    });
+
+                for block in blocks(method) {
+                    for ins in &method.body.blocks[block.0].instructions {
+                        let (line, _) =
+                            mir.location(ins.location()).line_column();
+
+                        indexes.entry((file, line)).or_insert_with(|| {
+                            counters.push((file, line));
+                            counters.len() - 1
+                        });
+                    }
+                }
+            }
+
+            if counters.is_empty() {
+                continue;
+            }
+
+            let symbol = format!(
+                "{}V_{}.$coverage",
+                SYMBOL_PREFIX,
+                module.id.name(db).as_str()
+            );
+
+            modules.push((
+                module.id,
+                ModuleCounters { symbol, lines: counters, indexes },
+            ));
+        }
+
+        Counters { files, lines, modules }
+    }
+
+    pub(crate) fn file_index(&self, path: &Path) -> Option<usize> {
+        self.files.iter().position(|p| p == path)
+    }
+
+    pub(crate) fn get(&self, module: ModuleId) -> Option<&ModuleCounters> {
+        self.modules.iter().find(|(id, _)| *id == module).map(|(_, c)| c)
+    }
+
+    /// Writes the coverage map to the given path.
+    pub(crate) fn write(&self, path: &Path) -> Result<(), String> {
+        let files = self
+            .files
+            .iter()
+            .map(|p| Json::string(p.to_string_lossy()))
+            .collect();
+        let modules = self
+            .modules
+            .iter()
+            .map(|(_, module)| {
+                Json::Array(
+                    module
+                        .lines
+                        .iter()
+                        .map(|&(file, line)| {
+                            Json::Array(vec![
+                                Json::Int(file as i64),
+                                Json::Int(line as i64),
+                            ])
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+        let lines = self
+            .files
+            .iter()
+            .map(|path| {
+                Json::Array(
+                    self.lines
+                        .get(path)
+                        .into_iter()
+                        .flatten()
+                        .map(|&line| Json::Int(line as i64))
+                        .collect(),
+                )
+            })
+            .collect();
+        let map = Json::object(vec![
+            ("files", Json::Array(files)),
+            ("lines", Json::Array(lines)),
+            ("modules", Json::Array(modules)),
+        ]);
+
+        write(path, map.to_string()).map_err(|err| {
+            format!("Failed to write {}: {}", path.display(), err)
+        })
+    }
+}
+
+/// A report of the number of times each line is executed.
+pub struct Report {
+    /// The execution counts per line, per source file.
+    files: BTreeMap<PathBuf, BTreeMap<usize, u64>>,
+}
+
+impl Report {
+    /// Produces a report using the coverage map of an executable, and the
+    /// counters written to the given directory.
+    pub fn new(executable: &Path, directory: &Path) -> Result<Report, String> {
+        let map_path = map_path(executable);
+        let map = read_to_string(&map_path)
+            .map_err(|err| err.to_string())
+            .and_then(|data| Json::parse(&data))
+            .map_err(|err| {
+                format!("Failed to read {}: {}", map_path.display(), err)
+            })?;
+        let paths: Vec<_> = map
+            .get("files")
+            .as_array()
+            .iter()
+            .map(|v| PathBuf::from(v.as_str().unwrap_or("")))
+            .collect();
+        let modules: Vec<Vec<(usize, usize)>> = map
+            .get("modules")
+            .as_array()
+            .iter()
+            .map(|lines| {
+                lines
+                    .as_array()
+                    .iter()
+                    .map(|pair| {
+                        let pair = pair.as_array();
+                        let file = pair.first().and_then(|v| v.as_int());
+                        let line = pair.get(1).and_then(|v| v.as_int());
+
+                        (file.unwrap_or(0) as usize, line.unwrap_or(0) as usize)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut counts: Vec<Vec<u64>> =
+            modules.iter().map(|lines| vec![0; lines.len()]).collect();
+        let entries = read_dir(directory).map_err(|err| {
+            format!("Failed to read {}: {}", directory.display(), err)
+        })?;
+
+        for entry in entries {
+            let path = entry.map_err(|err| err.to_string())?.path();
+            let data = read_to_string(&path).map_err(|err| {
+                format!("Failed to read {}: {}", path.display(), err)
+            })?;
+
+            Report::add_counts(&mut counts, &data).map_err(|err| {
+                format!(
+                    "The counters in {} are invalid: {}",
+                    path.display(),
+                    err
+                )
+            })?;
+        }
+
+        let mut files = BTreeMap::new();
+
+        // Lines without any counters belong to methods that are never used,
+        // and thus are never executed.
+        for (path, lines) in paths.iter().zip(map.get("lines").as_array()) {
+            let counts =
+                files.entry(path.clone()).or_insert_with(BTreeMap::new);
+
+            for line in lines.as_array().iter().filter_map(|v| v.as_int()) {
+                counts.insert(line as usize, 0);
+            }
+        }
+
+        for (lines, counts) in modules.iter().zip(counts) {
+            for (&(file, line), count) in lines.iter().zip(counts) {
+                let Some(path) = paths.get(file) else { continue };
+
+                *files
+                    .entry(path.clone())
+                    .or_insert_with(BTreeMap::new)
+                    .entry(line)
+                    .or_insert(0) += count;
+            }
+        }
+
+        Ok(Report { files })
+    }
+
+    /// Adds the counters written by a single OS process.
+    ///
+    /// Each line contains the space separated counters of a single module, in
+    /// the same order as the modules in the coverage map.
+    fn add_counts(counts: &mut [Vec<u64>], data: &str) -> Result<(), String> {
+        let lines: Vec<_> = data.lines().collect();
+
+        if lines.len() != counts.len() {
+            return Err(format!(
+                "expected {} modules, found {}",
+                counts.len(),
+                lines.len()
+            ));
+        }
+
+        for (module, line) in counts.iter_mut().zip(lines) {
+            let values = line
+                .split_whitespace()
+                .map(|v| v.parse::<u64>().map_err(|err| err.to_string()))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if values.len() != module.len() {
+                return Err(format!(
+                    "expected {} counters, found {}",
+                    module.len(),
+                    values.len()
+                ));
+            }
+
+            for (total, value) in module.iter_mut().zip(values) {
+                *total += value;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of lines that are executed at least once, and the
+    /// total number of instrumented lines.
+    pub fn totals(&self) -> (usize, usize) {
+        self.files.values().fold((0, 0), |(hit, found), lines| {
+            let executed = lines.values().filter(|&&v| v > 0).count();
+
+            (hit + executed, found + lines.len())
+        })
+    }
+
+    /// Returns the report in the lcov tracefile format.
+    pub fn to_lcov(&self) -> String {
+        let mut buf = String::new();
+
+        for (path, lines) in &self.files {
+            let hit = lines.values().filter(|&&v| v > 0).count();
+
+            buf.push_str("TN:\n");
+            buf.push_str(&format!("SF:{}\n", path.display()));
+
+            for (line, count) in lines {
+                buf.push_str(&format!("DA:{},{}\n", line, count));
+            }
+
+            buf.push_str(&format!("LH:{}\n", hit));
+            buf.push_str(&format!("LF:{}\n", lines.len()));
+            buf.push_str("end_of_record\n");
+        }
+
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all};
+
+    #[test]
+    fn test_map_path() {
+        assert_eq!(
+            map_path(Path::new("build/inko-tests")),
+            PathBuf::from("build/inko-tests.coverage.json")
+        );
+    }
+
+    #[test]
+    fn test_add_counts() {
+        let mut counts = vec![vec![0, 0], vec![0]];
+
+        assert!(Report::add_counts(&mut counts, "1 2\n3\n").is_ok());
+        assert!(Report::add_counts(&mut counts, "1 0\n0\n").is_ok());
+        assert_eq!(counts, vec![vec![2, 2], vec![3]]);
+
+        assert!(Report::add_counts(&mut counts, "1 2\n").is_err());
+        assert!(Report::add_counts(&mut counts, "1\n2\n").is_err());
+        assert!(Report::add_counts(&mut counts, "a b\n2\n").is_err());
+    }
+
+    #[test]
+    fn test_report() {
+        let dir = temp_dir().join("inko-coverage-report");
+        let counters = dir.join("counters");
+
+        if dir.exists() {
+            remove_dir_all(&dir).unwrap();
+        }
+
+        create_dir_all(&counters).unwrap();
+        write(
+            dir.join("test.coverage.json"),
+            "{\"files\":[\"/src/b.inko\",\"/src/a.inko\",\"/src/c.inko\"],\
+            \"lines\":[[1,2,5,6],[4],[1]],\
+            \"modules\":[[[0,1],[0,2]],[[1,4],[0,2]]]}",
+        )
+        .unwrap();
+        write(counters.join("1.txt"), "1 0\n2 3\n").unwrap();
+        write(counters.join("2.txt"), "1 0\n0 0\n").unwrap();
+
+        let report = Report::new(&dir.join("test"), &counters).unwrap();
+
+        // Lines 5 and 6 of b.inko and all of c.inko belong to methods that are
+        // never used, and thus don't have any counters.
+        assert_eq!(report.totals(), (3, 6));
+        assert_eq!(
+            report.to_lcov(),
+            "TN:\nSF:/src/a.inko\nDA:4,2\nLH:1\nLF:1\nend_of_record\n\
+            TN:\nSF:/src/b.inko\nDA:1,2\nDA:2,3\nDA:5,0\nDA:6,0\nLH:2\nLF:4\n\
+            end_of_record\n\
+            TN:\nSF:/src/c.inko\nDA:1,0\nLH:0\nLF:1\nend_of_record\n"
+        );
+
+        remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,10 +1,11 @@
 //! Types and methods for producing compiler diagnostics.
 use ast::source_location::SourceLocation;
+use std::collections::HashMap;
 use std::fmt;
 use std::path::PathBuf;
 
 /// The unique ID of a diagnostic.
-#[derive(PartialEq, Eq, Copy, Clone)]
+#[derive(PartialEq, Eq, Copy, Clone, Hash)]
 pub(crate) enum DiagnosticId {
     DuplicateSymbol,
     InvalidAssign,
@@ -26,7 +27,147 @@ pub(crate) enum DiagnosticId {
     MissingMain,
     MissingTrait,
     Moved,
+    UnknownDiagnostic,
     Unreachable,
+    UnusedArgument,
+    UnusedImport,
+    UnusedMethod,
+    UnusedVariable,
+}
+
+impl DiagnosticId {
+    /// All diagnostic IDs, sorted by their names.
+    pub(crate) const ALL: [DiagnosticId; 26] = [
+        DiagnosticId::DuplicateSymbol,
+        DiagnosticId::InvalidAssign,
+        DiagnosticId::InvalidCall,
+        DiagnosticId::InvalidCast,
+        DiagnosticId::InvalidConstExpr,
+        DiagnosticId::InvalidFile,
+        DiagnosticId::InvalidImplementation,
+        DiagnosticId::InvalidLoopKeyword,
+        DiagnosticId::InvalidMatch,
+        DiagnosticId::InvalidMethod,
+        DiagnosticId::InvalidPattern,
+        DiagnosticId::InvalidSymbol,
+        DiagnosticId::InvalidSyntax,
+        DiagnosticId::InvalidThrow,
+        DiagnosticId::InvalidType,
+        DiagnosticId::LimitReached,
+        DiagnosticId::MissingField,
+        DiagnosticId::MissingMain,
+        DiagnosticId::MissingTrait,
+        DiagnosticId::Moved,
+        DiagnosticId::UnknownDiagnostic,
+        DiagnosticId::Unreachable,
+        DiagnosticId::UnusedArgument,
+        DiagnosticId::UnusedImport,
+        DiagnosticId::UnusedMethod,
+        DiagnosticId::UnusedVariable,
+    ];
+
+    pub(crate) fn from_str(name: &str) -> Option<DiagnosticId> {
+        DiagnosticId::ALL.into_iter().find(|id| id.to_string() == name)
+    }
+
+    /// Returns `true` if the diagnostic is reported as a warning.
+    ///
+    /// Only the level of warnings can be changed, errors are always reported
+    /// as errors.
+    pub(crate) fn is_warning(self) -> bool {
+        matches!(
+            self,
+            DiagnosticId::UnknownDiagnostic
+                | DiagnosticId::Unreachable
+                | DiagnosticId::UnusedArgument
+                | DiagnosticId::UnusedImport
+                | DiagnosticId::UnusedMethod
+                | DiagnosticId::UnusedVariable
+        )
+    }
+
+    /// Returns a detailed explanation of the diagnostic, including examples
+    /// of code that produces the diagnostic and how to fix it.
+    pub(crate) fn explanation(self) -> &'static str {
+        match self {
+            DiagnosticId::DuplicateSymbol => {
+                include_str!("diagnostics/duplicate-symbol.txt")
+            }
+            DiagnosticId::InvalidAssign => {
+                include_str!("diagnostics/invalid-assign.txt")
+            }
+            DiagnosticId::InvalidCall => {
+                include_str!("diagnostics/invalid-call.txt")
+            }
+            DiagnosticId::InvalidCast => {
+                include_str!("diagnostics/invalid-cast.txt")
+            }
+            DiagnosticId::InvalidConstExpr => {
+                include_str!("diagnostics/invalid-const-expr.txt")
+            }
+            DiagnosticId::InvalidFile => {
+                include_str!("diagnostics/invalid-file.txt")
+            }
+            DiagnosticId::InvalidImplementation => {
+                include_str!("diagnostics/invalid-implementation.txt")
+            }
+            DiagnosticId::InvalidLoopKeyword => {
+                include_str!("diagnostics/invalid-loop-keyword.txt")
+            }
+            DiagnosticId::InvalidMatch => {
+                include_str!("diagnostics/invalid-match.txt")
+            }
+            DiagnosticId::InvalidMethod => {
+                include_str!("diagnostics/invalid-method.txt")
+            }
+            DiagnosticId::InvalidPattern => {
+                include_str!("diagnostics/invalid-pattern.txt")
+            }
+            DiagnosticId::InvalidSymbol => {
+                include_str!("diagnostics/invalid-symbol.txt")
+            }
+            DiagnosticId::InvalidSyntax => {
+                include_str!("diagnostics/invalid-syntax.txt")
+            }
+            DiagnosticId::InvalidThrow => {
+                include_str!("diagnostics/invalid-throw.txt")
+            }
+            DiagnosticId::InvalidType => {
+                include_str!("diagnostics/invalid-type.txt")
+            }
+            DiagnosticId::LimitReached => {
+                include_str!("diagnostics/limit-reached.txt")
+            }
+            DiagnosticId::MissingField => {
+                include_str!("diagnostics/missing-field.txt")
+            }
+            DiagnosticId::MissingMain => {
+                include_str!("diagnostics/missing-main.txt")
+            }
+            DiagnosticId::MissingTrait => {
+                include_str!("diagnostics/missing-trait.txt")
+            }
+            DiagnosticId::Moved => include_str!("diagnostics/moved.txt"),
+            DiagnosticId::UnknownDiagnostic => {
+                include_str!("diagnostics/unknown-diagnostic.txt")
+            }
+            DiagnosticId::Unreachable => {
+                include_str!("diagnostics/unreachable.txt")
+            }
+            DiagnosticId::UnusedArgument => {
+                include_str!("diagnostics/unused-argument.txt")
+            }
+            DiagnosticId::UnusedImport => {
+                include_str!("diagnostics/unused-import.txt")
+            }
+            DiagnosticId::UnusedMethod => {
+                include_str!("diagnostics/unused-method.txt")
+            }
+            DiagnosticId::UnusedVariable => {
+                include_str!("diagnostics/unused-variable.txt")
+            }
+        }
+    }
 }
 
 impl fmt::Display for DiagnosticId {
@@ -53,6 +194,11 @@ impl fmt::Display for DiagnosticId {
             DiagnosticId::LimitReached => "limit-reached",
             DiagnosticId::MissingMain => "missing-main",
             DiagnosticId::InvalidCast => "invalid-cast",
+            DiagnosticId::UnusedArgument => "unused-argument",
+            DiagnosticId::UnusedImport => "unused-import",
+            DiagnosticId::UnusedMethod => "unused-method",
+            DiagnosticId::UnusedVariable => "unused-variable",
+            DiagnosticId::UnknownDiagnostic => "unknown-diagnostic",
         };
 
         write!(f, "{}", id)
@@ -65,6 +211,34 @@ impl fmt::Debug for DiagnosticId {
     }
 }
 
+/// Returns the names of all the diagnostic IDs, such as "invalid-type".
+pub fn diagnostic_ids() -> Vec<String> {
+    DiagnosticId::ALL.iter().map(|id| id.to_string()).collect()
+}
+
+/// Returns a detailed explanation of the diagnostic with the given ID.
+///
+/// If the ID is invalid, a `None` is returned.
+pub fn explain(id: &str) -> Option<&'static str> {
+    DiagnosticId::from_str(id).map(|id| id.explanation())
+}
+
+/// The level to report a diagnostic at, as configured by the user.
+///
+/// Levels only apply to warnings: errors can't be silenced or turned into
+/// warnings, as compiling code with errors would produce invalid programs.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Level {
+    /// The diagnostic is silenced.
+    Allow,
+
+    /// The diagnostic is reported as a warning.
+    Warn,
+
+    /// The diagnostic is reported as an error.
+    Deny,
+}
+
 /// The type/kind of a diagnostic.
 #[derive(Copy, Clone)]
 pub(crate) enum DiagnosticType {
@@ -81,6 +255,33 @@ impl fmt::Display for DiagnosticType {
     }
 }
 
+/// A secondary location of a diagnostic, such as the location at which a
+/// value is moved when reporting the use of that value.
+pub(crate) struct Label {
+    pub(crate) message: String,
+    pub(crate) file: PathBuf,
+    pub(crate) location: SourceLocation,
+}
+
+/// The kind of a note attached to a diagnostic.
+#[derive(Copy, Clone)]
+pub(crate) enum NoteKind {
+    /// Additional information about the diagnostic.
+    Note,
+
+    /// A suggestion on how to resolve the diagnostic.
+    Help,
+}
+
+impl fmt::Display for NoteKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NoteKind::Note => write!(f, "note"),
+            NoteKind::Help => write!(f, "help"),
+        }
+    }
+}
+
 /// A single diagnostic such as a warning or error.
 pub(crate) struct Diagnostic {
     kind: DiagnosticType,
@@ -88,6 +289,8 @@ pub(crate) struct Diagnostic {
     message: String,
     file: PathBuf,
     location: SourceLocation,
+    labels: Vec<Label>,
+    notes: Vec<(NoteKind, String)>,
 }
 
 impl Diagnostic {
@@ -98,7 +301,36 @@ impl Diagnostic {
         file: PathBuf,
         location: SourceLocation,
     ) -> Self {
-        Self { kind, id, message, file, location }
+        Self {
+            kind,
+            id,
+            message,
+            file,
+            location,
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    /// Adds a secondary location to the diagnostic.
+    pub(crate) fn label<S: Into<String>>(
+        &mut self,
+        message: S,
+        file: PathBuf,
+        location: SourceLocation,
+    ) -> &mut Self {
+        self.labels.push(Label { message: message.into(), file, location });
+        self
+    }
+
+    pub(crate) fn note<S: Into<String>>(&mut self, message: S) -> &mut Self {
+        self.notes.push((NoteKind::Note, message.into()));
+        self
+    }
+
+    pub(crate) fn help<S: Into<String>>(&mut self, message: S) -> &mut Self {
+        self.notes.push((NoteKind::Help, message.into()));
+        self
     }
 
     pub(crate) fn is_error(&self) -> bool {
@@ -124,61 +356,152 @@ impl Diagnostic {
     pub(crate) fn location(&self) -> &SourceLocation {
         &self.location
     }
+
+    pub(crate) fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    pub(crate) fn notes(&self) -> &[(NoteKind, String)] {
+        &self.notes
+    }
 }
 
 /// A collection of warnings and errors.
 pub(crate) struct Diagnostics {
     values: Vec<Diagnostic>,
 
-    /// A flag indicating one or more errors have been produced.
+    /// The number of errors produced.
     ///
-    /// We use a dedicated flag as checking for the presence of errors happens
-    /// frequently. This avoids the need for iterating the diagnostics for every
-    /// such check.
-    errors: bool,
+    /// We use a dedicated counter as checking for the presence of errors
+    /// happens frequently. This avoids the need for iterating the diagnostics
+    /// for every such check.
+    errors: usize,
+
+    /// The levels to report specific warnings at.
+    levels: HashMap<DiagnosticId, Level>,
+
+    /// The level to report warnings at that don't have a specific level.
+    warnings: Level,
+
+    /// The warnings silenced using `# inko:allow(...)` comments, per file.
+    suppressions: HashMap<PathBuf, Vec<(DiagnosticId, SourceLocation)>>,
+
+    /// The last warning that is silenced.
+    ///
+    /// Silenced warnings are stored here instead of being discarded, such that
+    /// we can still return a mutable reference to them when they're produced.
+    allowed: Option<Diagnostic>,
 }
 
 impl Diagnostics {
-    pub(crate) fn new() -> Self {
-        Self { values: Vec::new(), errors: false }
+    /// Returns a new collection that reports warnings using the given levels.
+    ///
+    /// The `warnings` argument is the level to use for warnings not included
+    /// in `levels`.
+    pub(crate) fn new(
+        levels: HashMap<DiagnosticId, Level>,
+        warnings: Level,
+    ) -> Self {
+        Self {
+            values: Vec::new(),
+            errors: 0,
+            levels,
+            warnings,
+            suppressions: HashMap::new(),
+            allowed: None,
+        }
+    }
+
+    /// Silences warnings with the given ID produced for code at the given
+    /// location.
+    pub(crate) fn suppress(
+        &mut self,
+        id: DiagnosticId,
+        file: PathBuf,
+        location: SourceLocation,
+    ) {
+        self.suppressions.entry(file).or_default().push((id, location));
+    }
+
+    fn is_suppressed(
+        &self,
+        id: DiagnosticId,
+        file: &PathBuf,
+        location: &SourceLocation,
+    ) -> bool {
+        self.suppressions.get(file).map_or(false, |entries| {
+            entries.iter().any(|(i, loc)| *i == id && loc.contains(location))
+        })
     }
 
     pub(crate) fn has_errors(&self) -> bool {
+        self.errors > 0
+    }
+
+    pub(crate) fn error_count(&self) -> usize {
         self.errors
     }
 
+    /// Adds a new warning, returning it such that labels and notes can be
+    /// added to it.
+    ///
+    /// Depending on the configured levels, the warning may be silenced or
+    /// reported as an error instead.
     pub(crate) fn warn<S: Into<String>>(
         &mut self,
         id: DiagnosticId,
         message: S,
         file: PathBuf,
         location: SourceLocation,
-    ) {
-        self.values.push(Diagnostic::new(
-            DiagnosticType::Warning,
-            id,
-            message.into(),
-            file,
-            location,
-        ));
+    ) -> &mut Diagnostic {
+        let level = if self.is_suppressed(id, &file, &location) {
+            Level::Allow
+        } else {
+            self.levels.get(&id).cloned().unwrap_or(self.warnings)
+        };
+        let kind = match level {
+            Level::Allow => {
+                return self.allowed.insert(Diagnostic::new(
+                    DiagnosticType::Warning,
+                    id,
+                    message.into(),
+                    file,
+                    location,
+                ));
+            }
+            Level::Warn => DiagnosticType::Warning,
+            Level::Deny => {
+                self.errors += 1;
+                DiagnosticType::Error
+            }
+        };
+
+        self.push(Diagnostic::new(kind, id, message.into(), file, location))
     }
 
+    /// Adds a new error, returning it such that labels and notes can be added
+    /// to it.
     pub(crate) fn error<S: Into<String>>(
         &mut self,
         id: DiagnosticId,
         message: S,
         file: PathBuf,
         location: SourceLocation,
-    ) {
-        self.errors = true;
+    ) -> &mut Diagnostic {
+        self.errors += 1;
 
-        self.values.push(Diagnostic::new(
+        self.push(Diagnostic::new(
             DiagnosticType::Error,
             id,
             message.into(),
             file,
             location,
-        ));
+        ))
+    }
+
+    fn push(&mut self, diagnostic: Diagnostic) -> &mut Diagnostic {
+        self.values.push(diagnostic);
+        self.values.last_mut().unwrap()
     }
 
     pub(crate) fn undefined_symbol(
@@ -214,13 +537,18 @@ impl Diagnostics {
         name: &str,
         file: PathBuf,
         location: SourceLocation,
+        existing: Option<SourceLocation>,
     ) {
-        self.error(
+        let diag = self.error(
             DiagnosticId::DuplicateSymbol,
             format!("the symbol '{}' is already defined", name),
-            file,
+            file.clone(),
             location,
         );
+
+        if let Some(loc) = existing {
+            diag.label(format!("'{}' is first defined here", name), file, loc);
+        }
     }
 
     pub(crate) fn duplicate_field(
@@ -511,7 +839,7 @@ impl Diagnostics {
             format!("the symbol '{}' is defined but isn't a value", name),
             file,
             location,
-        )
+        );
     }
 
     pub(crate) fn invalid_instance_call(
@@ -552,6 +880,21 @@ impl Diagnostics {
         );
     }
 
+    pub(crate) fn unknown_diagnostic(
+        &mut self,
+        id: &str,
+        file: PathBuf,
+        location: SourceLocation,
+    ) {
+        self.warn(
+            DiagnosticId::UnknownDiagnostic,
+            format!("the diagnostic ID '{}' is undefined", id),
+            file,
+            location,
+        )
+        .help("run `inko explain --list` to list all diagnostic IDs");
+    }
+
     pub(crate) fn unreachable(
         &mut self,
         file: PathBuf,
@@ -565,6 +908,74 @@ impl Diagnostics {
         );
     }
 
+    pub(crate) fn unused_variable(
+        &mut self,
+        name: &str,
+        file: PathBuf,
+        location: SourceLocation,
+    ) {
+        self.warn(
+            DiagnosticId::UnusedVariable,
+            format!("the variable '{}' is never used", name),
+            file,
+            location,
+        )
+        .help(format!(
+            "if this is intentional, name the variable '_{}' instead",
+            name
+        ));
+    }
+
+    pub(crate) fn unused_argument(
+        &mut self,
+        name: &str,
+        file: PathBuf,
+        location: SourceLocation,
+    ) {
+        self.warn(
+            DiagnosticId::UnusedArgument,
+            format!("the argument '{}' is never used", name),
+            file,
+            location,
+        )
+        .help(format!(
+            "if this is intentional, name the argument '_{}' instead",
+            name
+        ));
+    }
+
+    pub(crate) fn unused_import(
+        &mut self,
+        name: &str,
+        file: PathBuf,
+        location: SourceLocation,
+    ) {
+        self.warn(
+            DiagnosticId::UnusedImport,
+            format!("the symbol '{}' is imported but never used", name),
+            file,
+            location,
+        );
+    }
+
+    pub(crate) fn unused_method(
+        &mut self,
+        name: &str,
+        file: PathBuf,
+        location: SourceLocation,
+    ) {
+        self.warn(
+            DiagnosticId::UnusedMethod,
+            format!("the private method '{}' is never used", name),
+            file,
+            location,
+        )
+        .help(format!(
+            "if this is intentional, name the method '_{}' instead",
+            name
+        ));
+    }
+
     pub(crate) fn unsendable_argument(
         &mut self,
         argument: String,
@@ -691,13 +1102,24 @@ impl Diagnostics {
         name: &str,
         file: PathBuf,
         location: SourceLocation,
+        moved: Option<SourceLocation>,
     ) {
-        self.error(
+        let diag = self.error(
             DiagnosticId::Moved,
             format!("'{}' can't be used as it has been moved", name),
-            file,
+            file.clone(),
             location,
         );
+
+        if let Some(loc) = moved {
+            diag.label(format!("'{}' is moved here", name), file, loc);
+        }
+
+        diag.help(format!(
+            "if '{}' must remain available, use a reference or a copy \
+            of the value (e.g. using 'clone') instead of moving it",
+            name
+        ));
     }
 
     pub(crate) fn implicit_receiver_moved(
@@ -719,16 +1141,26 @@ impl Diagnostics {
         name: &str,
         file: PathBuf,
         location: SourceLocation,
+        moved: Option<SourceLocation>,
     ) {
-        self.error(
+        let diag = self.error(
             DiagnosticId::Moved,
             format!(
                 "this closure can't capture '{}', as '{}' has been moved",
                 name, name,
             ),
-            file,
+            file.clone(),
             location,
         );
+
+        if let Some(loc) = moved {
+            diag.label(format!("'{}' is moved here", name), file, loc);
+        }
+
+        diag.note(
+            "closures capture variables when they are defined, not when \
+            they are called",
+        );
     }
 
     pub(crate) fn moved_variable_in_loop(
@@ -746,7 +1178,12 @@ impl Diagnostics {
             ),
             file,
             location,
-        );
+        )
+        .help(format!(
+            "assign '{}' a new value before the end of the loop, or move \
+            a copy of the value (e.g. using 'clone')",
+            name
+        ));
     }
 
     pub(crate) fn cant_infer_type(
@@ -780,7 +1217,7 @@ impl Diagnostics {
             ),
             file,
             location,
-        )
+        );
     }
 
     pub(crate) fn string_literal_too_large(
@@ -911,3 +1348,108 @@ impl Diagnostics {
         self.values.iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostic_id_from_str() {
+        for id in DiagnosticId::ALL {
+            assert_eq!(DiagnosticId::from_str(&id.to_string()), Some(id));
+        }
+
+        assert_eq!(DiagnosticId::from_str("foo"), None);
+    }
+
+    fn location() -> SourceLocation {
+        SourceLocation::new(1..=1, 1..=1)
+    }
+
+    #[test]
+    fn test_diagnostics_with_levels() {
+        let mut diags = Diagnostics::new(
+            HashMap::from([(DiagnosticId::Unreachable, Level::Deny)]),
+            Level::Warn,
+        );
+
+        diags.unreachable(PathBuf::from("a.inko"), location());
+
+        assert!(diags.has_errors());
+        assert!(diags.iter().next().unwrap().is_error());
+    }
+
+    #[test]
+    fn test_diagnostics_with_allowed_warnings() {
+        let mut diags = Diagnostics::new(
+            HashMap::from([(DiagnosticId::Unreachable, Level::Allow)]),
+            Level::Deny,
+        );
+
+        diags.unreachable(PathBuf::from("a.inko"), location());
+
+        assert!(!diags.has_errors());
+        assert_eq!(diags.iter().count(), 0);
+
+        // Errors can't be silenced.
+        diags.error(
+            DiagnosticId::Unreachable,
+            "foo",
+            PathBuf::from("a.inko"),
+            location(),
+        );
+
+        assert!(diags.has_errors());
+        assert_eq!(diags.iter().count(), 1);
+    }
+
+    #[test]
+    fn test_diagnostics_with_suppressed_warnings() {
+        let mut diags = Diagnostics::new(HashMap::new(), Level::Deny);
+        let file = PathBuf::from("a.inko");
+
+        diags.suppress(
+            DiagnosticId::Unreachable,
+            file.clone(),
+            SourceLocation::new(2..=4, 1..=1),
+        );
+        diags.unreachable(file.clone(), SourceLocation::new(3..=3, 5..=5));
+
+        assert_eq!(diags.iter().count(), 0);
+
+        diags.unreachable(file.clone(), location());
+        diags.unreachable(
+            PathBuf::from("b.inko"),
+            SourceLocation::new(3..=3, 5..=5),
+        );
+
+        assert_eq!(diags.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_diagnostics_with_denied_warnings() {
+        let mut diags = Diagnostics::new(HashMap::new(), Level::Deny);
+
+        diags.unreachable(PathBuf::from("a.inko"), location());
+
+        assert!(diags.has_errors());
+        assert_eq!(diags.error_count(), 1);
+    }
+
+    #[test]
+    fn test_diagnostic_id_is_warning() {
+        assert!(DiagnosticId::UnknownDiagnostic.is_warning());
+        assert!(DiagnosticId::UnusedImport.is_warning());
+        assert!(!DiagnosticId::InvalidSymbol.is_warning());
+    }
+
+    #[test]
+    fn test_explain() {
+        assert!(explain("moved").unwrap().starts_with("A value is used after"));
+        assert_eq!(explain("foo"), None);
+
+        for id in diagnostic_ids() {
+            assert!(explain(&id).is_some());
+        }
+    }
+}
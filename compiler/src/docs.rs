@@ -0,0 +1,831 @@
+//! Generating of documentation from source code comments.
+//!
+//! Documentation is generated for the public symbols of a project's modules,
+//! using the comments that directly precede the definitions of these symbols.
+//! Comments aren't part of the AST, so these are obtained separately using the
+//! lexer. The results are written as a set of static HTML pages, and as a
+//! single JSON file for use by other tools.
+mod html;
+
+use crate::hir;
+use crate::json::Json;
+use ast::lexer::{Lexer, TokenKind};
+use ast::source_location::SourceLocation;
+use std::collections::HashMap;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+use types::format::format_type;
+use types::{
+    ClassId, ClassKind, ConstantId, Database, FieldId, MethodId,
+    Symbol as TypeSymbol, TraitId, TypeParameterId, VariantId,
+};
+
+/// The name of the JSON file containing all documentation.
+const JSON_FILE: &str = "docs.json";
+
+/// The name of the stylesheet used by the HTML pages.
+const STYLE_FILE: &str = "style.css";
+
+fn line(location: &SourceLocation) -> usize {
+    *location.line_range.start()
+}
+
+fn type_parameters(db: &Database, params: Vec<TypeParameterId>) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+
+    let params: Vec<_> = params
+        .into_iter()
+        .map(|param| {
+            let mut name = format_type(db, param);
+            let reqs: Vec<_> = param
+                .requirements(db)
+                .into_iter()
+                .map(|req| format_type(db, req))
+                .collect();
+
+            if !reqs.is_empty() {
+                name.push_str(if param.is_mutable(db) { " + " } else { ": " });
+                name.push_str(&reqs.join(" + "));
+            }
+
+            name
+        })
+        .collect();
+
+    format!("[{}]", params.join(", "))
+}
+
+fn visibility(public: bool) -> &'static str {
+    if public {
+        "pub "
+    } else {
+        ""
+    }
+}
+
+fn class_signature(db: &Database, id: ClassId) -> String {
+    let kind = match id.kind(db) {
+        _ if id.is_builtin() => "builtin ",
+        ClassKind::Async => "async ",
+        ClassKind::Enum => "enum ",
+        ClassKind::Extern => "extern ",
+        _ => "",
+    };
+
+    format!(
+        "class {}{}{}{}",
+        visibility(id.is_public(db)),
+        kind,
+        id.name(db),
+        type_parameters(db, id.type_parameters(db))
+    )
+}
+
+fn trait_signature(db: &Database, id: TraitId) -> String {
+    let reqs: Vec<_> = id
+        .required_traits(db)
+        .into_iter()
+        .map(|req| format_type(db, req))
+        .collect();
+
+    format!(
+        "trait {}{}{}{}",
+        visibility(TypeSymbol::Trait(id).is_public(db)),
+        id.name(db),
+        type_parameters(db, id.type_parameters(db)),
+        if reqs.is_empty() {
+            String::new()
+        } else {
+            format!(": {}", reqs.join(" + "))
+        }
+    )
+}
+
+fn constant_signature(db: &Database, id: ConstantId) -> String {
+    format!(
+        "let {}{}: {}",
+        visibility(TypeSymbol::Constant(id).is_public(db)),
+        id.name(db),
+        format_type(db, id.value_type(db))
+    )
+}
+
+fn field_signature(db: &Database, id: FieldId) -> String {
+    format!(
+        "let {}@{}: {}",
+        visibility(id.is_public(db)),
+        id.name(db),
+        format_type(db, id.value_type(db))
+    )
+}
+
+fn variant_signature(db: &Database, id: VariantId) -> String {
+    let members: Vec<_> =
+        id.members(db).into_iter().map(|t| format_type(db, t)).collect();
+
+    if members.is_empty() {
+        format!("case {}", id.name(db))
+    } else {
+        format!("case {}({})", id.name(db), members.join(", "))
+    }
+}
+
+/// A documented symbol, such as a method or constant.
+pub(crate) struct Symbol {
+    pub(crate) name: String,
+
+    /// The signature of the symbol, such as `fn pub foo -> Int`.
+    pub(crate) signature: String,
+
+    /// The documentation of the symbol, in Markdown format.
+    pub(crate) documentation: String,
+
+    /// The line on which the symbol is defined.
+    pub(crate) line: usize,
+}
+
+impl Symbol {
+    fn pairs(&self) -> Vec<(&'static str, Json)> {
+        vec![
+            ("name", Json::string(&self.name)),
+            ("signature", Json::string(&self.signature)),
+            ("documentation", Json::string(&self.documentation)),
+            ("line", Json::Int(self.line as i64)),
+        ]
+    }
+
+    fn to_json(&self) -> Json {
+        Json::object(self.pairs())
+    }
+}
+
+fn symbols_to_json(symbols: &[Symbol]) -> Json {
+    Json::Array(symbols.iter().map(|s| s.to_json()).collect())
+}
+
+/// A documented class.
+pub(crate) struct Class {
+    id: ClassId,
+    pub(crate) symbol: Symbol,
+    pub(crate) fields: Vec<Symbol>,
+    pub(crate) variants: Vec<Symbol>,
+    pub(crate) methods: Vec<Symbol>,
+
+    /// The traits implemented by the class.
+    pub(crate) implementations: Vec<String>,
+}
+
+impl Class {
+    fn to_json(&self) -> Json {
+        let mut pairs = self.symbol.pairs();
+
+        pairs.push(("fields", symbols_to_json(&self.fields)));
+        pairs.push(("variants", symbols_to_json(&self.variants)));
+        pairs.push(("methods", symbols_to_json(&self.methods)));
+        pairs.push((
+            "implementations",
+            Json::Array(
+                self.implementations.iter().map(Json::string).collect(),
+            ),
+        ));
+
+        Json::object(pairs)
+    }
+}
+
+/// A documented trait.
+pub(crate) struct Trait {
+    pub(crate) symbol: Symbol,
+    pub(crate) required_methods: Vec<Symbol>,
+    pub(crate) default_methods: Vec<Symbol>,
+}
+
+impl Trait {
+    fn to_json(&self) -> Json {
+        let mut pairs = self.symbol.pairs();
+
+        pairs.push((
+            "required_methods",
+            symbols_to_json(&self.required_methods),
+        ));
+        pairs.push(("default_methods", symbols_to_json(&self.default_methods)));
+        Json::object(pairs)
+    }
+}
+
+/// The documentation of a single module.
+pub(crate) struct Module {
+    pub(crate) name: String,
+
+    /// The path to the module's source file, relative to the project root.
+    pub(crate) file: PathBuf,
+    pub(crate) documentation: String,
+    pub(crate) constants: Vec<Symbol>,
+    pub(crate) methods: Vec<Symbol>,
+    pub(crate) classes: Vec<Class>,
+    pub(crate) traits: Vec<Trait>,
+}
+
+impl Module {
+    fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("name", Json::string(&self.name)),
+            ("file", Json::string(self.file.to_string_lossy())),
+            ("documentation", Json::string(&self.documentation)),
+            ("constants", symbols_to_json(&self.constants)),
+            ("methods", symbols_to_json(&self.methods)),
+            (
+                "classes",
+                Json::Array(self.classes.iter().map(|c| c.to_json()).collect()),
+            ),
+            (
+                "traits",
+                Json::Array(self.traits.iter().map(|t| t.to_json()).collect()),
+            ),
+        ])
+    }
+}
+
+/// The comments of a source file that are placed on their own lines.
+struct Comments {
+    lines: HashMap<usize, String>,
+}
+
+impl Comments {
+    fn new(source: &str) -> Comments {
+        let code: Vec<_> = source.split('\n').collect();
+        let mut lexer = Lexer::new(source.as_bytes().to_vec());
+        let mut lines = HashMap::new();
+
+        loop {
+            let token = lexer.next_token();
+
+            match token.kind {
+                TokenKind::Comment => {
+                    let line = line(&token.location);
+
+                    // Comments placed at the end of a line of code are never
+                    // documentation.
+                    if code
+                        .get(line - 1)
+                        .map_or(false, |l| l.trim_start().starts_with('#'))
+                    {
+                        lines.insert(line, token.value);
+                    }
+                }
+                TokenKind::Null => break,
+                _ => {}
+            }
+        }
+
+        Comments { lines }
+    }
+
+    /// Removes and returns the lines of the comment that directly precedes the
+    /// given line.
+    fn before(&mut self, mut line: usize) -> String {
+        let mut lines = Vec::new();
+
+        while line > 1 {
+            if let Some(text) = self.lines.remove(&(line - 1)) {
+                lines.push(text);
+                line -= 1;
+            } else {
+                break;
+            }
+        }
+
+        lines.reverse();
+        Self::join(lines)
+    }
+
+    /// Removes and returns the comment at the start of the file.
+    fn module(&mut self) -> String {
+        let mut lines = Vec::new();
+        let mut line = 1;
+
+        while let Some(text) = self.lines.remove(&line) {
+            lines.push(text);
+            line += 1;
+        }
+
+        Self::join(lines)
+    }
+
+    /// Joins the lines of a comment, excluding any `# inko:allow(...)` lines
+    /// as these aren't documentation.
+    fn join(lines: Vec<String>) -> String {
+        lines
+            .into_iter()
+            .filter(|l| !l.starts_with("inko:allow("))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// A type for collecting the documentation of a module.
+struct Generator<'a> {
+    db: &'a Database,
+    comments: Comments,
+    module: Module,
+
+    /// The trait implementations and reopened methods of classes, which may be
+    /// defined in a different module than the classes themselves.
+    implementations: &'a mut Vec<(ClassId, String)>,
+    reopened: &'a mut Vec<(ClassId, Symbol)>,
+}
+
+impl<'a> Generator<'a> {
+    fn run(mut self, module: &hir::Module) -> Module {
+        for expr in &module.expressions {
+            match expr {
+                hir::TopLevelExpression::Constant(n) => self.constant(n),
+                hir::TopLevelExpression::ModuleMethod(n) => {
+                    let doc = self.comments.before(line(&n.location));
+
+                    if let Some(sym) =
+                        self.method(n.method_id, &n.location, doc)
+                    {
+                        self.module.methods.push(sym);
+                    }
+                }
+                hir::TopLevelExpression::ExternFunction(n) => {
+                    let doc = self.comments.before(line(&n.location));
+
+                    if let Some(sym) =
+                        self.method(n.method_id, &n.location, doc)
+                    {
+                        self.module.methods.push(sym);
+                    }
+                }
+                hir::TopLevelExpression::Class(n) => self.class(n),
+                hir::TopLevelExpression::ExternClass(n) => self.extern_class(n),
+                hir::TopLevelExpression::Trait(n) => self.define_trait(n),
+                hir::TopLevelExpression::Implement(n) => {
+                    self.implement_trait(n)
+                }
+                hir::TopLevelExpression::Reopen(n) => self.reopen_class(n),
+                hir::TopLevelExpression::Import(_)
+                | hir::TopLevelExpression::ExternImport(_) => {}
+            }
+        }
+
+        // The module comment is obtained last, such that a comment at the
+        // start of the module that documents a symbol isn't used as the
+        // module's documentation.
+        self.module.documentation = self.comments.module();
+        self.module
+    }
+
+    fn constant(&mut self, node: &hir::DefineConstant) {
+        let documentation = self.comments.before(line(&node.location));
+        let Some(id) = node.constant_id else { return };
+
+        if !TypeSymbol::Constant(id).is_public(self.db) {
+            return;
+        }
+
+        self.module.constants.push(Symbol {
+            name: id.name(self.db).clone(),
+            signature: constant_signature(self.db, id),
+            documentation,
+            line: line(&node.location),
+        });
+    }
+
+    fn method(
+        &self,
+        id: Option<MethodId>,
+        location: &SourceLocation,
+        documentation: String,
+    ) -> Option<Symbol> {
+        let id = id.filter(|id| id.is_public(self.db))?;
+
+        Some(Symbol {
+            name: id.name(self.db).clone(),
+            signature: format_type(self.db, id),
+            documentation,
+            line: line(location),
+        })
+    }
+
+    fn class_symbol(
+        &mut self,
+        id: ClassId,
+        location: &SourceLocation,
+    ) -> Option<Symbol> {
+        let documentation = self.comments.before(line(location));
+
+        if !id.is_public(self.db) {
+            return None;
+        }
+
+        Some(Symbol {
+            name: id.name(self.db).clone(),
+            signature: class_signature(self.db, id),
+            documentation,
+            line: line(location),
+        })
+    }
+
+    fn class(&mut self, node: &hir::DefineClass) {
+        let Some(id) = node.class_id else { return };
+        let Some(symbol) = self.class_symbol(id, &node.location) else {
+            return;
+        };
+        let mut class = Class {
+            id,
+            symbol,
+            fields: Vec::new(),
+            variants: Vec::new(),
+            methods: Vec::new(),
+            implementations: Vec::new(),
+        };
+
+        for expr in &node.body {
+            let (id, location) = match expr {
+                hir::ClassExpression::InstanceMethod(n) => {
+                    (n.method_id, &n.location)
+                }
+                hir::ClassExpression::StaticMethod(n) => {
+                    (n.method_id, &n.location)
+                }
+                hir::ClassExpression::AsyncMethod(n) => {
+                    (n.method_id, &n.location)
+                }
+                hir::ClassExpression::Field(n) => {
+                    if let Some(sym) = self.field(n) {
+                        class.fields.push(sym);
+                    }
+
+                    continue;
+                }
+                hir::ClassExpression::Variant(n) => {
+                    let documentation = self.comments.before(line(&n.location));
+
+                    if let Some(id) = n.variant_id {
+                        class.variants.push(Symbol {
+                            name: id.name(self.db).clone(),
+                            signature: variant_signature(self.db, id),
+                            documentation,
+                            line: line(&n.location),
+                        });
+                    }
+
+                    continue;
+                }
+            };
+
+            let doc = self.comments.before(line(location));
+
+            if let Some(sym) = self.method(id, location, doc) {
+                class.methods.push(sym);
+            }
+        }
+
+        self.module.classes.push(class);
+    }
+
+    fn extern_class(&mut self, node: &hir::DefineExternClass) {
+        let Some(id) = node.class_id else { return };
+        let Some(symbol) = self.class_symbol(id, &node.location) else {
+            return;
+        };
+        let mut class = Class {
+            id,
+            symbol,
+            fields: Vec::new(),
+            variants: Vec::new(),
+            methods: Vec::new(),
+            implementations: Vec::new(),
+        };
+
+        for field in &node.fields {
+            if let Some(sym) = self.field(field) {
+                class.fields.push(sym);
+            }
+        }
+
+        self.module.classes.push(class);
+    }
+
+    fn field(&mut self, node: &hir::DefineField) -> Option<Symbol> {
+        let documentation = self.comments.before(line(&node.location));
+        let id = node.field_id.filter(|id| id.is_public(self.db))?;
+
+        Some(Symbol {
+            name: id.name(self.db).clone(),
+            signature: field_signature(self.db, id),
+            documentation,
+            line: line(&node.location),
+        })
+    }
+
+    fn define_trait(&mut self, node: &hir::DefineTrait) {
+        let documentation = self.comments.before(line(&node.location));
+        let Some(id) = node.trait_id else { return };
+
+        if !TypeSymbol::Trait(id).is_public(self.db) {
+            return;
+        }
+
+        let mut tdoc = Trait {
+            symbol: Symbol {
+                name: id.name(self.db).clone(),
+                signature: trait_signature(self.db, id),
+                documentation,
+                line: line(&node.location),
+            },
+            required_methods: Vec::new(),
+            default_methods: Vec::new(),
+        };
+
+        for expr in &node.body {
+            match expr {
+                hir::TraitExpression::InstanceMethod(n) => {
+                    let doc = self.comments.before(line(&n.location));
+
+                    if let Some(sym) =
+                        self.method(n.method_id, &n.location, doc)
+                    {
+                        tdoc.default_methods.push(sym);
+                    }
+                }
+                hir::TraitExpression::RequiredMethod(n) => {
+                    let doc = self.comments.before(line(&n.location));
+
+                    if let Some(sym) =
+                        self.method(n.method_id, &n.location, doc)
+                    {
+                        tdoc.required_methods.push(sym);
+                    }
+                }
+            }
+        }
+
+        self.module.traits.push(tdoc);
+    }
+
+    fn implement_trait(&mut self, node: &hir::ImplementTrait) {
+        let (Some(trait_ins), Some(class_ins)) =
+            (node.trait_instance, node.class_instance)
+        else {
+            return;
+        };
+
+        if TypeSymbol::Trait(trait_ins.instance_of()).is_public(self.db) {
+            self.implementations.push((
+                class_ins.instance_of(),
+                format_type(self.db, trait_ins),
+            ));
+        }
+    }
+
+    fn reopen_class(&mut self, node: &hir::ReopenClass) {
+        let Some(class_id) = node.class_id else { return };
+
+        for expr in &node.body {
+            let (id, location) = match expr {
+                hir::ReopenClassExpression::InstanceMethod(n) => {
+                    (n.method_id, &n.location)
+                }
+                hir::ReopenClassExpression::StaticMethod(n) => {
+                    (n.method_id, &n.location)
+                }
+                hir::ReopenClassExpression::AsyncMethod(n) => {
+                    (n.method_id, &n.location)
+                }
+            };
+
+            let doc = self.comments.before(line(location));
+
+            if let Some(sym) = self.method(id, location, doc) {
+                self.reopened.push((class_id, sym));
+            }
+        }
+    }
+}
+
+/// Collects the documentation of the given modules.
+///
+/// The `root` argument is the directory that module paths are made relative
+/// to.
+fn collect(
+    db: &Database,
+    modules: &[hir::Module],
+    root: &Path,
+) -> Result<Vec<Module>, String> {
+    let mut docs = Vec::new();
+    let mut implementations = Vec::new();
+    let mut reopened = Vec::new();
+
+    for module in modules {
+        let path = module.module_id.file(db);
+        let source = read_to_string(&path).map_err(|err| {
+            format!("Failed to read {}: {}", path.display(), err)
+        })?;
+        let gen = Generator {
+            db,
+            comments: Comments::new(&source),
+            module: Module {
+                name: module.module_id.name(db).to_string(),
+                file: path.strip_prefix(root).unwrap_or(&path).to_path_buf(),
+                documentation: String::new(),
+                constants: Vec::new(),
+                methods: Vec::new(),
+                classes: Vec::new(),
+                traits: Vec::new(),
+            },
+            implementations: &mut implementations,
+            reopened: &mut reopened,
+        };
+
+        docs.push(gen.run(module));
+    }
+
+    let mut classes: HashMap<ClassId, &mut Class> = docs
+        .iter_mut()
+        .flat_map(|m| m.classes.iter_mut())
+        .map(|c| (c.id, c))
+        .collect();
+
+    for (id, name) in implementations {
+        if let Some(class) = classes.get_mut(&id) {
+            class.implementations.push(name);
+        }
+    }
+
+    for (id, method) in reopened {
+        if let Some(class) = classes.get_mut(&id) {
+            class.methods.push(method);
+        }
+    }
+
+    for class in classes.into_values() {
+        class.implementations.sort();
+        class.implementations.dedup();
+    }
+
+    docs.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(docs)
+}
+
+/// Generates the documentation of the given modules, and writes it to the
+/// given directory.
+pub(crate) fn generate(
+    db: &Database,
+    modules: &[hir::Module],
+    source: &Path,
+    directory: &Path,
+) -> Result<(), String> {
+    let root = source.parent().unwrap_or(source);
+    let modules = collect(db, modules, root)?;
+    let json = Json::object(vec![(
+        "modules",
+        Json::Array(modules.iter().map(|m| m.to_json()).collect()),
+    )]);
+    let mut files = vec![
+        (PathBuf::from(JSON_FILE), json.to_string()),
+        (PathBuf::from(STYLE_FILE), html::STYLE.to_string()),
+        (PathBuf::from("index.html"), html::index(&modules)),
+    ];
+
+    for module in &modules {
+        files.push((html::module_file(&module.name), html::module(module)));
+    }
+
+    create_dir_all(directory).map_err(|err| {
+        format!("Failed to create {}: {}", directory.display(), err)
+    })?;
+
+    for (name, content) in files {
+        let path = directory.join(name);
+
+        write(&path, content).map_err(|err| {
+            format!("Failed to write {}: {}", path.display(), err)
+        })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+    use crate::config::Config;
+    use std::env::temp_dir;
+    use std::fs::remove_dir_all;
+
+    #[test]
+    fn test_comments_before() {
+        let mut comments = Comments::new(
+            "# Module\n\n# A\n#\n# B\nclass A {}\nlet B = 10 # C\n# D\n\nlet E = 1",
+        );
+
+        assert_eq!(comments.before(6), "A\n\nB".to_string());
+        assert_eq!(comments.before(6), String::new());
+        assert_eq!(comments.before(8), String::new());
+        assert_eq!(comments.before(10), String::new());
+        assert_eq!(comments.module(), "Module".to_string());
+    }
+
+    #[test]
+    fn test_comments_module() {
+        let mut comments = Comments::new("# A\n# B\nclass A {}");
+
+        assert_eq!(comments.before(3), "A\nB".to_string());
+        assert_eq!(comments.module(), String::new());
+    }
+
+    #[test]
+    fn test_comments_with_suppressions() {
+        let mut comments =
+            Comments::new("# A\n# inko:allow(unreachable)\nfn a {}");
+
+        assert_eq!(comments.before(3), "A".to_string());
+    }
+
+    #[test]
+    fn test_generate() {
+        let root = temp_dir().join("inko-docs-test");
+        let source = root.join("src");
+        let output = root.join("docs");
+
+        create_dir_all(&source).unwrap();
+        write(
+            source.join("shapes.inko"),
+            "\
+# Types for working with shapes.
+import std.fmt.(Format, Formatter)
+
+# The number of sides of a square.
+let pub SIDES = 4
+
+# A shape with four sides.
+class pub Square {
+  # The width of the square.
+  let pub @width: Int
+  let @cache: Int
+
+  # Returns the area of the square.
+  fn pub area -> Int {
+    @width * @width
+  }
+
+  fn hidden {}
+}
+
+impl Format for Square {
+  fn pub fmt(formatter: mut Formatter) {}
+}
+
+class Private {}
+",
+        )
+        .unwrap();
+
+        let config = Config { source: source.clone(), ..Config::default() };
+        let mut compiler = Compiler::new(config);
+
+        assert!(compiler.document(&output).is_ok());
+
+        let json =
+            Json::parse(&read_to_string(output.join(JSON_FILE)).unwrap())
+                .unwrap();
+        let module = &json.get("modules").as_array()[0];
+        let class = &module.get("classes").as_array()[0];
+
+        assert!(output.join("index.html").is_file());
+        assert!(output.join("shapes.html").is_file());
+        assert_eq!(module.get("name").as_str(), Some("shapes"));
+        assert_eq!(
+            module.get("documentation").as_str(),
+            Some("Types for working with shapes.")
+        );
+        assert_eq!(
+            module.get("constants").as_array()[0].get("signature").as_str(),
+            Some("let pub SIDES: Int")
+        );
+        assert_eq!(module.get("classes").as_array().len(), 1);
+        assert_eq!(class.get("signature").as_str(), Some("class pub Square"));
+        assert_eq!(
+            class.get("documentation").as_str(),
+            Some("A shape with four sides.")
+        );
+        assert_eq!(class.get("fields").as_array().len(), 1);
+        assert_eq!(class.get("methods").as_array().len(), 1);
+        assert_eq!(
+            class.get("methods").as_array()[0].get("documentation").as_str(),
+            Some("Returns the area of the square.")
+        );
+        assert_eq!(
+            class.get("implementations").as_array(),
+            &[Json::string("Format")]
+        );
+
+        remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,480 @@
+//! Rendering of documentation as HTML.
+//!
+//! Documentation comments are written in Markdown. Rather than supporting all
+//! of Markdown, we only support the subset used in practise: paragraphs,
+//! headings, lists, code blocks, inline code, emphasis and links.
+use crate::docs::{Class, Module, Symbol, Trait};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// The stylesheet used by all pages.
+pub(crate) const STYLE: &str = "\
+body {
+  font-family: sans-serif;
+  line-height: 1.5;
+  max-width: 60rem;
+  margin: 0 auto;
+  padding: 1rem;
+  color: #222;
+}
+a { color: #0b5394; }
+code, pre { font-family: monospace; background: #f4f4f4; }
+code { padding: 0 0.2rem; }
+pre { padding: 0.5rem; overflow-x: auto; }
+pre code { padding: 0; }
+pre.signature { border-left: 3px solid #0b5394; font-weight: bold; }
+section.symbol { margin-left: 1rem; }
+ul.modules { list-style: none; padding: 0; }
+";
+
+/// Returns the name of the HTML file for a module.
+pub(crate) fn module_file(name: &str) -> PathBuf {
+    PathBuf::from(format!("{}.html", name))
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for chr in text.chars() {
+        match chr {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            chr => escaped.push(chr),
+        }
+    }
+
+    escaped
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+        <meta name=\"viewport\" content=\"width=device-width\">\n\
+        <title>{}</title>\n\
+        <link rel=\"stylesheet\" href=\"style.css\">\n\
+        </head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        body
+    )
+}
+
+/// Returns the first paragraph of a documentation comment.
+fn summary(documentation: &str) -> &str {
+    documentation.split("\n\n").next().unwrap_or("").trim()
+}
+
+/// Renders the index page, listing all modules.
+pub(crate) fn index(modules: &[Module]) -> String {
+    let mut body = String::from("<h1>Modules</h1>\n<ul class=\"modules\">\n");
+
+    for module in modules {
+        let _ = writeln!(
+            body,
+            "<li><a href=\"{}\">{}</a>{}</li>",
+            escape(&module_file(&module.name).to_string_lossy()),
+            escape(&module.name),
+            markdown(summary(&module.documentation), 1)
+        );
+    }
+
+    body.push_str("</ul>\n");
+    page("Modules", &body)
+}
+
+fn symbol(buffer: &mut String, id: &str, symbol: &Symbol, heading: usize) {
+    let _ = write!(
+        buffer,
+        "<section class=\"symbol\" id=\"{id}\">\n\
+        <h{heading}><a href=\"#{id}\">{}</a></h{heading}>\n\
+        <pre class=\"signature\"><code>{}</code></pre>\n{}",
+        escape(&symbol.name),
+        escape(&symbol.signature),
+        markdown(&symbol.documentation, heading),
+        id = escape(id),
+        heading = heading,
+    );
+}
+
+fn symbols(
+    buffer: &mut String,
+    title: &str,
+    prefix: &str,
+    symbols: &[Symbol],
+    heading: usize,
+) {
+    if symbols.is_empty() {
+        return;
+    }
+
+    let _ = writeln!(buffer, "<h{0}>{1}</h{0}>", heading, title);
+
+    for sym in symbols {
+        symbol(buffer, &format!("{}{}", prefix, sym.name), sym, heading + 1);
+        buffer.push_str("</section>\n");
+    }
+}
+
+fn class(buffer: &mut String, class: &Class) {
+    let prefix = format!("{}.", class.symbol.name);
+
+    symbol(buffer, &class.symbol.name, &class.symbol, 3);
+
+    if !class.implementations.is_empty() {
+        buffer.push_str("<h4>Implementations</h4>\n<ul>\n");
+
+        for name in &class.implementations {
+            let _ = writeln!(buffer, "<li><code>{}</code></li>", escape(name));
+        }
+
+        buffer.push_str("</ul>\n");
+    }
+
+    symbols(buffer, "Variants", &prefix, &class.variants, 4);
+    symbols(buffer, "Fields", &prefix, &class.fields, 4);
+    symbols(buffer, "Methods", &prefix, &class.methods, 4);
+    buffer.push_str("</section>\n");
+}
+
+fn define_trait(buffer: &mut String, tdoc: &Trait) {
+    let prefix = format!("{}.", tdoc.symbol.name);
+
+    symbol(buffer, &tdoc.symbol.name, &tdoc.symbol, 3);
+    symbols(buffer, "Required methods", &prefix, &tdoc.required_methods, 4);
+    symbols(buffer, "Default methods", &prefix, &tdoc.default_methods, 4);
+    buffer.push_str("</section>\n");
+}
+
+/// Renders the page of a single module.
+pub(crate) fn module(module: &Module) -> String {
+    let mut body = String::new();
+    let _ = write!(
+        body,
+        "<p><a href=\"index.html\">Modules</a></p>\n<h1>{}</h1>\n\
+        <p><code>{}</code></p>\n{}",
+        escape(&module.name),
+        escape(&module.file.to_string_lossy()),
+        markdown(&module.documentation, 1)
+    );
+
+    symbols(&mut body, "Constants", "", &module.constants, 2);
+    symbols(&mut body, "Methods", "", &module.methods, 2);
+
+    if !module.classes.is_empty() {
+        body.push_str("<h2>Classes</h2>\n");
+
+        for cls in &module.classes {
+            class(&mut body, cls);
+        }
+    }
+
+    if !module.traits.is_empty() {
+        body.push_str("<h2>Traits</h2>\n");
+
+        for tdoc in &module.traits {
+            define_trait(&mut body, tdoc);
+        }
+    }
+
+    page(&module.name, &body)
+}
+
+/// A block of Markdown that is being rendered.
+enum Block {
+    None,
+    Paragraph(Vec<String>),
+    List(&'static str, Vec<String>),
+}
+
+fn flush(buffer: &mut String, block: &mut Block) {
+    match std::mem::replace(block, Block::None) {
+        Block::None => {}
+        Block::Paragraph(lines) => {
+            let _ = writeln!(buffer, "<p>{}</p>", inline(&lines.join(" ")));
+        }
+        Block::List(tag, items) => {
+            let _ = writeln!(buffer, "<{}>", tag);
+
+            for item in items {
+                let _ = writeln!(buffer, "<li>{}</li>", inline(&item));
+            }
+
+            let _ = writeln!(buffer, "</{}>", tag);
+        }
+    }
+}
+
+fn list_item(line: &str) -> Option<(&'static str, &str)> {
+    if let Some(rest) =
+        line.strip_prefix("- ").or_else(|| line.strip_prefix("* "))
+    {
+        return Some(("ul", rest));
+    }
+
+    let digits = line.find(|c: char| !c.is_ascii_digit()).unwrap_or(0);
+
+    if digits > 0 {
+        if let Some(rest) = line[digits..].strip_prefix(". ") {
+            return Some(("ol", rest));
+        }
+    }
+
+    None
+}
+
+/// Renders Markdown as HTML.
+///
+/// The `heading` argument is the level of the heading the Markdown is placed
+/// under, such that headings in the Markdown are nested under it.
+pub(crate) fn markdown(text: &str, heading: usize) -> String {
+    let mut buffer = String::new();
+    let mut block = Block::None;
+    let mut lines = text.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        if line.trim().is_empty() {
+            flush(&mut buffer, &mut block);
+            continue;
+        }
+
+        if let Some(lang) = line.trim_start().strip_prefix("```") {
+            let mut code = Vec::new();
+
+            flush(&mut buffer, &mut block);
+
+            for line in lines.by_ref() {
+                if line.trim_start().starts_with("```") {
+                    break;
+                }
+
+                code.push(line);
+            }
+
+            let _ = writeln!(
+                buffer,
+                "<pre><code{}>{}</code></pre>",
+                if lang.trim().is_empty() {
+                    String::new()
+                } else {
+                    format!(" class=\"language-{}\"", escape(lang.trim()))
+                },
+                escape(&code.join("\n"))
+            );
+            continue;
+        }
+
+        // Lines indented by four spaces are code blocks, unless they continue
+        // a list item.
+        if line.starts_with("    ") && !matches!(block, Block::List(..)) {
+            let mut code = vec![&line[4..]];
+
+            flush(&mut buffer, &mut block);
+
+            while let Some(line) = lines.peek() {
+                if line.starts_with("    ") || line.trim().is_empty() {
+                    code.push(line.get(4..).unwrap_or(""));
+                    lines.next();
+                } else {
+                    break;
+                }
+            }
+
+            while code.last().map_or(false, |l| l.trim().is_empty()) {
+                code.pop();
+            }
+
+            let _ = writeln!(
+                buffer,
+                "<pre><code>{}</code></pre>",
+                escape(&code.join("\n"))
+            );
+            continue;
+        }
+
+        let level = line.find(|c| c != '#').unwrap_or(0);
+
+        if (1..=6).contains(&level) && line[level..].starts_with(' ') {
+            let tag = (heading + level).min(6);
+
+            flush(&mut buffer, &mut block);
+
+            let _ = writeln!(
+                buffer,
+                "<h{0}>{1}</h{0}>",
+                tag,
+                inline(line[level..].trim())
+            );
+            continue;
+        }
+
+        if let Some((tag, rest)) = list_item(line) {
+            match &mut block {
+                Block::List(current, items) if *current == tag => {
+                    items.push(rest.to_string());
+                }
+                _ => {
+                    flush(&mut buffer, &mut block);
+                    block = Block::List(tag, vec![rest.to_string()]);
+                }
+            }
+
+            continue;
+        }
+
+        match &mut block {
+            Block::Paragraph(lines) => lines.push(line.trim().to_string()),
+            Block::List(_, items) => {
+                let item = items.last_mut().unwrap();
+
+                item.push(' ');
+                item.push_str(line.trim());
+            }
+            Block::None => {
+                block = Block::Paragraph(vec![line.trim().to_string()]);
+            }
+        }
+    }
+
+    flush(&mut buffer, &mut block);
+    buffer
+}
+
+/// Renders the inline elements of a block of Markdown.
+fn inline(text: &str) -> String {
+    let mut buffer = String::new();
+    let mut rest = text;
+
+    while let Some(chr) = rest.chars().next() {
+        let after = &rest[chr.len_utf8()..];
+
+        match chr {
+            '`' => {
+                if let Some(end) = after.find('`') {
+                    let _ = write!(
+                        buffer,
+                        "<code>{}</code>",
+                        escape(&after[..end])
+                    );
+                    rest = &after[end + 1..];
+                    continue;
+                }
+            }
+            '[' => {
+                if let Some((label, url, remaining)) = link(after) {
+                    let _ = write!(
+                        buffer,
+                        "<a href=\"{}\">{}</a>",
+                        escape(url),
+                        inline(label)
+                    );
+                    rest = remaining;
+                    continue;
+                }
+            }
+            '*' if after.starts_with('*') => {
+                if let Some(end) = after[1..].find("**").filter(|&i| i > 0) {
+                    let _ = write!(
+                        buffer,
+                        "<strong>{}</strong>",
+                        inline(&after[1..end + 1])
+                    );
+                    rest = &after[end + 3..];
+                    continue;
+                }
+            }
+            '_' | '*' => {
+                let boundary = buffer
+                    .chars()
+                    .last()
+                    .map_or(true, |c| !c.is_alphanumeric());
+
+                if let Some(end) = emphasis(after, chr).filter(|_| boundary) {
+                    let _ =
+                        write!(buffer, "<em>{}</em>", inline(&after[..end]));
+                    rest = &after[end + 1..];
+                    continue;
+                }
+            }
+            _ => {}
+        }
+
+        buffer.push_str(&escape(&rest[..chr.len_utf8()]));
+        rest = after;
+    }
+
+    buffer
+}
+
+/// Returns the end of emphasised text, if the text is emphasised.
+fn emphasis(text: &str, delimiter: char) -> Option<usize> {
+    if text.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    text.match_indices(delimiter).map(|(i, _)| i).find(|&i| {
+        i > 0
+            && !text[..i].ends_with(char::is_whitespace)
+            && !text[i + 1..].starts_with(char::is_alphanumeric)
+    })
+}
+
+/// Parses a link of the form `[label](url)`, starting after the `[`.
+fn link(text: &str) -> Option<(&str, &str, &str)> {
+    let label_end = text.find(']')?;
+    let url_part = text[label_end + 1..].strip_prefix('(')?;
+    let url_end = url_part.find(')')?;
+
+    Some((&text[..label_end], &url_part[..url_end], &url_part[url_end + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_blocks() {
+        assert_eq!(
+            markdown(
+                "# Examples\n\nFoo\nbar.\n\n    let a = 10\n\n    a < 5\n",
+                3
+            ),
+            "<h4>Examples</h4>\n<p>Foo bar.</p>\n\
+            <pre><code>let a = 10\n\na &lt; 5</code></pre>\n"
+        );
+        assert_eq!(
+            markdown("- a\n  b\n- c\n\n1. d", 1),
+            "<ul>\n<li>a b</li>\n<li>c</li>\n</ul>\n<ol>\n<li>d</li>\n</ol>\n"
+        );
+        assert_eq!(
+            markdown("```inko\nfoo\n```", 1),
+            "<pre><code class=\"language-inko\">foo</code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_headings() {
+        assert_eq!(markdown("# A\n## B", 2), "<h3>A</h3>\n<h4>B</h4>\n");
+        assert_eq!(markdown("## A", 5), "<h6>A</h6>\n");
+        assert_eq!(markdown("#A", 1), "<p>#A</p>\n");
+    }
+
+    #[test]
+    fn test_markdown_inline() {
+        assert_eq!(inline("a `<b>` c"), "a <code>&lt;b&gt;</code> c");
+        assert_eq!(inline("_a_ foo_bar_baz"), "<em>a</em> foo_bar_baz");
+        assert_eq!(inline("**a** *b*"), "<strong>a</strong> <em>b</em>");
+        assert_eq!(
+            inline("see [the docs](https://inko-lang.org)"),
+            "see <a href=\"https://inko-lang.org\">the docs</a>"
+        );
+        assert_eq!(inline("a * b * c"), "a * b * c");
+        assert_eq!(inline("[a] b"), "[a] b");
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(summary("Foo\nbar\n\nBaz"), "Foo\nbar");
+        assert_eq!(summary(""), "");
+    }
+}
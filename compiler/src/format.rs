@@ -0,0 +1,1860 @@
+//! Formatting of Inko source code.
+//!
+//! Source code is formatted by parsing it into an AST, turning the AST into a
+//! document describing the layout of the code, then printing that document.
+//! Comments aren't part of the AST, so these are obtained separately using the
+//! lexer, and are placed before the expressions they originally preceded.
+mod document;
+
+use crate::format::document::{Doc, Printer};
+use ast::lexer::{Lexer, TokenKind};
+use ast::nodes::{
+    Argument, Arguments, Call, ClassExpression, ClassKind, Closure, Constant,
+    DefineClass, DefineMethod, DefineTrait, DoubleStringLiteral,
+    DoubleStringValue, Expression, Expressions, If, ImplementTrait, Import,
+    MatchCase, MethodKind, Module, Node, OperatorKind, Pattern, ReferrableType,
+    ReopenClass, Requirement, Requirements, StringLiteral, TopLevelExpression,
+    Type, TypeBounds, TypeName, TypeParameters,
+};
+use ast::parser::Parser;
+use ast::source_location::SourceLocation;
+use std::path::Path;
+use std::rc::Rc;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The maximum number of columns a line should occupy.
+const LINE_WIDTH: usize = 80;
+
+/// The number of method calls a chain must contain before we break it up over
+/// multiple lines.
+const CHAIN_LENGTH: usize = 3;
+
+fn parse(path: &Path, input: &[u8]) -> Result<Module, String> {
+    Parser::new(input.to_vec(), path.to_path_buf()).parse().map_err(|err| {
+        format!(
+            "{}:{}:{}: {}",
+            path.display(),
+            err.location.line_range.start(),
+            err.location.column_range.start(),
+            err.message
+        )
+    })
+}
+
+/// Formats the source code of a single module.
+///
+/// The `path` argument is only used for reporting syntax errors.
+pub fn format(path: &Path, input: &[u8]) -> Result<String, String> {
+    let module = parse(path, input)?;
+    let source = String::from_utf8_lossy(input);
+    let mut formatter = Formatter::new(&source);
+    let doc = formatter.module(&module);
+    let mut output = Printer::new(LINE_WIDTH).print(&doc);
+
+    if !output.is_empty() {
+        output.push('\n');
+    }
+
+    // Producing invalid code would result in users losing their work, so we
+    // refuse to produce any output in this case.
+    parse(path, output.as_bytes()).map_err(|err| {
+        format!("The formatter produced invalid code, this is a bug: {}", err)
+    })?;
+
+    Ok(output)
+}
+
+fn operator(kind: &OperatorKind) -> &'static str {
+    match kind {
+        OperatorKind::Add => "+",
+        OperatorKind::BitAnd => "&",
+        OperatorKind::BitOr => "|",
+        OperatorKind::BitXor => "^",
+        OperatorKind::Div => "/",
+        OperatorKind::Eq => "==",
+        OperatorKind::Ge => ">=",
+        OperatorKind::Gt => ">",
+        OperatorKind::Le => "<=",
+        OperatorKind::Lt => "<",
+        OperatorKind::Mod => "%",
+        OperatorKind::Mul => "*",
+        OperatorKind::Ne => "!=",
+        OperatorKind::Pow => "**",
+        OperatorKind::Shl => "<<",
+        OperatorKind::Shr => ">>",
+        OperatorKind::Sub => "-",
+        OperatorKind::UnsignedShr => ">>>",
+    }
+}
+
+fn constant(node: &Constant) -> String {
+    if let Some(source) = &node.source {
+        format!("{}.{}", source.name, node.name)
+    } else {
+        node.name.clone()
+    }
+}
+
+fn type_name(node: &TypeName) -> String {
+    let mut name = constant(&node.name);
+
+    if let Some(args) = &node.arguments {
+        name.push('[');
+        name.push_str(&join(args.values.iter().map(type_reference), ", "));
+        name.push(']');
+    }
+
+    name
+}
+
+fn type_reference(node: &Type) -> String {
+    match node {
+        Type::Named(n) => type_name(n),
+        Type::Ref(n) => format!("ref {}", referrable_type(&n.type_reference)),
+        Type::Mut(n) => format!("mut {}", referrable_type(&n.type_reference)),
+        Type::Uni(n) => format!("uni {}", referrable_type(&n.type_reference)),
+        Type::Closure(n) => closure_type(&n.arguments, &n.return_type),
+        Type::Tuple(n) => tuple_type(&n.values),
+    }
+}
+
+fn referrable_type(node: &ReferrableType) -> String {
+    match node {
+        ReferrableType::Named(n) => type_name(n),
+        ReferrableType::Closure(n) => {
+            closure_type(&n.arguments, &n.return_type)
+        }
+        ReferrableType::Tuple(n) => tuple_type(&n.values),
+    }
+}
+
+fn tuple_type(values: &[Type]) -> String {
+    format!("({})", join(values.iter().map(type_reference), ", "))
+}
+
+fn closure_type(
+    arguments: &Option<ast::nodes::Types>,
+    return_type: &Option<Type>,
+) -> String {
+    let mut name = "fn".to_string();
+
+    if let Some(args) = arguments.as_ref().filter(|a| !a.values.is_empty()) {
+        name.push_str(" (");
+        name.push_str(&join(args.values.iter().map(type_reference), ", "));
+        name.push(')');
+    }
+
+    if let Some(typ) = return_type {
+        name.push_str(" -> ");
+        name.push_str(&type_reference(typ));
+    }
+
+    name
+}
+
+fn requirements(node: &Requirements) -> String {
+    join(
+        node.values.iter().map(|req| match req {
+            Requirement::Trait(n) => type_name(n),
+            Requirement::Mutable(_) => "mut".to_string(),
+        }),
+        " + ",
+    )
+}
+
+fn type_bounds(node: &TypeBounds) -> String {
+    let bounds = node.values.iter().map(|bound| {
+        format!("{}: {}", bound.name.name, requirements(&bound.requirements))
+    });
+
+    format!(" if {}", join(bounds, ", "))
+}
+
+fn lines(location: &SourceLocation) -> (usize, usize) {
+    (*location.line_range.start(), *location.line_range.end())
+}
+
+/// Returns the last line of an expression.
+///
+/// The location of a method call doesn't include a trailing closure passed to
+/// it (e.g. `foo(10) fn { ... }`), so we have to look at the arguments to
+/// obtain the correct line.
+fn end_line(node: &Expression) -> usize {
+    let line = *node.location().line_range.end();
+    let last = match node {
+        Expression::Call(n) => {
+            n.arguments.as_ref().and_then(|a| a.values.last()).map(|arg| {
+                match arg {
+                    Argument::Positional(n) => n,
+                    Argument::Named(n) => &n.value,
+                }
+            })
+        }
+        Expression::Binary(n) => Some(&n.right),
+        Expression::And(n) => Some(&n.right),
+        Expression::Or(n) => Some(&n.right),
+        Expression::AssignVariable(n) => Some(&n.value),
+        Expression::ReplaceVariable(n) => Some(&n.value),
+        Expression::AssignField(n) => Some(&n.value),
+        Expression::ReplaceField(n) => Some(&n.value),
+        Expression::AssignSetter(n) => Some(&n.value),
+        Expression::BinaryAssignVariable(n) => Some(&n.value),
+        Expression::BinaryAssignField(n) => Some(&n.value),
+        Expression::BinaryAssignSetter(n) => Some(&n.value),
+        Expression::DefineVariable(n) => Some(&n.value),
+        Expression::Ref(n) => Some(&n.value),
+        Expression::Mut(n) => Some(&n.value),
+        Expression::Throw(n) => Some(&n.value),
+        Expression::Return(n) => n.value.as_ref(),
+        Expression::Try(n) => Some(&n.expression),
+        Expression::Recover(n) => n.body.values.last(),
+        _ => None,
+    };
+
+    last.map_or(line, |expr| end_line(expr).max(line))
+}
+
+/// Returns `true` if the given expression can be spread over multiple lines
+/// when it's the last value of a list.
+fn expandable(node: &Expression) -> bool {
+    matches!(
+        node,
+        Expression::Closure(_)
+            | Expression::ClassLiteral(_)
+            | Expression::Array(_)
+    )
+}
+
+fn join<I: Iterator<Item = String>>(values: I, separator: &str) -> String {
+    values.collect::<Vec<_>>().join(separator)
+}
+
+fn escape_single(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+fn escape_double(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for chr in value.chars() {
+        match chr {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '{' => escaped.push_str("\\{"),
+            '\0' => escaped.push_str("\\0"),
+            '\x1b' => escaped.push_str("\\e"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            _ => escaped.push(chr),
+        }
+    }
+
+    escaped
+}
+
+/// A comment that has yet to be placed in the output.
+struct Comment {
+    /// The line the comment is placed on.
+    line: usize,
+
+    /// The text of the comment, including the leading `#`.
+    text: String,
+}
+
+/// Settings for formatting a list of values.
+#[derive(Copy, Clone)]
+struct List<'a> {
+    /// The token that opens the list.
+    open: &'a str,
+
+    /// The token that closes the list.
+    close: &'a str,
+
+    /// If spaces should be placed between the values and the surrounding
+    /// tokens, such as for `Foo { @a = 10 }`.
+    spaces: bool,
+
+    /// If a trailing comma is allowed when placing values on separate lines.
+    trailing_comma: bool,
+
+    /// If the last value can be spread over multiple lines while keeping the
+    /// other values on the same line.
+    expand_last: bool,
+
+    /// If values should be placed on the same line until the line is full,
+    /// instead of placing each value on its own line.
+    fill: bool,
+}
+
+impl<'a> List<'a> {
+    fn new(open: &'a str, close: &'a str) -> List<'a> {
+        List {
+            open,
+            close,
+            spaces: false,
+            trailing_comma: true,
+            expand_last: false,
+            fill: false,
+        }
+    }
+}
+
+/// A part of a sequence of binary operations.
+enum BinaryPart<'a> {
+    Operator(&'static str, &'a Expression),
+    Cast(&'a Type),
+}
+
+/// A type that turns an AST into a document to print.
+struct Formatter<'a> {
+    /// The lines of the source code, used for obtaining the source code of
+    /// literals and comments as-is.
+    lines: Vec<&'a str>,
+
+    /// All comments in the module, in the order they appear in.
+    comments: Vec<Comment>,
+
+    /// The index of the next comment to place in the output.
+    comment: usize,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(source: &'a str) -> Formatter<'a> {
+        let lines: Vec<_> = source.split('\n').collect();
+        let mut lexer = Lexer::new(source.as_bytes().to_vec());
+        let mut comments = Vec::new();
+
+        loop {
+            let token = lexer.next_token();
+
+            match token.kind {
+                TokenKind::Comment => {
+                    let (line, column) = token.location.line_column();
+
+                    // The lexer strips the first space of a comment, so we get
+                    // the text from the source code to retain it as-is.
+                    let text = lines
+                        .get(line - 1)
+                        .map(|l| {
+                            l.graphemes(true)
+                                .skip(column - 1)
+                                .collect::<String>()
+                                .trim_end()
+                                .to_string()
+                        })
+                        .filter(|text| text.starts_with('#'))
+                        .unwrap_or_else(|| {
+                            format!("# {}", token.value).trim_end().to_string()
+                        });
+
+                    comments.push(Comment { line, text });
+                }
+                TokenKind::Null => break,
+                _ => {}
+            }
+        }
+
+        Formatter { lines, comments, comment: 0 }
+    }
+
+    /// Returns the source code covered by the given location.
+    fn source(&self, location: &SourceLocation) -> Option<String> {
+        let first = *location.line_range.start();
+        let last = *location.line_range.end();
+        let mut source = String::new();
+
+        if first == 0 || first > last || last > self.lines.len() {
+            return None;
+        }
+
+        for line in first..=last {
+            let chars: Vec<_> = self.lines[line - 1].graphemes(true).collect();
+            let start = if line == first {
+                location.column_range.start().saturating_sub(1)
+            } else {
+                0
+            };
+            let end = if line == last {
+                *location.column_range.end()
+            } else {
+                chars.len()
+            };
+
+            if start > end || end > chars.len() {
+                return None;
+            }
+
+            source.extend(chars[start..end].iter().copied());
+
+            if line != last {
+                source.push('\n');
+            }
+        }
+
+        Some(source)
+    }
+
+    /// Returns the source code of a string literal as-is.
+    ///
+    /// The lexer replaces escape sequences in strings, so we use the source
+    /// code to retain these sequences.
+    fn string_source(
+        &self,
+        location: &SourceLocation,
+        quote: char,
+    ) -> Option<String> {
+        self.source(location).filter(|source| {
+            source.len() >= 2
+                && source.starts_with(quote)
+                && source.ends_with(quote)
+        })
+    }
+
+    /// Removes and returns the comments that start before the given line.
+    fn comments_before(&mut self, line: usize) -> Vec<Comment> {
+        let start = self.comment;
+
+        while self.comment < self.comments.len()
+            && self.comments[self.comment].line < line
+        {
+            self.comment += 1;
+        }
+
+        self.comments[start..self.comment]
+            .iter_mut()
+            .map(|c| Comment {
+                line: c.line,
+                text: std::mem::take(&mut c.text),
+            })
+            .collect()
+    }
+
+    /// Removes and returns the comment placed at the end of the given line.
+    fn trailing_comment(&mut self, line: usize) -> Option<String> {
+        let comment = self.comments.get_mut(self.comment)?;
+
+        if comment.line == line {
+            self.comment += 1;
+            Some(std::mem::take(&mut comment.text))
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if any comments are placed within the given lines.
+    fn has_comments_in(&self, location: &SourceLocation) -> bool {
+        self.comments[self.comment..]
+            .iter()
+            .take_while(|c| c.line <= *location.line_range.end())
+            .any(|c| c.line >= *location.line_range.start())
+    }
+
+    /// Formats a sequence of nodes that are each placed on a separate line.
+    ///
+    /// The `start` and `end` arguments are the lines of the tokens that
+    /// surround the nodes, such as the curly braces of a method body.
+    ///
+    /// Comments are placed on their own lines before the nodes they precede,
+    /// while a comment at the end of the last line of a node is kept on that
+    /// line. Empty lines between nodes are retained, though multiple empty
+    /// lines are reduced to a single empty line.
+    ///
+    /// The return value is the document, and the number of lines (excluding
+    /// empty lines) it contains.
+    fn lines<T, L, F>(
+        &mut self,
+        nodes: &[T],
+        start: usize,
+        end: usize,
+        span: L,
+        mut format: F,
+    ) -> (Doc, usize)
+    where
+        L: Fn(&T) -> (usize, usize),
+        F: FnMut(&mut Self, &T) -> Doc,
+    {
+        let mut docs = Vec::new();
+        let mut count = 0;
+        let mut last = start;
+        let mut push = |doc: Doc, line: usize, last_line: usize| {
+            if count > 0 {
+                docs.push(Doc::HardLine);
+
+                if line > last + 1 {
+                    docs.push(Doc::HardLine);
+                }
+            }
+
+            docs.push(doc);
+            count += 1;
+            last = last_line;
+        };
+
+        for node in nodes {
+            let (first_line, last_line) = span(node);
+
+            for comment in self.comments_before(first_line) {
+                push(Doc::Text(comment.text), comment.line, comment.line);
+            }
+
+            let mut doc = format(self, node);
+
+            // Comments that remain within the node (e.g. between arguments)
+            // are placed before the node, ensuring we never lose comments.
+            for comment in self.comments_before(last_line) {
+                push(Doc::Text(comment.text), first_line, first_line);
+            }
+
+            if let Some(text) = self.trailing_comment(last_line) {
+                doc = Doc::Nodes(vec![
+                    doc,
+                    Doc::text(format!(" {}", text)),
+                    Doc::BreakParent,
+                ]);
+            }
+
+            push(doc, first_line, last_line);
+        }
+
+        for comment in self.comments_before(end) {
+            push(Doc::Text(comment.text), comment.line, comment.line);
+        }
+
+        (Doc::Nodes(docs), count)
+    }
+
+    /// Surrounds the output of `Formatter::lines()` with curly braces.
+    fn braces(doc: Doc, count: usize) -> Doc {
+        if count == 0 {
+            return Doc::text("{}");
+        }
+
+        Doc::Nodes(vec![
+            Doc::text("{"),
+            Doc::indent(Doc::Nodes(vec![Doc::HardLine, doc])),
+            Doc::HardLine,
+            Doc::text("}"),
+        ])
+    }
+
+    /// Formats a list of values surrounded by the given tokens, such as the
+    /// elements of an array.
+    fn list(open: &str, close: &str, values: Vec<Doc>) -> Doc {
+        Self::list_with(List::new(open, close), values)
+    }
+
+    /// Formats a list of values using the given settings.
+    ///
+    /// The values are placed on a single line if possible. If not, each value
+    /// is placed on its own line. If the last value can be expanded (e.g. it's
+    /// a closure), we first try to keep the other values on the same line as
+    /// the opening token, and only spread the last value over multiple lines.
+    fn list_with(list: List, values: Vec<Doc>) -> Doc {
+        if values.is_empty() {
+            return Doc::text(format!("{}{}", list.open, list.close));
+        }
+
+        let last = values.len() - 1;
+        let last_breaks = values[last].contains_break();
+        let expand = (list.expand_last || last_breaks)
+            && !values[..last].iter().any(|v| v.contains_break());
+        let expanded = if expand && !last_breaks {
+            Some(values[last].broken())
+        } else {
+            None
+        };
+        let values: Vec<_> = values.into_iter().map(Rc::new).collect();
+        let line = || if list.spaces { Doc::Line } else { Doc::SoftLine };
+        let mut multi = vec![line()];
+
+        for (idx, val) in values.iter().enumerate() {
+            let val = Doc::Shared(val.clone());
+
+            if idx == 0 {
+                multi.push(val);
+            } else if list.fill {
+                multi.push(Doc::text(","));
+                multi.push(Doc::group(Doc::Nodes(vec![Doc::Line, val])));
+            } else {
+                multi.push(Doc::text(","));
+                multi.push(Doc::Line);
+                multi.push(val);
+            }
+        }
+
+        if list.trailing_comma {
+            multi.push(Doc::IfBreak(","));
+        }
+
+        let mut doc = Doc::group(Doc::Nodes(vec![
+            Doc::text(list.open),
+            Doc::indent(Doc::Nodes(multi)),
+            line(),
+            Doc::text(list.close),
+        ]));
+
+        if !expand {
+            return doc;
+        }
+
+        let single = |last_doc: Doc| {
+            let pad = if list.spaces { " " } else { "" };
+            let mut docs = vec![Doc::text(format!("{}{}", list.open, pad))];
+
+            for val in &values[..last] {
+                docs.push(Doc::Shared(val.clone()));
+                docs.push(Doc::text(", "));
+            }
+
+            docs.push(last_doc);
+            docs.push(Doc::text(format!("{}{}", pad, list.close)));
+            Doc::Nodes(docs)
+        };
+
+        if let Some(val) = expanded {
+            doc = Doc::choice(single(val), doc);
+        }
+
+        Doc::choice(single(Doc::Shared(values[last].clone())), doc)
+    }
+
+    /// Formats the values of a list, such as the arguments of a method call.
+    ///
+    /// Comments placed before a value are kept before that value.
+    fn list_values<T, L, F>(
+        &mut self,
+        nodes: &[T],
+        line: L,
+        mut format: F,
+    ) -> Vec<Doc>
+    where
+        L: Fn(&T) -> usize,
+        F: FnMut(&mut Self, &T) -> Doc,
+    {
+        let mut values = Vec::with_capacity(nodes.len());
+
+        for node in nodes {
+            let comments = self.comments_before(line(node));
+            let value = format(self, node);
+
+            if comments.is_empty() {
+                values.push(value);
+                continue;
+            }
+
+            let mut docs = Vec::new();
+
+            for comment in comments {
+                docs.push(Doc::Text(comment.text));
+                docs.push(Doc::HardLine);
+            }
+
+            docs.push(value);
+            values.push(Doc::Nodes(docs));
+        }
+
+        values
+    }
+
+    fn module(&mut self, node: &Module) -> Doc {
+        self.lines(
+            &node.expressions,
+            0,
+            usize::MAX,
+            |n| {
+                match n {
+                    // The location of a reopened class with bounds ends at the
+                    // bounds, instead of at the end of its body.
+                    TopLevelExpression::ReopenClass(n) => {
+                        lines(&n.location).0..=lines(&n.body.location).1
+                    }
+                    _ => n.location().line_range.clone(),
+                }
+                .into_inner()
+            },
+            |s, n| s.top_level_expression(n),
+        )
+        .0
+    }
+
+    fn top_level_expression(&mut self, node: &TopLevelExpression) -> Doc {
+        match node {
+            TopLevelExpression::DefineConstant(n) => {
+                let public = if n.public { " pub" } else { "" };
+
+                Doc::Nodes(vec![
+                    Doc::text(format!("let{} {} = ", public, n.name.name)),
+                    self.expression(&n.value),
+                ])
+            }
+            TopLevelExpression::DefineMethod(n) => self.define_method(n),
+            TopLevelExpression::DefineClass(n) => self.define_class(n),
+            TopLevelExpression::DefineTrait(n) => self.define_trait(n),
+            TopLevelExpression::ReopenClass(n) => self.reopen_class(n),
+            TopLevelExpression::ImplementTrait(n) => self.implement_trait(n),
+            TopLevelExpression::Import(n) => self.import(n),
+            TopLevelExpression::ExternImport(n) => {
+                let path = self
+                    .string_source(&n.path.location, '"')
+                    .or_else(|| self.string_source(&n.path.location, '\''))
+                    .unwrap_or_else(|| {
+                        format!("'{}'", escape_single(&n.path.path))
+                    });
+
+                Doc::text(format!("import extern {}", path))
+            }
+        }
+    }
+
+    fn import(&mut self, node: &Import) -> Doc {
+        let path = join(node.path.steps.iter().map(|s| s.name.clone()), ".");
+        let mut docs = vec![Doc::text(format!("import {}", path))];
+
+        if let Some(symbols) = &node.symbols {
+            let mut names: Vec<_> = symbols
+                .values
+                .iter()
+                .map(|sym| {
+                    if let Some(alias) = &sym.alias {
+                        Doc::text(format!("{} as {}", sym.name, alias.name))
+                    } else {
+                        Doc::text(&sym.name)
+                    }
+                })
+                .collect();
+
+            // `import foo.Bar` is the same as `import foo.(Bar)`, but only
+            // constants can be imported without the parentheses.
+            if symbols.values.len() == 1
+                && symbols.location == symbols.values[0].location
+            {
+                docs.push(Doc::text("."));
+                docs.push(names.pop().unwrap());
+            } else {
+                docs.push(Doc::text("."));
+                docs.push(Self::list_with(
+                    List { fill: true, ..List::new("(", ")") },
+                    names,
+                ));
+            }
+        }
+
+        if let Some(tags) = &node.tags {
+            docs.push(Doc::text(format!(
+                " if {}",
+                join(tags.values.iter().map(|t| t.name.clone()), " and ")
+            )));
+        }
+
+        Doc::Nodes(docs)
+    }
+
+    fn type_parameters(node: &Option<TypeParameters>) -> Option<Doc> {
+        let params = node.as_ref()?.values.iter().map(|param| {
+            let mut name = param.name.name.clone();
+
+            if let Some(reqs) = &param.requirements {
+                name.push_str(": ");
+                name.push_str(&requirements(reqs));
+            }
+
+            Doc::Text(name)
+        });
+
+        Some(Self::list("[", "]", params.collect()))
+    }
+
+    fn define_method(&mut self, node: &DefineMethod) -> Doc {
+        let public = if node.public { " pub" } else { "" };
+        let kind = match node.kind {
+            MethodKind::Instance => "",
+            MethodKind::Static => " static",
+            MethodKind::Async => " async",
+            MethodKind::Moving => " move",
+            MethodKind::Mutable => " mut",
+            MethodKind::AsyncMutable => " async mut",
+            MethodKind::Extern => " extern",
+        };
+
+        let mut docs =
+            vec![Doc::text(format!("fn{}{} {}", public, kind, node.name.name))];
+
+        docs.extend(Self::type_parameters(&node.type_parameters));
+
+        if let Some(args) = node
+            .arguments
+            .as_ref()
+            .filter(|a| !a.values.is_empty() || a.variadic)
+        {
+            let mut values: Vec<_> = args
+                .values
+                .iter()
+                .map(|arg| {
+                    Doc::text(format!(
+                        "{}: {}",
+                        arg.name.name,
+                        type_reference(&arg.value_type)
+                    ))
+                })
+                .collect();
+
+            if args.variadic {
+                values.push(Doc::text("..."));
+            }
+
+            docs.push(Self::list_with(
+                List { trailing_comma: !args.variadic, ..List::new("(", ")") },
+                values,
+            ));
+        }
+
+        if let Some(typ) = &node.return_type {
+            docs.push(Doc::text(format!(" -> {}", type_reference(typ))));
+        }
+
+        if let Some(body) = &node.body {
+            docs.push(Doc::text(" "));
+            docs.push(self.block(body, false));
+        }
+
+        Doc::Nodes(docs)
+    }
+
+    fn define_class(&mut self, node: &DefineClass) -> Doc {
+        let public = if node.public { " pub" } else { "" };
+        let kind = match node.kind {
+            ClassKind::Async => " async",
+            ClassKind::Builtin => " builtin",
+            ClassKind::Enum => " enum",
+            ClassKind::Regular => "",
+            ClassKind::Extern => " extern",
+        };
+
+        let mut docs = vec![Doc::text(format!(
+            "class{}{} {}",
+            public, kind, node.name.name
+        ))];
+
+        docs.extend(Self::type_parameters(&node.type_parameters));
+
+        let (body, count) = self.lines(
+            &node.body.values,
+            *node.body.location.line_range.start(),
+            *node.body.location.line_range.end(),
+            |n| match n {
+                ClassExpression::DefineMethod(n) => lines(&n.location),
+                ClassExpression::DefineField(n) => lines(&n.location),
+                ClassExpression::DefineVariant(n) => lines(&n.location),
+            },
+            |s, n| match n {
+                ClassExpression::DefineMethod(n) => s.define_method(n),
+                ClassExpression::DefineField(n) => {
+                    let public = if n.public { " pub" } else { "" };
+
+                    Doc::text(format!(
+                        "let{} @{}: {}",
+                        public,
+                        n.name.name,
+                        type_reference(&n.value_type)
+                    ))
+                }
+                ClassExpression::DefineVariant(n) => {
+                    let mut docs =
+                        vec![Doc::text(format!("case {}", n.name.name))];
+
+                    if let Some(members) = &n.members {
+                        let types = members
+                            .values
+                            .iter()
+                            .map(|t| Doc::Text(type_reference(t)))
+                            .collect();
+
+                        docs.push(Self::list("(", ")", types));
+                    }
+
+                    Doc::Nodes(docs)
+                }
+            },
+        );
+
+        docs.push(Doc::text(" "));
+        docs.push(Self::braces(body, count));
+        Doc::Nodes(docs)
+    }
+
+    fn define_trait(&mut self, node: &DefineTrait) -> Doc {
+        let public = if node.public { " pub" } else { "" };
+        let mut docs =
+            vec![Doc::text(format!("trait{} {}", public, node.name.name))];
+
+        docs.extend(Self::type_parameters(&node.type_parameters));
+
+        if let Some(reqs) = &node.requirements {
+            docs.push(Doc::text(format!(
+                ": {}",
+                join(reqs.values.iter().map(type_name), " + ")
+            )));
+        }
+
+        docs.push(Doc::text(" "));
+        docs.push(self.methods(&node.body.values, &node.body.location));
+        Doc::Nodes(docs)
+    }
+
+    fn reopen_class(&mut self, node: &ReopenClass) -> Doc {
+        let mut head = format!("impl {}", node.class_name.name);
+
+        if let Some(bounds) = &node.bounds {
+            head.push_str(&type_bounds(bounds));
+        }
+
+        head.push(' ');
+
+        Doc::Nodes(vec![
+            Doc::Text(head),
+            self.methods(&node.body.values, &node.body.location),
+        ])
+    }
+
+    fn implement_trait(&mut self, node: &ImplementTrait) -> Doc {
+        let mut head = format!(
+            "impl {} for {}",
+            type_name(&node.trait_name),
+            node.class_name.name
+        );
+
+        if let Some(bounds) = &node.bounds {
+            head.push_str(&type_bounds(bounds));
+        }
+
+        head.push(' ');
+
+        Doc::Nodes(vec![
+            Doc::Text(head),
+            self.methods(&node.body.values, &node.body.location),
+        ])
+    }
+
+    fn methods(
+        &mut self,
+        nodes: &[DefineMethod],
+        location: &SourceLocation,
+    ) -> Doc {
+        let (doc, count) = self.lines(
+            nodes,
+            *location.line_range.start(),
+            *location.line_range.end(),
+            |n| lines(&n.location),
+            |s, n| s.define_method(n),
+        );
+
+        Self::braces(doc, count)
+    }
+
+    /// Formats a body of expressions surrounded by curly braces.
+    ///
+    /// If `flatten` is `true` and the body consists of a single expression,
+    /// the body may be placed on a single line. In this case the caller is
+    /// responsible for wrapping the result in a group.
+    fn block(&mut self, node: &Expressions, flatten: bool) -> Doc {
+        let (doc, count) = self.lines(
+            &node.values,
+            *node.location.line_range.start(),
+            *node.location.line_range.end(),
+            |n| (*n.location().line_range.start(), end_line(n)),
+            |s, n| s.expression(n),
+        );
+
+        if flatten && count == 1 && !doc.contains_break() {
+            Doc::Nodes(vec![
+                Doc::text("{"),
+                Doc::indent(Doc::Nodes(vec![Doc::Line, doc])),
+                Doc::Line,
+                Doc::text("}"),
+            ])
+        } else {
+            Self::braces(doc, count)
+        }
+    }
+
+    /// Formats a body of which the curly braces are optional, such as the body
+    /// of a `match` case.
+    ///
+    /// If the body is a single expression that doesn't fit on the current
+    /// line, the curly braces are added back and the expression is placed on
+    /// its own line.
+    fn optional_block(&mut self, node: &Expressions) -> Doc {
+        if node.values.len() != 1 || self.has_comments_in(&node.location) {
+            return Doc::group(self.block(node, true));
+        }
+
+        let expr = self.expression(&node.values[0]);
+
+        if expr.contains_break() {
+            return expr;
+        }
+
+        let expr = Rc::new(expr);
+
+        Doc::choice(
+            Doc::Shared(expr.clone()),
+            Doc::Nodes(vec![
+                Doc::text("{"),
+                Doc::indent(Doc::Nodes(vec![Doc::HardLine, Doc::Shared(expr)])),
+                Doc::HardLine,
+                Doc::text("}"),
+            ]),
+        )
+    }
+
+    fn expression(&mut self, node: &Expression) -> Doc {
+        match node {
+            Expression::Int(n) => Doc::text(&n.value),
+            Expression::Float(n) => Doc::text(&n.value),
+            Expression::SingleString(n) => self.single_string(n),
+            Expression::DoubleString(n) => self.double_string(n),
+            Expression::Binary(_) | Expression::TypeCast(_) => {
+                self.binary(node)
+            }
+            Expression::And(_) | Expression::Or(_) => self.boolean(node),
+            Expression::Field(n) => Doc::text(format!("@{}", n.name)),
+            Expression::Constant(n) => Doc::Text(constant(n)),
+            Expression::Identifier(n) => Doc::text(&n.name),
+            Expression::Call(n) => self.call(n),
+            Expression::AssignVariable(n) => {
+                self.assign(&n.variable.name, "=", &n.value)
+            }
+            Expression::ReplaceVariable(n) => {
+                self.assign(&n.variable.name, ":=", &n.value)
+            }
+            Expression::AssignField(n) => {
+                self.assign(&format!("@{}", n.field.name), "=", &n.value)
+            }
+            Expression::ReplaceField(n) => {
+                self.assign(&format!("@{}", n.field.name), ":=", &n.value)
+            }
+            Expression::AssignSetter(n) => {
+                let receiver = self.expression(&n.receiver);
+                let assign =
+                    self.assign(&format!(".{}", n.name.name), "=", &n.value);
+
+                Doc::Nodes(vec![receiver, assign])
+            }
+            Expression::BinaryAssignVariable(n) => {
+                let op = format!("{}=", operator(&n.operator.kind));
+
+                self.assign(&n.variable.name, &op, &n.value)
+            }
+            Expression::BinaryAssignField(n) => {
+                let op = format!("{}=", operator(&n.operator.kind));
+
+                self.assign(&format!("@{}", n.field.name), &op, &n.value)
+            }
+            Expression::BinaryAssignSetter(n) => {
+                let op = format!("{}=", operator(&n.operator.kind));
+                let receiver = self.expression(&n.receiver);
+                let assign =
+                    self.assign(&format!(".{}", n.name.name), &op, &n.value);
+
+                Doc::Nodes(vec![receiver, assign])
+            }
+            Expression::Closure(n) => self.closure(n),
+            Expression::DefineVariable(n) => {
+                let mut name = "let ".to_string();
+
+                if n.mutable {
+                    name.push_str("mut ");
+                }
+
+                name.push_str(&n.name.name);
+
+                if let Some(typ) = &n.value_type {
+                    name.push_str(": ");
+                    name.push_str(&type_reference(typ));
+                }
+
+                self.assign(&name, "=", &n.value)
+            }
+            Expression::SelfObject(_) => Doc::text("self"),
+            Expression::Group(n) => Doc::Nodes(vec![
+                Doc::text("("),
+                self.expression(&n.value),
+                Doc::text(")"),
+            ]),
+            Expression::Next(_) => Doc::text("next"),
+            Expression::Break(_) => Doc::text("break"),
+            Expression::Ref(n) => self.prefix("ref ", &n.value),
+            Expression::Mut(n) => self.prefix("mut ", &n.value),
+            Expression::Recover(n) => Doc::Nodes(vec![
+                Doc::text("recover "),
+                self.optional_block(&n.body),
+            ]),
+            Expression::Throw(n) => self.prefix("throw ", &n.value),
+            Expression::Return(n) => match &n.value {
+                Some(value) => self.prefix("return ", value),
+                None => Doc::text("return"),
+            },
+            Expression::Try(n) => self.prefix("try ", &n.expression),
+            Expression::If(n) => self.if_expression(n),
+            Expression::Match(n) => {
+                let expr = self.expression(&n.expression);
+                let (cases, count) = self.lines(
+                    &n.cases,
+                    *n.expression.location().line_range.end(),
+                    *n.location.line_range.end(),
+                    |n| {
+                        let end = n.body.values.last().map_or(0, end_line);
+
+                        (lines(&n.location).0, end.max(lines(&n.location).1))
+                    },
+                    |s, n| s.match_case(n),
+                );
+
+                Doc::Nodes(vec![
+                    Doc::text("match "),
+                    expr,
+                    Doc::text(" "),
+                    Self::braces(cases, count),
+                ])
+            }
+            Expression::Loop(n) => Doc::group(Doc::Nodes(vec![
+                Doc::text("loop "),
+                self.block(&n.body, true),
+            ])),
+            Expression::While(n) => {
+                let cond = self.expression(&n.condition);
+
+                Doc::group(Doc::Nodes(vec![
+                    Doc::text("while "),
+                    cond,
+                    Doc::text(" "),
+                    self.block(&n.body, true),
+                ]))
+            }
+            Expression::True(_) => Doc::text("true"),
+            Expression::False(_) => Doc::text("false"),
+            Expression::Nil(_) => Doc::text("nil"),
+            Expression::ClassLiteral(n) => {
+                let fields = self.list_values(
+                    &n.fields,
+                    |f| lines(&f.location).0,
+                    |s, f| {
+                        Doc::Nodes(vec![
+                            Doc::text(format!("@{} = ", f.field.name)),
+                            s.expression(&f.value),
+                        ])
+                    },
+                );
+                let open = format!("{} {{", constant(&n.class_name));
+                let expand_last =
+                    n.fields.last().map_or(false, |f| expandable(&f.value));
+
+                Self::list_with(
+                    List { spaces: true, expand_last, ..List::new(&open, "}") },
+                    fields,
+                )
+            }
+            Expression::Scope(n) => Doc::group(self.block(&n.body, true)),
+            Expression::Array(n) => {
+                let values = self.list_values(
+                    &n.values,
+                    |v| lines(v.location()).0,
+                    |s, v| s.expression(v),
+                );
+
+                // Arrays of numbers (e.g. lookup tables) would take up a lot
+                // of lines when placing every value on its own line.
+                let fill = n.values.iter().all(|v| {
+                    matches!(
+                        v,
+                        Expression::Int(_)
+                            | Expression::Float(_)
+                            | Expression::Constant(_)
+                    )
+                });
+                let expand_last = n.values.last().map_or(false, expandable);
+
+                Self::list_with(
+                    List { fill, expand_last, ..List::new("[", "]") },
+                    values,
+                )
+            }
+            Expression::Tuple(n) => {
+                let mut values = self.list_values(
+                    &n.values,
+                    |v| lines(v.location()).0,
+                    |s, v| s.expression(v),
+                );
+
+                // A tuple with a single value requires a trailing comma, as
+                // it would otherwise be parsed as a grouped expression.
+                if values.len() == 1 {
+                    Doc::Nodes(vec![
+                        Doc::text("("),
+                        values.pop().unwrap(),
+                        Doc::text(",)"),
+                    ])
+                } else {
+                    Self::list("(", ")", values)
+                }
+            }
+        }
+    }
+
+    fn prefix(&mut self, prefix: &str, value: &Expression) -> Doc {
+        Doc::Nodes(vec![Doc::text(prefix), self.expression(value)])
+    }
+
+    fn assign(
+        &mut self,
+        name: &str,
+        operator: &str,
+        value: &Expression,
+    ) -> Doc {
+        Doc::Nodes(vec![
+            Doc::text(format!("{} {} ", name, operator)),
+            self.expression(value),
+        ])
+    }
+
+    fn single_string(&mut self, node: &StringLiteral) -> Doc {
+        let source =
+            self.string_source(&node.location, '\'').unwrap_or_else(|| {
+                let value =
+                    node.value.as_ref().map_or("", |v| v.value.as_str());
+
+                format!("'{}'", escape_single(value))
+            });
+
+        Doc::Text(source)
+    }
+
+    fn double_string(&mut self, node: &DoubleStringLiteral) -> Doc {
+        if let Some(source) = self.string_source(&node.location, '"') {
+            return Doc::Text(source);
+        }
+
+        let mut docs = vec![Doc::text("\"")];
+
+        for value in &node.values {
+            match value {
+                DoubleStringValue::Text(n) => {
+                    docs.push(Doc::Text(escape_double(&n.value)))
+                }
+                DoubleStringValue::Expression(n) => {
+                    docs.push(Doc::text("{"));
+                    docs.push(self.expression(&n.value));
+                    docs.push(Doc::text("}"));
+                }
+            }
+        }
+
+        docs.push(Doc::text("\""));
+        Doc::Nodes(docs)
+    }
+
+    /// Formats a sequence of binary operations and type casts.
+    ///
+    /// Binary operators all have the same precedence, so `a + b * c` is parsed
+    /// as `(a + b) * c`. This means we only need to walk down the left-hand
+    /// side to obtain all operations of the sequence.
+    fn binary(&mut self, node: &Expression) -> Doc {
+        let mut parts = Vec::new();
+        let mut current = node;
+
+        loop {
+            match current {
+                Expression::Binary(n) => {
+                    parts.push(BinaryPart::Operator(
+                        operator(&n.operator.kind),
+                        &n.right,
+                    ));
+                    current = &n.left;
+                }
+                Expression::TypeCast(n) => {
+                    parts.push(BinaryPart::Cast(&n.cast_to));
+                    current = &n.value;
+                }
+                _ => break,
+            }
+        }
+
+        let first = self.expression(current);
+        let mut rest = Vec::new();
+
+        for part in parts.into_iter().rev() {
+            match part {
+                BinaryPart::Operator(op, rhs) => {
+                    rest.push(Doc::Line);
+                    rest.push(Doc::text(format!("{} ", op)));
+                    rest.push(self.expression(rhs));
+                }
+                BinaryPart::Cast(typ) => {
+                    rest.push(Doc::text(format!(" as {}", type_reference(typ))))
+                }
+            }
+        }
+
+        Doc::group(Doc::Nodes(vec![first, Doc::indent(Doc::Nodes(rest))]))
+    }
+
+    /// Formats a sequence of `and` and `or` expressions.
+    fn boolean(&mut self, node: &Expression) -> Doc {
+        let mut parts = Vec::new();
+        let mut current = node;
+
+        loop {
+            match current {
+                Expression::And(n) => {
+                    parts.push(("and ", &n.right));
+                    current = &n.left;
+                }
+                Expression::Or(n) => {
+                    parts.push(("or ", &n.right));
+                    current = &n.left;
+                }
+                _ => break,
+            }
+        }
+
+        let first = self.expression(current);
+        let mut rest = Vec::new();
+
+        for (op, rhs) in parts.into_iter().rev() {
+            rest.push(Doc::Line);
+            rest.push(Doc::text(op));
+            rest.push(self.expression(rhs));
+        }
+
+        Doc::group(Doc::Nodes(vec![first, Doc::indent(Doc::Nodes(rest))]))
+    }
+
+    fn call(&mut self, node: &Call) -> Doc {
+        let mut calls = vec![node];
+        let mut receiver = node.receiver.as_ref();
+
+        while let Some(Expression::Call(call)) = receiver {
+            if call.receiver.is_none() {
+                break;
+            }
+
+            calls.push(call);
+            receiver = call.receiver.as_ref();
+        }
+
+        let (receiver, short) = match receiver {
+            Some(expr) => (
+                self.expression(expr),
+                matches!(
+                    expr,
+                    Expression::Identifier(_)
+                        | Expression::Constant(_)
+                        | Expression::Field(_)
+                        | Expression::SelfObject(_)
+                        | Expression::Int(_)
+                ),
+            ),
+            None => {
+                return self.call_arguments(&node.name.name, &node.arguments)
+            }
+        };
+
+        let calls: Vec<_> = calls
+            .into_iter()
+            .rev()
+            .map(|call| {
+                Doc::Nodes(vec![
+                    Doc::text("."),
+                    self.call_arguments(&call.name.name, &call.arguments),
+                ])
+            })
+            .collect();
+
+        // If the chain ends with a closure, we break up the closure instead of
+        // the chain.
+        let closure = matches!(
+            node.arguments.as_ref().and_then(|a| a.values.last()),
+            Some(Argument::Positional(Expression::Closure(_)))
+        );
+
+        if calls.len() < CHAIN_LENGTH
+            || closure
+            || calls.iter().any(|c| c.contains_break())
+        {
+            return Doc::Nodes(
+                Some(receiver).into_iter().chain(calls).collect(),
+            );
+        }
+
+        let mut calls = calls.into_iter();
+        let mut head = vec![receiver];
+        let mut chain = Vec::new();
+
+        // A short receiver (e.g. `foo` in `foo.bar.baz.quix`) stays on the same
+        // line as the first call, as placing the first call on a new line
+        // wouldn't save any space.
+        if short {
+            head.extend(calls.next());
+        }
+
+        for call in calls {
+            chain.push(Doc::SoftLine);
+            chain.push(call);
+        }
+
+        head.push(Doc::indent(Doc::Nodes(chain)));
+        Doc::group(Doc::Nodes(head))
+    }
+
+    fn call_arguments(
+        &mut self,
+        name: &str,
+        arguments: &Option<Arguments>,
+    ) -> Doc {
+        let args = match arguments {
+            Some(args) => args,
+            None => return Doc::text(name),
+        };
+
+        // For `foo(a) fn { ... }` the closure is the last argument, but it's
+        // placed after the parentheses.
+        let paren_end = lines(&args.location).1;
+        let paren_end = (paren_end, *args.location.column_range.end());
+        let trailing = match args.values.last() {
+            Some(Argument::Positional(Expression::Closure(n))) => {
+                n.location == args.location
+                    || n.location.line_column() > paren_end
+            }
+            _ => false,
+        };
+
+        let mut values = self.list_values(
+            &args.values,
+            |arg| match arg {
+                Argument::Positional(n) => lines(n.location()).0,
+                Argument::Named(n) => lines(&n.location).0,
+            },
+            |s, arg| s.argument(arg),
+        );
+        let expand_last = match args.values.last() {
+            Some(Argument::Positional(n)) => expandable(n),
+            Some(Argument::Named(n)) => expandable(&n.value),
+            None => false,
+        };
+        let list = List { expand_last, ..List::new("(", ")") };
+
+        if !trailing {
+            return Doc::Nodes(vec![
+                Doc::text(name),
+                Self::list_with(list, values),
+            ]);
+        }
+
+        if values.len() == 1 {
+            return Doc::Nodes(vec![
+                Doc::text(format!("{} ", name)),
+                values.pop().unwrap(),
+            ]);
+        }
+
+        // A trailing closure must start on the same line as the name of the
+        // method, otherwise it's parsed as a separate expression. If the other
+        // arguments don't fit on that line, we pass the closure as a regular
+        // argument instead.
+        let closure = values.pop().unwrap();
+
+        if values.iter().any(|v| v.contains_break()) {
+            values.push(closure);
+
+            return Doc::Nodes(vec![
+                Doc::text(name),
+                Self::list_with(list, values),
+            ]);
+        }
+
+        let values: Vec<_> = values.into_iter().map(Rc::new).collect();
+        let closure = Rc::new(closure);
+        let mut single = vec![Doc::text(format!("{}(", name))];
+
+        for (idx, val) in values.iter().enumerate() {
+            if idx > 0 {
+                single.push(Doc::text(", "));
+            }
+
+            single.push(Doc::Shared(val.clone()));
+        }
+
+        single.push(Doc::text(") "));
+        single.push(Doc::Shared(closure.clone()));
+
+        let mut multi: Vec<_> = values.into_iter().map(Doc::Shared).collect();
+
+        multi.push(Doc::Shared(closure));
+
+        Doc::choice(
+            Doc::Nodes(single),
+            Doc::Nodes(vec![Doc::text(name), Self::list_with(list, multi)]),
+        )
+    }
+
+    fn argument(&mut self, node: &Argument) -> Doc {
+        match node {
+            Argument::Positional(n) => self.expression(n),
+            Argument::Named(n) => Doc::Nodes(vec![
+                Doc::text(format!("{}: ", n.name.name)),
+                self.expression(&n.value),
+            ]),
+        }
+    }
+
+    fn closure(&mut self, node: &Closure) -> Doc {
+        let mut docs =
+            vec![Doc::text(if node.moving { "fn move" } else { "fn" })];
+
+        if let Some(args) =
+            node.arguments.as_ref().filter(|a| !a.values.is_empty())
+        {
+            let values = args
+                .values
+                .iter()
+                .map(|arg| match &arg.value_type {
+                    Some(typ) => Doc::text(format!(
+                        "{}: {}",
+                        arg.name.name,
+                        type_reference(typ)
+                    )),
+                    None => Doc::text(&arg.name.name),
+                })
+                .collect();
+
+            docs.push(Doc::text(" "));
+            docs.push(Self::list("(", ")", values));
+        }
+
+        if let Some(typ) = &node.return_type {
+            docs.push(Doc::text(format!(" -> {}", type_reference(typ))));
+        }
+
+        docs.push(Doc::text(" "));
+        docs.push(self.block(&node.body, true));
+        Doc::group(Doc::Nodes(docs))
+    }
+
+    fn if_expression(&mut self, node: &If) -> Doc {
+        let mut docs = vec![
+            Doc::text("if "),
+            self.expression(&node.if_true.condition),
+            Doc::text(" "),
+            self.block(&node.if_true.body, true),
+        ];
+
+        for cond in &node.else_if {
+            docs.push(Doc::text(" else if "));
+            docs.push(self.expression(&cond.condition));
+            docs.push(Doc::text(" "));
+            docs.push(self.block(&cond.body, true));
+        }
+
+        if let Some(body) = &node.else_body {
+            docs.push(Doc::text(" else "));
+            docs.push(self.block(body, true));
+        }
+
+        Doc::group(Doc::Nodes(docs))
+    }
+
+    fn match_case(&mut self, node: &MatchCase) -> Doc {
+        let mut docs = vec![Doc::text("case "), self.pattern(&node.pattern)];
+
+        if let Some(guard) = &node.guard {
+            docs.push(Doc::text(" if "));
+            docs.push(self.expression(guard));
+        }
+
+        docs.push(Doc::text(" -> "));
+        docs.push(self.optional_block(&node.body));
+        Doc::Nodes(docs)
+    }
+
+    fn pattern(&mut self, node: &Pattern) -> Doc {
+        match node {
+            Pattern::Constant(n) => Doc::Text(constant(n)),
+            Pattern::Variant(n) => {
+                let values = n.values.iter().map(|v| self.pattern(v)).collect();
+
+                Doc::Nodes(vec![
+                    Doc::text(&n.name.name),
+                    self.patterns("(", ")", values, false),
+                ])
+            }
+            Pattern::Class(n) => {
+                let values = n
+                    .values
+                    .iter()
+                    .map(|v| {
+                        Doc::Nodes(vec![
+                            Doc::text(format!("@{} = ", v.field.name)),
+                            self.pattern(&v.pattern),
+                        ])
+                    })
+                    .collect();
+
+                self.patterns("{", "}", values, true)
+            }
+            Pattern::Expression(n) => self.expression(n),
+            Pattern::Identifier(n) => {
+                let mut name = String::new();
+
+                if n.mutable {
+                    name.push_str("mut ");
+                }
+
+                name.push_str(&n.name.name);
+
+                if let Some(typ) = &n.value_type {
+                    name.push_str(": ");
+                    name.push_str(&type_reference(typ));
+                }
+
+                Doc::Text(name)
+            }
+            Pattern::Tuple(n) => {
+                let values = n.values.iter().map(|v| self.pattern(v)).collect();
+
+                self.patterns("(", ")", values, false)
+            }
+            Pattern::Wildcard(_) => Doc::text("_"),
+            Pattern::Or(n) => {
+                let mut docs = Vec::new();
+
+                for (idx, pat) in n.patterns.iter().enumerate() {
+                    if idx > 0 {
+                        docs.push(Doc::text(" or "));
+                    }
+
+                    docs.push(self.pattern(pat));
+                }
+
+                Doc::Nodes(docs)
+            }
+            Pattern::String(n) => {
+                let source = self
+                    .string_source(&n.location, '\'')
+                    .or_else(|| self.string_source(&n.location, '"'))
+                    .unwrap_or_else(|| {
+                        format!("'{}'", escape_single(&n.value))
+                    });
+
+                Doc::Text(source)
+            }
+        }
+    }
+
+    /// Formats a list of patterns.
+    ///
+    /// Unlike other lists, patterns don't allow trailing commas and are always
+    /// placed on a single line.
+    fn patterns(
+        &mut self,
+        open: &str,
+        close: &str,
+        values: Vec<Doc>,
+        spaces: bool,
+    ) -> Doc {
+        if values.is_empty() {
+            return Doc::text(format!("{}{}", open, close));
+        }
+
+        let pad = if spaces { " " } else { "" };
+        let mut docs = vec![Doc::text(format!("{}{}", open, pad))];
+
+        for (idx, val) in values.into_iter().enumerate() {
+            if idx > 0 {
+                docs.push(Doc::text(", "));
+            }
+
+            docs.push(val);
+        }
+
+        docs.push(Doc::text(format!("{}{}", pad, close)));
+        Doc::Nodes(docs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(input: &str) -> String {
+        format(Path::new("test.inko"), input.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_format_idempotent() {
+        let input = "import std.stdio.STDOUT
+
+class async Main {
+  fn async main {
+    STDOUT.new.print('hello')
+  }
+}
+";
+
+        assert_eq!(fmt(input), input);
+    }
+
+    #[test]
+    fn test_format_whitespace() {
+        assert_eq!(
+            fmt("fn  foo(a:Int,b : Int)->Int{a+b}"),
+            "fn foo(a: Int, b: Int) -> Int {\n  a + b\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_format_comments() {
+        let input = "# A
+fn foo {
+  # B
+  let a = 10 # C
+
+
+  # D
+  a
+  # E
+}
+# F
+";
+
+        assert_eq!(
+            fmt(input),
+            "# A
+fn foo {
+  # B
+  let a = 10 # C
+
+  # D
+  a
+  # E
+}
+# F
+"
+        );
+    }
+
+    #[test]
+    fn test_format_trailing_comment_in_single_line_block() {
+        assert_eq!(
+            fmt("fn foo {\n  if a { b } # c\n}"),
+            "fn foo {\n  if a {\n    b # c\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_format_long_arguments() {
+        assert_eq!(
+            fmt("fn foo {\n  bar(aaaaaaaaaaaaaaaaaaaaaaaaaa, bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb, ccccccccccccccc)\n}"),
+            "fn foo {
+  bar(
+    aaaaaaaaaaaaaaaaaaaaaaaaaa,
+    bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb,
+    ccccccccccccccc,
+  )
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_format_trailing_closure() {
+        assert_eq!(
+            fmt("fn foo {\n  a.each(10) fn (v) { v\nv }\n  a.each fn (v) { v }\n}"),
+            "fn foo {
+  a.each(10) fn (v) {
+    v
+    v
+  }
+  a.each fn (v) { v }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_format_strings() {
+        let input = "fn foo {\n  'a\\'b\\n'\n  \"a\\t{10}\\u{1F600}\"\n}\n";
+
+        assert_eq!(fmt(input), input);
+    }
+
+    #[test]
+    fn test_format_strings_with_invalid_locations() {
+        // The lexer counts the escaped "\r\n" as a single column, so the
+        // location of the string can't be used to obtain its source code.
+        let input = "fn foo {\n  \"\\t\\r\\n\\thello\"\n}\n";
+
+        assert_eq!(fmt(input), input);
+    }
+
+    #[test]
+    fn test_format_match() {
+        assert_eq!(
+            fmt(
+                "fn foo {\n  match a { case Some(v) -> { v } case _ -> {} }\n}"
+            ),
+            "fn foo {
+  match a {
+    case Some(v) -> v
+    case _ -> {}
+  }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_format_comments_in_lists() {
+        let input = "fn foo {
+  [
+    # A
+    10,
+    # B
+    20,
+  ]
+}
+";
+
+        assert_eq!(fmt(input), input);
+    }
+
+    #[test]
+    fn test_format_expand_last_argument() {
+        assert_eq!(
+            fmt("fn foo {\n  foo(aaaaaaaaaaaaaaaaaaaa, Foo { @bbbbbbbbbbbbbbbbbbbb = 10, @cccccccccccccccccccc = 20 })\n}"),
+            "fn foo {
+  foo(aaaaaaaaaaaaaaaaaaaa, Foo {
+    @bbbbbbbbbbbbbbbbbbbb = 10,
+    @cccccccccccccccccccc = 20,
+  })
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_format_fill() {
+        assert_eq!(
+            fmt("import std.foo.(Aaaaaaaaaa, Bbbbbbbbbbb, Ccccccccccc, Ddddddddddd, Eeeeeeeeee, Fffffffff)"),
+            "import std.foo.(
+  Aaaaaaaaaa, Bbbbbbbbbbb, Ccccccccccc, Ddddddddddd, Eeeeeeeeee, Fffffffff,
+)
+"
+        );
+    }
+
+    #[test]
+    fn test_format_invalid_syntax() {
+        assert!(format(Path::new("test.inko"), b"fn {").is_err());
+    }
+}
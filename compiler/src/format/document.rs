@@ -0,0 +1,373 @@
+//! A document type and layout algorithm for pretty-printing source code.
+//!
+//! The algorithm is based on the paper "A prettier printer" by Philip Wadler,
+//! extended with the ability to force groups to break and to pick between two
+//! alternative layouts.
+use std::rc::Rc;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The number of spaces to use for a single level of indentation.
+const INDENT: usize = 2;
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// A document describing the layout of source code.
+#[derive(Clone)]
+pub(crate) enum Doc {
+    /// A piece of text that can't be broken up.
+    Text(String),
+
+    /// A space when laid out flat, or a newline otherwise.
+    Line,
+
+    /// Nothing when laid out flat, or a newline otherwise.
+    SoftLine,
+
+    /// A newline that's always produced.
+    HardLine,
+
+    /// Text that's only produced if the surrounding group is broken up.
+    IfBreak(&'static str),
+
+    /// A marker that forces the surrounding groups to be broken up, without
+    /// producing any output.
+    ///
+    /// This is used for trailing comments, which must be followed by a
+    /// newline.
+    BreakParent,
+
+    /// A sequence of documents.
+    Nodes(Vec<Doc>),
+
+    /// A document to indent when it's broken up over multiple lines.
+    Indent(Box<Doc>),
+
+    /// A document to lay out on a single line if possible.
+    ///
+    /// The flag is set to `true` if the document contains a newline that must
+    /// be produced, meaning the group never fits on a single line.
+    Group(Box<Doc>, bool),
+
+    /// Two alternative layouts of the same source code.
+    ///
+    /// The first layout is used if its first line fits, otherwise the second
+    /// layout is used.
+    Choice(Box<Doc>, Box<Doc>),
+
+    /// A document that's shared between multiple alternative layouts.
+    Shared(Rc<Doc>),
+}
+
+impl Doc {
+    pub(crate) fn text<S: Into<String>>(value: S) -> Doc {
+        Doc::Text(value.into())
+    }
+
+    pub(crate) fn indent(node: Doc) -> Doc {
+        Doc::Indent(Box::new(node))
+    }
+
+    pub(crate) fn group(node: Doc) -> Doc {
+        let broken = node.contains_break();
+
+        Doc::Group(Box::new(node), broken)
+    }
+
+    pub(crate) fn choice(first: Doc, second: Doc) -> Doc {
+        Doc::Choice(Box::new(first), Box::new(second))
+    }
+
+    /// Returns a copy of this document with its outer group broken up.
+    pub(crate) fn broken(&self) -> Doc {
+        match self {
+            Doc::Group(node, _) => Doc::Group(node.clone(), true),
+            Doc::Choice(_, second) => second.broken(),
+            Doc::Shared(node) => node.broken(),
+            _ => self.clone(),
+        }
+    }
+
+    /// Returns `true` if this document contains a newline that must always be
+    /// produced.
+    pub(crate) fn contains_break(&self) -> bool {
+        match self {
+            Doc::HardLine | Doc::BreakParent => true,
+            Doc::Text(val) => val.contains('\n'),
+            Doc::Nodes(nodes) => nodes.iter().any(|n| n.contains_break()),
+            Doc::Indent(node) => node.contains_break(),
+            Doc::Group(_, broken) => *broken,
+            Doc::Choice(first, _) => first.contains_break(),
+            Doc::Shared(node) => node.contains_break(),
+            _ => false,
+        }
+    }
+}
+
+fn width(value: &str) -> usize {
+    value.graphemes(true).count()
+}
+
+/// A type that lays out a document, producing a String.
+pub(crate) struct Printer {
+    /// The maximum number of columns to fill before breaking up groups.
+    max_width: usize,
+
+    /// The output produced thus far.
+    buffer: String,
+
+    /// The current column, starting at zero.
+    column: usize,
+
+    /// The indentation to produce before the next piece of text.
+    ///
+    /// Indentation is produced lazily such that empty lines don't end up
+    /// containing whitespace.
+    pending_indent: Option<usize>,
+}
+
+impl Printer {
+    pub(crate) fn new(max_width: usize) -> Printer {
+        Printer {
+            max_width,
+            buffer: String::new(),
+            column: 0,
+            pending_indent: None,
+        }
+    }
+
+    pub(crate) fn print(mut self, node: &Doc) -> String {
+        let mut stack = vec![(0, Mode::Break, node)];
+
+        while let Some((indent, mode, node)) = stack.pop() {
+            match node {
+                Doc::Text(val) => self.text(val),
+                Doc::Line if mode == Mode::Flat => self.text(" "),
+                Doc::SoftLine if mode == Mode::Flat => {}
+                Doc::Line | Doc::SoftLine | Doc::HardLine => {
+                    self.newline(indent)
+                }
+                Doc::IfBreak(val) if mode == Mode::Break => self.text(val),
+                Doc::IfBreak(_) | Doc::BreakParent => {}
+                Doc::Nodes(nodes) => {
+                    for node in nodes.iter().rev() {
+                        stack.push((indent, mode, node));
+                    }
+                }
+                Doc::Indent(node) => {
+                    stack.push((indent + INDENT, mode, node));
+                }
+                Doc::Group(node, broken) => {
+                    let mode = if *broken {
+                        Mode::Break
+                    } else if mode == Mode::Flat
+                        || self.fits((indent, Mode::Flat, node), &stack)
+                    {
+                        Mode::Flat
+                    } else {
+                        Mode::Break
+                    };
+
+                    stack.push((indent, mode, node));
+                }
+                Doc::Choice(first, second) => {
+                    if self.fits((indent, Mode::Flat, first), &stack) {
+                        stack.push((indent, Mode::Flat, first));
+                    } else {
+                        stack.push((indent, mode, second));
+                    }
+                }
+                Doc::Shared(node) => stack.push((indent, mode, node)),
+            }
+        }
+
+        self.buffer
+    }
+
+    fn text(&mut self, value: &str) {
+        if value.is_empty() {
+            return;
+        }
+
+        if let Some(indent) = self.pending_indent.take() {
+            self.buffer.extend((0..indent).map(|_| ' '));
+            self.column = indent;
+        }
+
+        self.buffer.push_str(value);
+
+        if let Some(idx) = value.rfind('\n') {
+            self.column = width(&value[idx + 1..]);
+        } else {
+            self.column += width(value);
+        }
+    }
+
+    fn newline(&mut self, indent: usize) {
+        self.buffer.push('\n');
+        self.column = 0;
+        self.pending_indent = Some(indent);
+    }
+
+    /// Returns `true` if the given document fits on the rest of the current
+    /// line.
+    ///
+    /// The `rest` argument contains the documents to lay out after the given
+    /// document, as these may end up on the same line.
+    fn fits(
+        &self,
+        node: (usize, Mode, &Doc),
+        rest: &[(usize, Mode, &Doc)],
+    ) -> bool {
+        let column = self.pending_indent.unwrap_or(self.column);
+        let mut remaining = self.max_width as isize - column as isize;
+        let mut rest_idx = rest.len();
+        let (indent, mode, node) = node;
+        let mut stack = vec![(indent, mode, node, false)];
+
+        loop {
+            let (indent, mode, node, in_rest) = match stack.pop() {
+                Some(val) => val,
+                None if rest_idx > 0 => {
+                    rest_idx -= 1;
+
+                    let (indent, mode, node) = rest[rest_idx];
+
+                    (indent, mode, node, true)
+                }
+                None => return true,
+            };
+
+            match node {
+                Doc::Text(val) => {
+                    if let Some(idx) = val.find('\n') {
+                        return remaining >= width(&val[..idx]) as isize;
+                    }
+
+                    remaining -= width(val) as isize;
+                }
+                Doc::Line if mode == Mode::Flat => remaining -= 1,
+                Doc::SoftLine if mode == Mode::Flat => {}
+                Doc::Line | Doc::SoftLine | Doc::HardLine => return true,
+                Doc::IfBreak(val) if mode == Mode::Break => {
+                    remaining -= width(val) as isize
+                }
+                Doc::IfBreak(_) | Doc::BreakParent => {}
+                Doc::Nodes(nodes) => {
+                    for node in nodes.iter().rev() {
+                        stack.push((indent, mode, node, in_rest));
+                    }
+                }
+                Doc::Indent(node) => {
+                    stack.push((indent + INDENT, mode, node, in_rest))
+                }
+                Doc::Group(node, broken) => {
+                    let mode = if *broken { Mode::Break } else { mode };
+
+                    stack.push((indent, mode, node, in_rest));
+                }
+                // The documents that follow the one we're checking pick their
+                // own layout, so we assume they pick the layout that takes up
+                // the least amount of space on the current line.
+                Doc::Choice(_, second) if in_rest => {
+                    stack.push((indent, mode, second, in_rest))
+                }
+                Doc::Choice(first, _) => {
+                    stack.push((indent, mode, first, in_rest))
+                }
+                Doc::Shared(node) => stack.push((indent, mode, node, in_rest)),
+            }
+
+            if remaining < 0 {
+                return false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn print(width: usize, node: Doc) -> String {
+        Printer::new(width).print(&node)
+    }
+
+    fn list() -> Doc {
+        Doc::group(Doc::Nodes(vec![
+            Doc::text("foo("),
+            Doc::indent(Doc::Nodes(vec![
+                Doc::SoftLine,
+                Doc::text("aaaa,"),
+                Doc::Line,
+                Doc::text("bbbb"),
+                Doc::IfBreak(","),
+            ])),
+            Doc::SoftLine,
+            Doc::text(")"),
+        ]))
+    }
+
+    #[test]
+    fn test_group_fits() {
+        assert_eq!(print(80, list()), "foo(aaaa, bbbb)");
+    }
+
+    #[test]
+    fn test_group_breaks() {
+        assert_eq!(print(10, list()), "foo(\n  aaaa,\n  bbbb,\n)");
+    }
+
+    #[test]
+    fn test_forced_break() {
+        let node = Doc::group(Doc::Nodes(vec![
+            Doc::text("a"),
+            Doc::Line,
+            Doc::text("b"),
+            Doc::BreakParent,
+        ]));
+
+        assert_eq!(print(80, node), "a\nb");
+    }
+
+    #[test]
+    fn test_broken() {
+        assert_eq!(print(80, list().broken()), "foo(\n  aaaa,\n  bbbb,\n)");
+    }
+
+    #[test]
+    fn test_empty_lines_without_indentation() {
+        let node = Doc::indent(Doc::Nodes(vec![
+            Doc::text("a"),
+            Doc::HardLine,
+            Doc::HardLine,
+            Doc::text("b"),
+        ]));
+
+        assert_eq!(print(80, node), "a\n\n  b");
+    }
+
+    #[test]
+    fn test_choice() {
+        let node = || {
+            Doc::choice(
+                Doc::Nodes(vec![
+                    Doc::text("foo(fn {"),
+                    Doc::indent(Doc::Nodes(vec![
+                        Doc::HardLine,
+                        Doc::text("a"),
+                    ])),
+                    Doc::HardLine,
+                    Doc::text("})"),
+                ]),
+                Doc::text("x"),
+            )
+        };
+
+        assert_eq!(print(80, node()), "foo(fn {\n  a\n})");
+        assert_eq!(print(4, node()), "x");
+    }
+}
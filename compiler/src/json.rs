@@ -0,0 +1,428 @@
+//! Parsing and generating of JSON.
+//!
+//! This module only implements what the compiler needs: a simple value type,
+//! a parser, and a way of turning values back into JSON strings. Objects
+//! retain the order of their keys, such that generated output is
+//! deterministic.
+use std::fmt;
+
+/// A JSON value.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parses a JSON document.
+    pub(crate) fn parse(input: &str) -> Result<Json, String> {
+        let mut parser = Parser { input: input.as_bytes(), index: 0 };
+        let value = parser.value()?;
+
+        parser.whitespace();
+
+        if parser.index < parser.input.len() {
+            return Err(parser.error("the end of the input"));
+        }
+
+        Ok(value)
+    }
+
+    /// Returns a new object from the given key/value pairs.
+    pub(crate) fn object<S: Into<String>>(pairs: Vec<(S, Json)>) -> Json {
+        Json::Object(pairs.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    pub(crate) fn string<S: Into<String>>(value: S) -> Json {
+        Json::String(value.into())
+    }
+
+    /// Returns the value of the given key, or `Json::Null` if the key doesn't
+    /// exist or `self` isn't an object.
+    pub(crate) fn get(&self, key: &str) -> &Json {
+        if let Json::Object(pairs) = self {
+            for (k, v) in pairs {
+                if k == key {
+                    return v;
+                }
+            }
+        }
+
+        &Json::Null
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        if let Json::String(val) = self {
+            Some(val)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn as_int(&self) -> Option<i64> {
+        match self {
+            Json::Int(val) => Some(*val),
+            Json::Float(val) if val.fract() == 0.0 => Some(*val as i64),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> &[Json] {
+        if let Json::Array(values) = self {
+            values
+        } else {
+            &[]
+        }
+    }
+
+    pub(crate) fn is_null(&self) -> bool {
+        matches!(self, Json::Null)
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(val) => write!(f, "{}", val),
+            Json::Int(val) => write!(f, "{}", val),
+            Json::Float(val) if val.is_finite() => write!(f, "{:?}", val),
+            Json::Float(_) => write!(f, "null"),
+            Json::String(val) => write_string(f, val),
+            Json::Array(values) => {
+                write!(f, "[")?;
+
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write!(f, "{}", value)?;
+                }
+
+                write!(f, "]")
+            }
+            Json::Object(pairs) => {
+                write!(f, "{{")?;
+
+                for (index, (key, value)) in pairs.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+
+    for chr in value.chars() {
+        match chr {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            chr if (chr as u32) < 0x20 => write!(f, "\\u{:04x}", chr as u32)?,
+            chr => write!(f, "{}", chr)?,
+        }
+    }
+
+    write!(f, "\"")
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    index: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn value(&mut self) -> Result<Json, String> {
+        self.whitespace();
+
+        match self.peek() {
+            Some(b'n') => self.keyword("null", Json::Null),
+            Some(b't') => self.keyword("true", Json::Bool(true)),
+            Some(b'f') => self.keyword("false", Json::Bool(false)),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'[') => self.array(),
+            Some(b'{') => self.object(),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            _ => Err(self.error("a JSON value")),
+        }
+    }
+
+    fn keyword(&mut self, name: &str, value: Json) -> Result<Json, String> {
+        if self.input[self.index..].starts_with(name.as_bytes()) {
+            self.index += name.len();
+            Ok(value)
+        } else {
+            Err(self.error(name))
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.index;
+        let mut float = false;
+
+        while let Some(byte) = self.peek() {
+            match byte {
+                b'0'..=b'9' | b'-' | b'+' => {}
+                b'.' | b'e' | b'E' => float = true,
+                _ => break,
+            }
+
+            self.index += 1;
+        }
+
+        let text = String::from_utf8_lossy(&self.input[start..self.index]);
+
+        if !float {
+            if let Ok(val) = text.parse::<i64>() {
+                return Ok(Json::Int(val));
+            }
+        }
+
+        text.parse::<f64>()
+            .map(Json::Float)
+            .map_err(|_| format!("the number '{}' is invalid", text))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let mut buffer = Vec::new();
+
+        self.expect(b'"')?;
+
+        loop {
+            match self.next() {
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    let chr = match self.next() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => self.unicode_escape()?,
+                        _ => return Err(self.error("an escape sequence")),
+                    };
+
+                    let mut buf = [0; 4];
+
+                    buffer.extend_from_slice(
+                        chr.encode_utf8(&mut buf).as_bytes(),
+                    );
+                }
+                Some(byte) => buffer.push(byte),
+                None => return Err(self.error("a closing '\"'")),
+            }
+        }
+
+        String::from_utf8(buffer).map_err(|e| e.to_string())
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex()?;
+
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high)
+                .ok_or_else(|| self.error("a valid code point"));
+        }
+
+        // Code points outside of the basic multilingual plane are encoded as
+        // surrogate pairs.
+        if self.next() != Some(b'\\') || self.next() != Some(b'u') {
+            return Err(self.error("a low surrogate"));
+        }
+
+        let low = self.hex()?;
+
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(self.error("a low surrogate"));
+        }
+
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+            .ok_or_else(|| self.error("a valid code point"))
+    }
+
+    fn hex(&mut self) -> Result<u32, String> {
+        let digits = self
+            .input
+            .get(self.index..self.index + 4)
+            .and_then(|v| std::str::from_utf8(v).ok())
+            .and_then(|v| u32::from_str_radix(v, 16).ok())
+            .ok_or_else(|| self.error("four hexadecimal digits"))?;
+
+        self.index += 4;
+        Ok(digits)
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        let mut values = Vec::new();
+
+        self.expect(b'[')?;
+        self.whitespace();
+
+        if self.peek() == Some(b']') {
+            self.index += 1;
+            return Ok(Json::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+            self.whitespace();
+
+            match self.next() {
+                Some(b',') => {}
+                Some(b']') => break,
+                _ => return Err(self.error("a ',' or ']'")),
+            }
+        }
+
+        Ok(Json::Array(values))
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        let mut pairs = Vec::new();
+
+        self.expect(b'{')?;
+        self.whitespace();
+
+        if self.peek() == Some(b'}') {
+            self.index += 1;
+            return Ok(Json::Object(pairs));
+        }
+
+        loop {
+            self.whitespace();
+
+            let key = self.string()?;
+
+            self.whitespace();
+            self.expect(b':')?;
+            pairs.push((key, self.value()?));
+            self.whitespace();
+
+            match self.next() {
+                Some(b',') => {}
+                Some(b'}') => break,
+                _ => return Err(self.error("a ',' or '}'")),
+            }
+        }
+
+        Ok(Json::Object(pairs))
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        if self.peek() == Some(byte) {
+            self.index += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("'{}'", byte as char)))
+        }
+    }
+
+    fn whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.index += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.index).cloned()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek();
+
+        if byte.is_some() {
+            self.index += 1;
+        }
+
+        byte
+    }
+
+    fn error(&self, expected: &str) -> String {
+        format!("expected {} at byte offset {}", expected, self.index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scalars() {
+        assert_eq!(Json::parse("null"), Ok(Json::Null));
+        assert_eq!(Json::parse(" true "), Ok(Json::Bool(true)));
+        assert_eq!(Json::parse("false"), Ok(Json::Bool(false)));
+        assert_eq!(Json::parse("-12"), Ok(Json::Int(-12)));
+        assert_eq!(Json::parse("1.5e2"), Ok(Json::Float(150.0)));
+    }
+
+    #[test]
+    fn test_parse_strings() {
+        assert_eq!(
+            Json::parse(r#""a\"b\\c\né😀""#),
+            Ok(Json::string("a\"b\\c\né😀"))
+        );
+        assert_eq!(Json::parse("\"é\""), Ok(Json::string("é")));
+        assert!(Json::parse(r#""\ud83d""#).is_err());
+        assert!(Json::parse("\"abc").is_err());
+    }
+
+    #[test]
+    fn test_parse_collections() {
+        assert_eq!(
+            Json::parse(r#"{"a": [1, {}], "b": []}"#),
+            Ok(Json::object(vec![
+                (
+                    "a",
+                    Json::Array(vec![Json::Int(1), Json::Object(Vec::new())])
+                ),
+                ("b", Json::Array(Vec::new())),
+            ]))
+        );
+        assert!(Json::parse("[1,]").is_err());
+        assert!(Json::parse("{} {}").is_err());
+    }
+
+    #[test]
+    fn test_get() {
+        let value = Json::parse(r#"{"a": {"b": 10}}"#).unwrap();
+
+        assert_eq!(value.get("a").get("b").as_int(), Some(10));
+        assert!(value.get("b").is_null());
+        assert!(Json::Int(1).get("a").is_null());
+    }
+
+    #[test]
+    fn test_to_string() {
+        let value = Json::object(vec![
+            ("a", Json::string("\"\n\u{1}")),
+            ("b", Json::Array(vec![Json::Int(1), Json::Float(1.0)])),
+            ("c", Json::Null),
+        ]);
+
+        assert_eq!(
+            value.to_string(),
+            r#"{"a":"\"\n\u0001","b":[1,1.0],"c":null}"#
+        );
+        assert_eq!(Json::parse(&value.to_string()), Ok(value));
+    }
+}
@@ -1,14 +1,20 @@
 #![cfg_attr(feature = "cargo-clippy", allow(clippy::new_without_default))]
 #![cfg_attr(feature = "cargo-clippy", allow(clippy::enum_variant_names))]
 
-mod diagnostics;
+pub mod coverage;
+pub mod diagnostics;
+mod docs;
+pub mod format;
 mod hir;
+mod json;
 mod linker;
 mod llvm;
+pub mod lsp;
 mod mir;
 mod modules_parser;
 pub mod pkg;
 mod presenters;
+mod settings;
 mod state;
 mod symbol_names;
 pub mod target;
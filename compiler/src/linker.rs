@@ -1,32 +1,63 @@
 use crate::config::Config;
 use crate::state::State;
-use crate::target::OperatingSystem;
+use crate::target::{Architecture, OperatingSystem};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
+/// The directories (relative to a sysroot) to search for the runtime library.
+const SYSROOT_RUNTIME_DIRECTORIES: [&str; 2] =
+    ["usr/lib/inko/runtime", "usr/local/lib/inko/runtime"];
+
 fn runtime_library(config: &Config) -> Option<PathBuf> {
-    let mut files = vec![format!("libinko-{}.a", &config.target)];
+    let target = format!("libinko-{}.a", &config.target);
+    let mut paths = vec![config.runtime.join(&target)];
 
     // When compiling for the native target we also support DIR/libinko.a, as
     // this makes development of Inko easier by just using e.g. `./target/debug`
     // as the search directory.
     if config.target.is_native() {
-        files.push("libinko.a".to_string());
+        paths.push(config.runtime.join("libinko.a"));
     }
 
-    files.iter().find_map(|file| {
-        let path = config.runtime.join(file);
-
-        if path.is_file() {
-            Some(path)
-        } else {
-            None
+    // A sysroot contains the files of the target platform, so a runtime
+    // library installed into the sysroot is built for the target.
+    if let Some(root) = &config.sysroot {
+        for dir in SYSROOT_RUNTIME_DIRECTORIES {
+            paths.push(root.join(dir).join(&target));
+            paths.push(root.join(dir).join("libinko.a"));
         }
-    })
+    }
+
+    paths.into_iter().find(|p| p.is_file())
 }
 
-fn lld_is_available() -> bool {
-    Command::new("ld.lld")
+/// Returns the program to use for linking, and whether we need to tell it
+/// what target to link for.
+fn linker(config: &Config) -> (&Path, bool) {
+    if let Some(path) = &config.linker {
+        (path, false)
+    } else if config.target.is_native() {
+        (Path::new("cc"), false)
+    } else if config.target.is_musl()
+        && config.target.arch == Architecture::native()
+        && is_available("musl-gcc")
+    {
+        // When targeting musl on a host using a different libc (e.g. glibc),
+        // we use the musl-gcc wrapper if it's available, as it's commonly
+        // provided by Linux distributions and sets up the necessary search
+        // paths for us.
+        (Path::new("musl-gcc"), false)
+    } else {
+        // The system compiler is usually GCC, which only supports linking for
+        // the native target. clang on the other hand supports any target it
+        // has been compiled for, which typically includes all the targets we
+        // support.
+        (Path::new("clang"), true)
+    }
+}
+
+fn is_available(program: &str) -> bool {
+    Command::new(program)
         .arg("--version")
         .stdout(Stdio::null())
         .stderr(Stdio::null())
@@ -48,7 +79,16 @@ pub(crate) fn link(
     // As we only use this executable for linking it doesn't really matter
     // if this ends up using gcc, clang or something else, because we only
     // use it as a wrapper around the linker executable.
-    let mut cmd = Command::new("cc");
+    let (program, set_target) = linker(&state.config);
+    let mut cmd = Command::new(program);
+
+    if set_target {
+        cmd.arg(format!("--target={}", state.config.target.llvm_triple()));
+    }
+
+    if let Some(path) = &state.config.sysroot {
+        cmd.arg(format!("--sysroot={}", path.display()));
+    }
 
     // Object files must come before any of the libraries to link against, as
     // certain linkers are very particular about the order of flags such as
@@ -58,7 +98,13 @@ pub(crate) fn link(
     }
 
     let rt_path = runtime_library(&state.config).ok_or_else(|| {
-        format!("No runtime is available for target '{}'", state.config.target)
+        format!(
+            "No runtime is available for target '{}', as {} doesn't contain \
+            libinko-{}.a",
+            state.config.target,
+            state.config.runtime.display(),
+            state.config.target
+        )
     })?;
 
     cmd.arg(&rt_path);
@@ -70,11 +116,12 @@ pub(crate) fn link(
     // macOS includes libm in the standard C library, so there's no need to
     // explicitly include it.
     //
-    // We don't support static linking as libm is part of glibc, libc doesn't
-    // really support (proper) static linking, and you can't statically link
-    // libm _without_ statically linking libc. See
+    // We don't support static linking against glibc as libm is part of glibc,
+    // glibc doesn't really support (proper) static linking, and you can't
+    // statically link libm _without_ statically linking libc. See
     // https://bugzilla.redhat.com/show_bug.cgi?id=1433347 for some extra
-    // details.
+    // details. musl on the other hand does support static linking, in which
+    // case we produce a fully static executable.
     match state.config.target.os {
         OperatingSystem::Linux => {
             // Certain versions of Linux (e.g. Debian 11) also need libdl and
@@ -110,6 +157,17 @@ pub(crate) fn link(
         _ => (),
     }
 
+    // When linking against musl statically, _everything_ is linked statically
+    // (including libc), so toggling between static and dynamic linking isn't
+    // necessary. Doing so would in fact result in the linker trying to link
+    // libc dynamically, as it's added after the libraries we specify.
+    let fully_static = static_linking && state.config.target.is_musl();
+
+    if fully_static {
+        cmd.arg("-static");
+        static_linking = false;
+    }
+
     if static_linking {
         cmd.arg("-Wl,-Bstatic");
     }
@@ -136,14 +194,26 @@ pub(crate) fn link(
         // This removes the need for installing libgcc in deployment
         // environments.
         cmd.arg("-static-libgcc");
+    }
 
-        // On platforms where lld isn't the default (e.g. Linux), we'll use it
-        // if available, speeding up the linking process.
-        if lld_is_available() {
-            cmd.arg("-fuse-ld=lld");
-        }
+    // On platforms where lld isn't the default (e.g. Linux), we'll use it
+    // if available, speeding up the linking process. When cross compiling we
+    // also use it for FreeBSD, as the system linker only supports the native
+    // target, while lld supports all targets.
+    let use_lld = match state.config.target.os {
+        OperatingSystem::Linux => true,
+        OperatingSystem::Freebsd => !state.config.target.is_native(),
+        OperatingSystem::Mac => false,
+    };
+
+    if use_lld && is_available("ld.lld") {
+        cmd.arg("-fuse-ld=lld");
     }
 
+    // Custom arguments come last, such that they can override the arguments
+    // we pass by default.
+    cmd.args(&state.config.linker_arguments);
+
     cmd.stdin(Stdio::null());
     cmd.stderr(Stdio::piped());
     cmd.stdout(Stdio::null());
@@ -166,3 +236,57 @@ pub(crate) fn link(
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::target::Target;
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    /// Returns a target that differs from the native target.
+    fn foreign_target() -> &'static str {
+        if Target::native().arch_name() == "amd64" {
+            "arm64-linux-gnu"
+        } else {
+            "amd64-linux-gnu"
+        }
+    }
+
+    #[test]
+    fn test_linker() {
+        let mut config = Config::new();
+
+        assert_eq!(linker(&config), (Path::new("cc"), false));
+
+        config.set_target(foreign_target()).unwrap();
+
+        assert_eq!(linker(&config), (Path::new("clang"), true));
+
+        config.linker = Some(PathBuf::from("aarch64-linux-gnu-gcc"));
+
+        assert_eq!(
+            linker(&config),
+            (Path::new("aarch64-linux-gnu-gcc"), false)
+        );
+    }
+
+    #[test]
+    fn test_runtime_library_in_sysroot() {
+        let root = temp_dir().join("inko-linker-sysroot");
+        let dir = root.join("usr").join("lib").join("inko").join("runtime");
+        let mut config = Config::new();
+
+        config.runtime = root.join("missing");
+        config.set_target(foreign_target()).unwrap();
+
+        assert_eq!(runtime_library(&config), None);
+
+        create_dir_all(&dir).unwrap();
+        write(dir.join("libinko.a"), "").unwrap();
+        config.sysroot = Some(root.clone());
+
+        assert_eq!(runtime_library(&config), Some(dir.join("libinko.a")));
+        remove_dir_all(&root).unwrap();
+    }
+}
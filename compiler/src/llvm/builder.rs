@@ -99,13 +99,13 @@ impl<'ctx> Builder<'ctx> {
         }
     }
 
-    pub(crate) fn load_array_index(
+    pub(crate) fn array_index_address(
         &self,
         array_type: ArrayType<'ctx>,
         array: PointerValue<'ctx>,
         index: usize,
-    ) -> BasicValueEnum<'ctx> {
-        let ptr = unsafe {
+    ) -> PointerValue<'ctx> {
+        unsafe {
             self.inner.build_gep(
                 array_type,
                 array,
@@ -115,7 +115,16 @@ impl<'ctx> Builder<'ctx> {
                 ],
                 "",
             )
-        };
+        }
+    }
+
+    pub(crate) fn load_array_index(
+        &self,
+        array_type: ArrayType<'ctx>,
+        array: PointerValue<'ctx>,
+        index: usize,
+    ) -> BasicValueEnum<'ctx> {
+        let ptr = self.array_index_address(array_type, array, index);
 
         self.inner.build_load(array_type.get_element_type(), ptr, "")
     }
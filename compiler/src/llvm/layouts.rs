@@ -239,9 +239,9 @@ impl<'ctx> Layouts<'ctx> {
                 // Mutexes are smaller on Linux, resulting in a smaller process
                 // size, so we have to take that into account when calculating
                 // field offsets.
-                112
+                144
             }
-            _ => 128,
+            _ => 160,
         };
 
         for id in mir.classes.keys() {
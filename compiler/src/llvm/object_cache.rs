@@ -0,0 +1,159 @@
+//! Reusing object files produced by a previous compilation.
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use std::collections::HashMap;
+use std::fs::{read_to_string, write};
+use std::path::{Path, PathBuf};
+
+/// The version of the compiler.
+///
+/// This version is included in every hash, such that upgrading the compiler
+/// results in all object files being generated again.
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+fn object_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// A manifest of the object files produced by a previous compilation, along
+/// with the hashes of the modules they were produced from.
+///
+/// The hashes are derived from the LLVM IR of a module. This IR is the result
+/// of the module's source code, the layouts and methods of the types it depends
+/// on (including those defined in other modules), and the target to compile
+/// for. If the hash of a module didn't change, its object file can be reused
+/// instead of generating it again.
+pub(crate) struct ObjectCache {
+    /// The path to the file the manifest is stored in.
+    path: PathBuf,
+
+    /// The object file names and the hashes of the modules they belong to.
+    hashes: HashMap<String, String>,
+}
+
+impl ObjectCache {
+    /// Loads the manifest stored at the given path.
+    ///
+    /// If the manifest doesn't exist or can't be read, an empty cache is
+    /// returned, resulting in all object files being generated again.
+    pub(crate) fn load(path: PathBuf) -> ObjectCache {
+        let hashes = read_to_string(&path)
+            .map(|data| {
+                data.lines()
+                    .filter_map(|line| line.split_once(' '))
+                    .map(|(hash, name)| (name.to_string(), hash.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        ObjectCache { path, hashes }
+    }
+
+    /// Returns the hash of the given LLVM IR.
+    pub(crate) fn hash(ir: &[u8]) -> String {
+        let mut hasher: Blake2b<U32> = Blake2b::new();
+
+        hasher.update(VERSION);
+        hasher.update(ir);
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Returns `true` if the object file at the given path can be reused.
+    pub(crate) fn is_valid(&self, object: &Path, hash: &str) -> bool {
+        object.is_file()
+            && self
+                .hashes
+                .get(&object_name(object))
+                .map_or(false, |val| val == hash)
+    }
+
+    pub(crate) fn insert(&mut self, object: &Path, hash: String) {
+        self.hashes.insert(object_name(object), hash);
+    }
+
+    pub(crate) fn save(&self) -> Result<(), String> {
+        let mut entries: Vec<_> = self.hashes.iter().collect();
+
+        // Sorting the entries makes it easier to compare the manifests of
+        // different builds when debugging the cache.
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+
+        let data = entries.into_iter().fold(String::new(), |mut buf, pair| {
+            buf.push_str(pair.1);
+            buf.push(' ');
+            buf.push_str(pair.0);
+            buf.push('\n');
+            buf
+        });
+
+        write(&self.path, data).map_err(|err| {
+            format!("Failed to write {}: {}", self.path.display(), err)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+    use std::fs::remove_file;
+
+    struct TempFile {
+        path: PathBuf,
+    }
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            Self { path: temp_dir().join(name) }
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = remove_file(&self.path);
+        }
+    }
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(ObjectCache::hash(b"foo"), ObjectCache::hash(b"foo"));
+        assert_ne!(ObjectCache::hash(b"foo"), ObjectCache::hash(b"bar"));
+    }
+
+    #[test]
+    fn test_load_missing_manifest() {
+        let cache = ObjectCache::load(temp_dir().join("inko-cache-missing"));
+
+        assert!(cache.hashes.is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let manifest = TempFile::new("inko-cache-manifest1");
+        let object = TempFile::new("inko-cache-object1.o");
+        let hash = ObjectCache::hash(b"foo");
+        let mut cache = ObjectCache::load(manifest.path.clone());
+
+        write(&object.path, "").unwrap();
+        cache.insert(&object.path, hash.clone());
+        cache.save().unwrap();
+
+        let cache = ObjectCache::load(manifest.path.clone());
+
+        assert!(cache.is_valid(&object.path, &hash));
+        assert!(!cache.is_valid(&object.path, &ObjectCache::hash(b"bar")));
+    }
+
+    #[test]
+    fn test_is_valid_with_missing_object() {
+        let manifest = TempFile::new("inko-cache-manifest2");
+        let object = temp_dir().join("inko-cache-object2.o");
+        let hash = ObjectCache::hash(b"foo");
+        let mut cache = ObjectCache::load(manifest.path.clone());
+
+        cache.insert(&object, hash.clone());
+
+        assert!(!cache.is_valid(&object, &hash));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::config::BuildDirectories;
+use crate::coverage::{Counters, ModuleCounters};
 use crate::llvm::builder::Builder;
 use crate::llvm::constants::{
     ARRAY_BUF_INDEX, ARRAY_CAPA_INDEX, ARRAY_LENGTH_INDEX,
@@ -11,9 +12,10 @@ use crate::llvm::constants::{
 use crate::llvm::context::Context;
 use crate::llvm::layouts::Layouts;
 use crate::llvm::module::Module;
+use crate::llvm::object_cache::ObjectCache;
 use crate::llvm::runtime_function::RuntimeFunction;
 use crate::mir::{
-    CastType, Constant, Instruction, LocationId, Method, Mir, RegisterId,
+    Block, CastType, Constant, Instruction, LocationId, Method, Mir, RegisterId,
 };
 use crate::state::State;
 use crate::symbol_names::SymbolNames;
@@ -22,7 +24,8 @@ use inkwell::basic_block::BasicBlock;
 use inkwell::module::Linkage;
 use inkwell::passes::{PassManager, PassManagerBuilder};
 use inkwell::targets::{
-    CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetTriple,
+    CodeModel, FileType, InitializationConfig, RelocMode, Target,
+    TargetMachine, TargetTriple,
 };
 use inkwell::types::{
     BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType,
@@ -33,9 +36,14 @@ use inkwell::values::{
 };
 use inkwell::AddressSpace;
 use inkwell::OptimizationLevel;
+use std::cmp::min;
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::rename;
+use std::panic::resume_unwind;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::scope;
 use types::module_name::ModuleName;
 use types::{
     BuiltinFunction, ClassId, Database, Shape, TypeRef, BYTE_ARRAY_ID,
@@ -51,26 +59,59 @@ pub(crate) struct Compile<'a, 'b, 'ctx> {
     names: &'a SymbolNames,
     context: &'ctx Context,
     module: &'b mut Module<'a, 'ctx>,
+    counters: Option<&'a Counters>,
 }
 
-impl<'a, 'b, 'ctx> Compile<'a, 'b, 'ctx> {
-    /// Compiles all the modules into object files.
-    ///
-    /// The return value is a list of file paths of the object files.
-    pub(crate) fn run_all(
-        state: &'a State,
-        directories: &BuildDirectories,
-        mir: &'a Mir,
-    ) -> Result<Vec<PathBuf>, String> {
-        match state.config.target.arch {
-            Architecture::Amd64 => {
-                Target::initialize_x86(&InitializationConfig::default());
-            }
-            Architecture::Arm64 => {
-                Target::initialize_aarch64(&InitializationConfig::default());
-            }
-        }
+/// A type for sharing the compiler state with the threads used for generating
+/// code.
+///
+/// The type database isn't `Sync` because type placeholders store their values
+/// in a `Cell`. The database is frozen while the threads run, turning any
+/// attempt to assign a placeholder into a panic instead of a data race. All
+/// other data in the database is only read during code generation.
+struct SharedState<'a>(&'a State);
+
+unsafe impl<'a> Sync for SharedState<'a> {}
 
+impl<'a> SharedState<'a> {
+    fn get(&self) -> &'a State {
+        self.0
+    }
+}
+
+/// An object file produced (or reused) by a `Worker`.
+struct Object {
+    /// The index of the module the object file belongs to.
+    index: usize,
+
+    /// The path to the object file.
+    path: PathBuf,
+
+    /// The hash of the module's LLVM IR.
+    hash: String,
+
+    /// A flag indicating the object file was generated, instead of being
+    /// reused from a previous compilation.
+    changed: bool,
+}
+
+/// A thread that lowers MIR modules to LLVM, then turns them into object files.
+///
+/// Workers share a counter that stores the index of the next module to compile.
+/// Each worker uses its own LLVM context, as LLVM contexts can't be shared
+/// between threads.
+struct Worker<'a> {
+    state: &'a State,
+    mir: &'a Mir,
+    names: &'a SymbolNames,
+    directories: &'a BuildDirectories,
+    cache: &'a ObjectCache,
+    next: &'a AtomicUsize,
+    counters: Option<&'a Counters>,
+}
+
+impl<'a> Worker<'a> {
+    fn run(self) -> Result<Vec<Object>, String> {
         // LLVM's optimisation level controls which passes to run, but some/many
         // of those may not be relevant to Inko, while slowing down compile
         // times. Thus instead of using this knob, we provide our own list of
@@ -78,7 +119,8 @@ impl<'a, 'b, 'ctx> Compile<'a, 'b, 'ctx> {
         let opt = OptimizationLevel::None;
         let reloc = RelocMode::PIC;
         let model = CodeModel::Default;
-        let triple = TargetTriple::create(&state.config.target.llvm_triple());
+        let triple =
+            TargetTriple::create(&self.state.config.target.llvm_triple());
         let target = Target::from_triple(&triple).unwrap();
         let target_machine = target
             .create_target_machine(&triple, "", "", opt, reloc, model)
@@ -86,53 +128,11 @@ impl<'a, 'b, 'ctx> Compile<'a, 'b, 'ctx> {
 
         let context = Context::new();
         let types = Layouts::new(
-            state,
-            mir,
+            self.state,
+            self.mir,
             &context,
             target_machine.get_target_data(),
         );
-        let names = SymbolNames::new(&state.db, mir);
-        let mut modules = Vec::with_capacity(mir.modules.len());
-
-        for module_index in 0..mir.modules.len() {
-            let mod_id = mir.modules[module_index].id;
-            let name = mod_id.name(&state.db).clone();
-            let path = mod_id.file(&state.db);
-            let mut module = Module::new(&context, &types, name, &path);
-
-            Compile {
-                db: &state.db,
-                mir,
-                module_index,
-                names: &names,
-                context: &context,
-                module: &mut module,
-                layouts: &types,
-            }
-            .run();
-
-            modules.push(module);
-        }
-
-        let main_module = Module::new(
-            &context,
-            &types,
-            ModuleName::new("$main"),
-            Path::new("$main.inko"),
-        );
-
-        GenerateMain::new(
-            &state.db,
-            mir,
-            &types,
-            &names,
-            &context,
-            &main_module,
-        )
-        .run();
-
-        modules.push(main_module);
-
         let layout = target_machine.get_target_data().get_data_layout();
         let pm_builder = PassManagerBuilder::create();
         let pm = PassManager::create(());
@@ -141,71 +141,230 @@ impl<'a, 'b, 'ctx> Compile<'a, 'b, 'ctx> {
         pm_builder.populate_module_pass_manager(&pm);
         pm.add_promote_memory_to_register_pass();
 
-        for module in &modules {
+        let mut objects = Vec::new();
+
+        loop {
+            let index = self.next.fetch_add(1, Ordering::Relaxed);
+            let module = if index < self.mir.modules.len() {
+                let mod_id = self.mir.modules[index].id;
+                let name = mod_id.name(&self.state.db).clone();
+                let path = mod_id.file(&self.state.db);
+                let mut module = Module::new(&context, &types, name, &path);
+
+                Compile {
+                    db: &self.state.db,
+                    mir: self.mir,
+                    module_index: index,
+                    names: self.names,
+                    context: &context,
+                    module: &mut module,
+                    layouts: &types,
+                    counters: self.counters,
+                }
+                .run();
+
+                module
+            } else if index == self.mir.modules.len() {
+                let module = Module::new(
+                    &context,
+                    &types,
+                    ModuleName::new("$main"),
+                    Path::new("$main.inko"),
+                );
+
+                GenerateMain::new(
+                    &self.state.db,
+                    self.mir,
+                    &types,
+                    self.names,
+                    &context,
+                    &module,
+                )
+                .run(self.counters);
+
+                module
+            } else {
+                break;
+            };
+
             module.set_data_layout(&layout);
             module.set_triple(&triple);
             pm.run_on(&module.inner);
+            objects.push(self.write(index, &module, &target_machine)?);
         }
 
-        let mut paths = Vec::with_capacity(modules.len());
+        Ok(objects)
+    }
 
-        if state.config.write_llvm {
-            directories.create_llvm()?;
+    fn write(
+        &self,
+        index: usize,
+        module: &Module,
+        target_machine: &TargetMachine,
+    ) -> Result<Object, String> {
+        let name = module.name.normalized_name();
+
+        if self.state.config.write_llvm {
+            let path = self.directories.llvm_ir.join(format!("{}.ll", name));
+
+            module.print_to_file(&path).map_err(|err| {
+                format!("Failed to create {}: {}", path.display(), err)
+            })?;
         }
 
-        if state.config.write_llvm {
-            for module in &modules {
-                let name = module.name.normalized_name();
-                let path = directories.llvm_ir.join(format!("{}.ll", name));
-
-                module.print_to_file(&path).map_err(|err| {
-                    format!("Failed to create {}: {}", path.display(), err)
-                })?;
+        // We verify _after_ writing the LLVM IR (if enabled) such that the IR
+        // can be inspected in the event of a verification failure.
+        if self.state.config.verify_llvm {
+            if let Err(err) = module.verify() {
+                panic!(
+                    "the LLVM module '{}' must be valid:\n\n{}\n",
+                    module.name,
+                    err.to_string(),
+                );
             }
         }
 
-        // We verify _after_ writing the LLVM IR (if enabled) such that the IR
-        // can be inspected in the event of a verification failure.
-        if state.config.verify_llvm {
-            for module in &modules {
-                if let Err(err) = module.verify() {
-                    panic!(
-                        "the LLVM module '{}' must be valid:\n\n{}\n",
-                        module.name,
-                        err.to_string(),
-                    );
-                }
+        let path = self.directories.objects.join(format!("{}.o", name));
+        let hash = ObjectCache::hash(module.print_to_string().to_bytes());
+
+        if self.cache.is_valid(&path, &hash) {
+            return Ok(Object { index, path, hash, changed: false });
+        }
+
+        // The object file is written to a temporary file first, such that a
+        // failure to write it doesn't leave an incomplete object file behind
+        // that a future compilation might reuse.
+        let tmp = path.with_extension("o.tmp");
+
+        target_machine
+            .write_to_file(&module.inner, FileType::Object, tmp.as_path())
+            .map_err(|err| err.to_string())
+            .and_then(|_| rename(&tmp, &path).map_err(|err| err.to_string()))
+            .map_err(|err| {
+                format!("Failed to create {}: {}", path.display(), err)
+            })?;
+
+        Ok(Object { index, path, hash, changed: true })
+    }
+}
+
+impl<'a, 'b, 'ctx> Compile<'a, 'b, 'ctx> {
+    /// Compiles all the modules into object files.
+    ///
+    /// Modules are compiled in parallel, using up to `Config::jobs` threads.
+    ///
+    /// If coverage counters are given, the generated code increments these
+    /// counters when executing the lines they belong to.
+    ///
+    /// The return value is a list of file paths of the object files.
+    pub(crate) fn run_all(
+        state: &'a State,
+        directories: &BuildDirectories,
+        mir: &'a Mir,
+        counters: Option<&'a Counters>,
+    ) -> Result<Vec<PathBuf>, String> {
+        match state.config.target.arch {
+            Architecture::Amd64 => {
+                Target::initialize_x86(&InitializationConfig::default());
+            }
+            Architecture::Arm64 => {
+                Target::initialize_aarch64(&InitializationConfig::default());
             }
         }
 
-        for module in &modules {
-            let name = module.name.normalized_name();
-            let path = directories.objects.join(format!("{}.o", name));
+        if state.config.write_llvm {
+            directories.create_llvm()?;
+        }
 
-            target_machine
-                .write_to_file(&module.inner, FileType::Object, path.as_path())
-                .map_err(|err| {
-                    format!("Failed to create {}: {}", path.display(), err)
-                })?;
+        let names = SymbolNames::new(&state.db, mir);
+        let mut cache = ObjectCache::load(directories.object_cache.clone());
+        let shared = SharedState(state);
+        let next = AtomicUsize::new(0);
+
+        // The extra module is the module that defines the main() function.
+        let total = mir.modules.len() + 1;
+        let jobs = min(state.config.jobs, total);
+
+        state.db.set_frozen(true);
+
+        let results: Vec<_> = scope(|s| {
+            let handles: Vec<_> = (0..jobs)
+                .map(|_| {
+                    s.spawn(|| {
+                        Worker {
+                            state: shared.get(),
+                            mir,
+                            names: &names,
+                            directories,
+                            cache: &cache,
+                            next: &next,
+                            counters,
+                        }
+                        .run()
+                    })
+                })
+                .collect();
 
-            paths.push(path);
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap_or_else(|err| resume_unwind(err)))
+                .collect()
+        });
+
+        state.db.set_frozen(false);
+
+        let mut objects = Vec::with_capacity(total);
+
+        for result in results {
+            objects.append(&mut result?);
+        }
+
+        objects.sort_by_key(|obj| obj.index);
+
+        let mut changed = false;
+        let mut paths = Vec::with_capacity(objects.len());
+
+        for obj in objects {
+            if obj.changed {
+                cache.insert(&obj.path, obj.hash);
+                changed = true;
+            }
+
+            paths.push(obj.path);
+        }
+
+        if changed {
+            cache.save()?;
         }
 
         Ok(paths)
     }
 
     pub(crate) fn run(mut self) {
+        let mod_id = self.mir.modules[self.module_index].id;
+        let coverage = self
+            .counters
+            .and_then(|all| all.get(mod_id).map(|counters| (all, counters)));
+        let global = coverage.map(|(_, counters)| self.add_counters(counters));
+
         for method in &self.mir.modules[self.module_index].methods {
-            LowerMethod::new(
+            let method = &self.mir.methods[method];
+            let coverage = coverage.zip(global).and_then(|((all, c), g)| {
+                all.file_index(&method.id.module(self.db).file(self.db))
+                    .map(|file| Coverage { counters: c, global: g, file })
+            });
+            let mut lower = LowerMethod::new(
                 self.db,
                 self.mir,
                 self.layouts,
                 self.context,
                 self.names,
                 self.module,
-                &self.mir.methods[method],
-            )
-            .run();
+                method,
+            );
+
+            lower.coverage = coverage;
+            lower.run();
         }
 
         self.setup_classes();
@@ -213,6 +372,14 @@ impl<'a, 'b, 'ctx> Compile<'a, 'b, 'ctx> {
         self.module.debug_builder.finalize();
     }
 
+    fn add_counters(&mut self, counters: &ModuleCounters) -> GlobalValue<'ctx> {
+        let typ = self.context.i64_type().array_type(counters.len() as _);
+        let global = self.module.add_global(typ, &counters.symbol);
+
+        global.set_initializer(&typ.const_zero());
+        global
+    }
+
     fn setup_classes(&mut self) {
         let mod_id = self.mir.modules[self.module_index].id;
         let space = AddressSpace::default();
@@ -360,7 +527,14 @@ impl<'a, 'b, 'ctx> Compile<'a, 'b, 'ctx> {
             self.set_constant_global(&builder, state_var, value, global);
         }
 
-        for (value, global) in &self.module.strings {
+        // The order of the strings map isn't stable, so we sort the strings
+        // such that compiling the same module always produces the same code,
+        // allowing its object file to be reused between compilations.
+        let mut strings: Vec<_> = self.module.strings.iter().collect();
+
+        strings.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (value, global) in strings {
             let ptr = global.as_pointer_value();
             let val = self.new_string(&builder, state_var, value);
 
@@ -497,6 +671,18 @@ impl<'a, 'b, 'ctx> Compile<'a, 'b, 'ctx> {
     }
 }
 
+/// The coverage counters to increment when lowering a method.
+#[derive(Copy, Clone)]
+struct Coverage<'a, 'ctx> {
+    counters: &'a ModuleCounters,
+
+    /// The global that stores the counters of the module.
+    global: GlobalValue<'ctx>,
+
+    /// The index of the source file the method is defined in.
+    file: usize,
+}
+
 /// A pass for lowering the MIR of a single method.
 pub struct LowerMethod<'a, 'b, 'ctx> {
     db: &'a Database,
@@ -522,6 +708,9 @@ pub struct LowerMethod<'a, 'b, 'ctx> {
 
     /// The LLVM types for each MIR register.
     variable_types: HashMap<RegisterId, BasicTypeEnum<'ctx>>,
+
+    /// The coverage counters to increment, if coverage is enabled.
+    coverage: Option<Coverage<'a, 'ctx>>,
 }
 
 impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
@@ -547,6 +736,7 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
             builder,
             variables: HashMap::new(),
             variable_types: HashMap::new(),
+            coverage: None,
         }
     }
 
@@ -703,7 +893,10 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
 
             self.builder.switch_to_block(llvm_block);
 
+            let mut line = self.entry_line(mir_block);
+
             for ins in &mir_block.instructions {
+                self.count_line(ins.location(), &mut line);
                 self.instruction(&llvm_blocks, state_var, proc_var, ins);
             }
 
@@ -2172,6 +2365,52 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
         }
     }
 
+    /// Returns the line that execution is on when entering a block.
+    ///
+    /// If all predecessors of the block end on the same line, entering the
+    /// block doesn't start executing a new line. This prevents a single line
+    /// from being counted multiple times when its code spans multiple blocks.
+    fn entry_line(&self, block: &Block) -> Option<usize> {
+        self.coverage?;
+
+        let mut lines = block.predecessors.iter().map(|id| {
+            self.method.body.blocks[id.0]
+                .instructions
+                .last()
+                .map(|ins| self.mir.location(ins.location()).line_column().0)
+        });
+        let first = lines.next()??;
+
+        lines.all(|line| line == Some(first)).then_some(first)
+    }
+
+    /// Increments the coverage counter of the line of the given location, if
+    /// it differs from the line of the previous instruction in the block.
+    fn count_line(&self, location: LocationId, last: &mut Option<usize>) {
+        let Some(coverage) = self.coverage else { return };
+        let (line, _) = self.mir.location(location).line_column();
+
+        if last.replace(line) == Some(line) {
+            return;
+        }
+
+        let Some(index) = coverage.counters.index(coverage.file, line) else {
+            return;
+        };
+        let typ = self
+            .builder
+            .context
+            .i64_type()
+            .array_type(coverage.counters.len() as _);
+        let ptr = self.builder.array_index_address(
+            typ,
+            coverage.global.as_pointer_value(),
+            index,
+        );
+
+        self.builder.atomic_add(ptr, self.builder.u64_literal(1));
+    }
+
     fn set_debug_location(&self, location_id: LocationId) {
         let scope = self.builder.debug_scope();
         let (line, col) = self.mir.location(location_id).line_column();
@@ -2235,7 +2474,7 @@ impl<'a, 'ctx> GenerateMain<'a, 'ctx> {
         GenerateMain { db, mir, layouts, names, context, module, builder }
     }
 
-    fn run(self) {
+    fn run(self, counters: Option<&Counters>) {
         let space = AddressSpace::default();
         let entry_block = self.builder.add_block();
 
@@ -2271,6 +2510,28 @@ impl<'a, 'ctx> GenerateMain<'a, 'ctx> {
         let state =
             self.builder.call(rt_state, &[runtime.into()]).into_pointer_value();
 
+        // The coverage counters are registered in the same order as they're
+        // listed in the coverage map, such that the runtime writes them in
+        // that order.
+        if let Some(counters) = counters {
+            let func =
+                self.module.runtime_function(RuntimeFunction::CoverageRegister);
+
+            for (_, counters) in &counters.modules {
+                let len = counters.len();
+                let typ = self.context.i64_type().array_type(len as _);
+                let global = self.module.add_global(typ, &counters.symbol);
+
+                self.builder.call_void(
+                    func,
+                    &[
+                        global.as_pointer_value().into(),
+                        self.builder.u64_literal(len as _).into(),
+                    ],
+                );
+            }
+        }
+
         // Allocate and store all the classes in their corresponding globals.
         for &id in self.mir.modules.keys() {
             let name = &self.names.setup_classes[&id];
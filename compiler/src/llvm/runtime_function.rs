@@ -7,6 +7,7 @@ pub(crate) enum RuntimeFunction {
     CheckRefs,
     ClassObject,
     ClassProcess,
+    CoverageRegister,
     Free,
     MessageNew,
     Allocate,
@@ -30,6 +31,7 @@ impl RuntimeFunction {
             RuntimeFunction::CheckRefs => "inko_check_refs",
             RuntimeFunction::ClassObject => "inko_class_object",
             RuntimeFunction::ClassProcess => "inko_class_process",
+            RuntimeFunction::CoverageRegister => "inko_coverage_register",
             RuntimeFunction::Free => "inko_free",
             RuntimeFunction::MessageNew => "inko_message_new",
             RuntimeFunction::Allocate => "inko_alloc",
@@ -64,6 +66,13 @@ impl RuntimeFunction {
 
                 ret.fn_type(&[proc, val], false)
             }
+            RuntimeFunction::CoverageRegister => {
+                let counters = context.pointer_type().into();
+                let length = context.i64_type().into();
+                let ret = context.void_type();
+
+                ret.fn_type(&[counters, length], false)
+            }
             RuntimeFunction::Free => {
                 let val = context.pointer_type().into();
                 let ret = context.void_type();
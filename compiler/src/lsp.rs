@@ -0,0 +1,1194 @@
+//! A language server for Inko, using the Language Server Protocol.
+//!
+//! The server communicates with a client (typically a text editor) over STDIN
+//! and STDOUT. Source code is type-checked whenever a document changes, using
+//! the unsaved contents of the documents opened by the client. The results
+//! are used to produce diagnostics, and to provide information for hovering
+//! over symbols, jumping to their definitions, and completing names.
+mod index;
+
+use crate::compiler::Compiler;
+use crate::config::Config;
+use crate::diagnostics::{Diagnostic, DiagnosticId};
+use crate::json::Json;
+use crate::lsp::index::{Index, Item, Position};
+use crate::presenters::encode_path;
+use ast::source_location::SourceLocation;
+use std::collections::HashMap;
+use std::env::set_current_dir;
+use std::fs::read_to_string;
+use std::io::{stdin, stdout, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::thread::spawn;
+use types::format::format_type;
+use types::{
+    Database, MethodLookup, ModuleId, Symbol, TypeId, TypeParameterId, TypeRef,
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+const ERROR: i64 = 1;
+const WARNING: i64 = 2;
+
+const METHOD: i64 = 2;
+const FUNCTION: i64 = 3;
+const FIELD: i64 = 5;
+const VARIABLE: i64 = 6;
+const CLASS: i64 = 7;
+const INTERFACE: i64 = 8;
+const MODULE: i64 = 9;
+const CONSTANT: i64 = 21;
+
+/// The error code and message of a request that failed.
+type RequestError = (i64, String);
+
+fn invalid_params() -> RequestError {
+    (INVALID_PARAMS, "the request parameters are invalid".to_string())
+}
+
+/// Reads a single message, returning `None` if the end of the input is
+/// reached.
+fn read_message<R: BufRead>(input: &mut R) -> Result<Option<String>, String> {
+    let mut length = None;
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+
+        let read = input.read_line(&mut line).map_err(|e| e.to_string())?;
+
+        if read == 0 {
+            return Ok(None);
+        }
+
+        let header = line.trim_end();
+
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length.ok_or_else(|| {
+        "the message doesn't specify a valid Content-Length".to_string()
+    })?;
+    let mut body = vec![0; length];
+
+    input.read_exact(&mut body).map_err(|e| e.to_string())?;
+    String::from_utf8(body).map(Some).map_err(|e| e.to_string())
+}
+
+fn write_message<W: Write>(
+    output: &mut W,
+    message: &Json,
+) -> Result<(), String> {
+    let body = message.to_string();
+
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)
+        .and_then(|_| output.flush())
+        .map_err(|e| e.to_string())
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut index = 0;
+
+    while index < encoded.len() {
+        if encoded[index] == b'%' {
+            let hex =
+                std::str::from_utf8(encoded.get(index + 1..index + 3)?).ok()?;
+
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        } else {
+            bytes.push(encoded[index]);
+            index += 1;
+        }
+    }
+
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+fn path_to_uri(path: &Path) -> String {
+    encode_path("file://", path)
+}
+
+/// Returns the line with the given number (starting at 1), without its
+/// trailing newline.
+fn line(text: &str, number: usize) -> &str {
+    text.split('\n')
+        .nth(number.saturating_sub(1))
+        .map_or("", |l| l.strip_suffix('\r').unwrap_or(l))
+}
+
+/// Converts a number of grapheme clusters to a number of UTF-16 code units.
+///
+/// The compiler counts columns in grapheme clusters, while the Language Server
+/// Protocol uses UTF-16 code units by default.
+fn utf16_length(line: &str, graphemes: usize) -> usize {
+    line.graphemes(true).take(graphemes).map(|g| g.encode_utf16().count()).sum()
+}
+
+/// Converts a number of UTF-16 code units to a number of grapheme clusters.
+fn grapheme_length(line: &str, units: usize) -> usize {
+    let mut offset = 0;
+    let mut count = 0;
+
+    for grapheme in line.graphemes(true) {
+        if offset >= units {
+            break;
+        }
+
+        offset += grapheme.encode_utf16().count();
+        count += 1;
+    }
+
+    count
+}
+
+fn range(text: &str, location: &SourceLocation) -> Json {
+    let start_line = *location.line_range.start();
+    let end_line = *location.line_range.end();
+    let start_col = location.column_range.start().saturating_sub(1);
+    let end_col = *location.column_range.end();
+
+    Json::object(vec![
+        ("start", position(text, start_line, start_col)),
+        ("end", position(text, end_line, end_col)),
+    ])
+}
+
+fn position(text: &str, number: usize, graphemes: usize) -> Json {
+    Json::object(vec![
+        ("line", Json::Int(number.saturating_sub(1) as i64)),
+        (
+            "character",
+            Json::Int(utf16_length(line(text, number), graphemes) as i64),
+        ),
+    ])
+}
+
+fn response(id: Json, result: Json) -> Json {
+    Json::object(vec![
+        ("jsonrpc", Json::string("2.0")),
+        ("id", id),
+        ("result", result),
+    ])
+}
+
+fn error_response(id: Json, code: i64, message: String) -> Json {
+    Json::object(vec![
+        ("jsonrpc", Json::string("2.0")),
+        ("id", id),
+        (
+            "error",
+            Json::object(vec![
+                ("code", Json::Int(code)),
+                ("message", Json::String(message)),
+            ]),
+        ),
+    ])
+}
+
+fn notification(method: &str, params: Json) -> Json {
+    Json::object(vec![
+        ("jsonrpc", Json::string("2.0")),
+        ("method", Json::string(method)),
+        ("params", params),
+    ])
+}
+
+fn completion_item(label: &str, kind: i64, detail: String) -> Json {
+    Json::object(vec![
+        ("label", Json::string(label)),
+        ("kind", Json::Int(kind)),
+        ("detail", Json::String(detail)),
+    ])
+}
+
+fn is_identifier(grapheme: &str) -> bool {
+    grapheme.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '?')
+}
+
+fn type_parameters(db: &Database, params: Vec<TypeParameterId>) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+
+    let names: Vec<_> =
+        params.into_iter().map(|p| format_type(db, p)).collect();
+
+    format!("[{}]", names.join(", "))
+}
+
+/// Returns a description of an item, used when hovering over a symbol.
+fn describe(db: &Database, item: Item) -> String {
+    match item {
+        Item::Class(id) => format!(
+            "class {}{}",
+            id.name(db),
+            type_parameters(db, id.type_parameters(db))
+        ),
+        Item::Trait(id) => format!(
+            "trait {}{}",
+            id.name(db),
+            type_parameters(db, id.type_parameters(db))
+        ),
+        Item::Module(id) => format!("import {}", id.name(db)),
+        Item::Method(id) => format_type(db, id),
+        Item::Field(id) => format!(
+            "let {}@{}: {}",
+            if id.is_public(db) { "pub " } else { "" },
+            id.name(db),
+            format_type(db, id.value_type(db))
+        ),
+        Item::Constant(id) => format!(
+            "let {}{}: {}",
+            if Symbol::Constant(id).is_public(db) { "pub " } else { "" },
+            id.name(db),
+            format_type(db, id.value_type(db))
+        ),
+        Item::Variable(id) => format!(
+            "let {}{}: {}",
+            if id.is_mutable(db) { "mut " } else { "" },
+            id.name(db),
+            format_type(db, id.value_type(db))
+        ),
+    }
+}
+
+/// Returns the methods (and optionally fields) available to a value of the
+/// given type.
+fn members(
+    db: &Database,
+    module: ModuleId,
+    typ: TypeRef,
+    private: bool,
+    fields: bool,
+) -> Vec<Json> {
+    let id = match typ.type_id(db) {
+        Ok(id) => id,
+        Err(_) => return Vec::new(),
+    };
+    let mut items = Vec::new();
+
+    for name in id.method_names(db) {
+        // Operators such as `+` can't be called using the regular method call
+        // syntax, so we don't suggest them.
+        if !name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+            continue;
+        }
+
+        if let MethodLookup::Ok(method) =
+            id.lookup_method(db, &name, module, private)
+        {
+            items.push(completion_item(&name, METHOD, format_type(db, method)));
+        }
+    }
+
+    if let (true, TypeId::ClassInstance(ins)) = (fields, id) {
+        for field in ins.instance_of().fields(db) {
+            if field.is_visible_to(db, module) {
+                items.push(completion_item(
+                    field.name(db),
+                    FIELD,
+                    format_type(db, field.value_type(db)),
+                ));
+            }
+        }
+    }
+
+    items
+}
+
+fn symbol_completion(
+    db: &Database,
+    name: &str,
+    symbol: Symbol,
+) -> Option<Json> {
+    let (kind, detail) = match symbol {
+        Symbol::Class(id) => (CLASS, describe(db, Item::Class(id))),
+        Symbol::Trait(id) => (INTERFACE, describe(db, Item::Trait(id))),
+        Symbol::Module(id) => (MODULE, describe(db, Item::Module(id))),
+        Symbol::Constant(id) => (CONSTANT, describe(db, Item::Constant(id))),
+        Symbol::Method(id) => (FUNCTION, format_type(db, id)),
+        Symbol::TypeParameter(_) => return None,
+    };
+
+    Some(completion_item(name, kind, detail))
+}
+
+/// Returns the names available in the scope at the given position.
+fn scope_completions(
+    db: &Database,
+    module: ModuleId,
+    file: &index::File,
+    position: Position,
+) -> Vec<Json> {
+    let mut items = Vec::new();
+
+    for id in file.locals(position) {
+        items.push(completion_item(
+            id.name(db),
+            VARIABLE,
+            format_type(db, id.value_type(db)),
+        ));
+    }
+
+    if let Some(method) = file.method(position) {
+        items.append(&mut members(
+            db,
+            module,
+            method.receiver(db),
+            true,
+            false,
+        ));
+    }
+
+    for (name, symbol) in module.symbols(db) {
+        if let Some(item) = symbol_completion(db, &name, symbol) {
+            items.push(item);
+        }
+    }
+
+    items
+}
+
+/// A document opened by the client.
+struct Document {
+    /// The URI used by the client to refer to the document.
+    uri: String,
+
+    /// The current (possibly unsaved) source code of the document.
+    text: String,
+
+    /// A flag indicating the document changed since it was last checked.
+    dirty: bool,
+}
+
+/// The results of type-checking a document and its dependencies.
+struct Analysis {
+    db: Database,
+    index: Index,
+}
+
+/// A language server that communicates over STDIN and STDOUT.
+#[derive(Default)]
+pub struct Server {
+    /// Additional source directories to search for modules.
+    sources: Vec<PathBuf>,
+
+    /// The documents opened by the client, using their canonical paths as the
+    /// keys.
+    documents: HashMap<PathBuf, Document>,
+
+    /// The results of the most recent successful type-checking run.
+    analysis: Option<Analysis>,
+
+    /// The diagnostics produced by checking each document, grouped by the
+    /// files they're produced for.
+    diagnostics: HashMap<PathBuf, HashMap<PathBuf, Vec<Json>>>,
+
+    /// The diagnostics last published per file.
+    published: HashMap<PathBuf, Vec<Json>>,
+
+    /// The messages to send to the client.
+    output: Vec<Json>,
+
+    /// A flag indicating the client requested the server to shut down.
+    shutdown: bool,
+}
+
+impl Server {
+    pub fn add_source_directory(&mut self, path: PathBuf) {
+        self.sources.push(path);
+    }
+
+    /// Runs the server until the client tells it to exit, returning the exit
+    /// status to use.
+    pub fn run(mut self) -> Result<i32, String> {
+        let (sender, receiver) = channel();
+
+        // Messages are read on a separate thread. This way we can process all
+        // the changes that arrived while checking code, before checking the
+        // code again.
+        spawn(move || {
+            let stdin = stdin();
+            let mut input = stdin.lock();
+
+            while let Some(message) = read_message(&mut input).transpose() {
+                let stop = message.is_err();
+
+                if sender.send(message).is_err() || stop {
+                    break;
+                }
+            }
+        });
+
+        let stdout = stdout();
+        let mut output = stdout.lock();
+
+        while let Ok(message) = receiver.recv() {
+            let mut next = Some(message);
+
+            while let Some(message) = next {
+                let status = self.receive(&message?);
+
+                self.flush(&mut output)?;
+
+                if let Some(status) = status {
+                    return Ok(status);
+                }
+
+                next = receiver.try_recv().ok();
+            }
+
+            self.check_documents();
+            self.flush(&mut output)?;
+        }
+
+        // The client closed its output without telling us to exit first.
+        Ok(1)
+    }
+
+    fn flush<W: Write>(&mut self, output: &mut W) -> Result<(), String> {
+        for message in self.output.drain(..) {
+            write_message(output, &message)?;
+        }
+
+        Ok(())
+    }
+
+    /// Processes a single message, returning an exit status if the server
+    /// should stop.
+    fn receive(&mut self, body: &str) -> Option<i32> {
+        let message = match Json::parse(body) {
+            Ok(message) => message,
+            Err(err) => {
+                self.output.push(error_response(Json::Null, PARSE_ERROR, err));
+                return None;
+            }
+        };
+
+        // Messages without a method are responses to requests sent by the
+        // server. We don't send any requests, so these are ignored.
+        let method = message.get("method").as_str()?;
+        let params = message.get("params");
+        let id = message.get("id");
+
+        if id.is_null() {
+            return self.notification(method, params);
+        }
+
+        let result = if self.shutdown {
+            Err((INVALID_REQUEST, "the server is shutting down".to_string()))
+        } else {
+            self.request(method, params)
+        };
+
+        self.output.push(match result {
+            Ok(value) => response(id.clone(), value),
+            Err((code, msg)) => error_response(id.clone(), code, msg),
+        });
+
+        None
+    }
+
+    fn request(
+        &mut self,
+        method: &str,
+        params: &Json,
+    ) -> Result<Json, RequestError> {
+        match method {
+            "initialize" => Ok(self.initialize(params)),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Json::Null)
+            }
+            "textDocument/hover" => self.hover(params),
+            "textDocument/definition" => self.definition(params),
+            "textDocument/completion" => self.completion(params),
+            _ => Err((
+                METHOD_NOT_FOUND,
+                format!("the method '{}' isn't supported", method),
+            )),
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &Json) -> Option<i32> {
+        match method {
+            "exit" => return Some(if self.shutdown { 0 } else { 1 }),
+            "textDocument/didOpen" => {
+                let doc = params.get("textDocument");
+
+                if let (Some(uri), Some(text)) =
+                    (doc.get("uri").as_str(), doc.get("text").as_str())
+                {
+                    self.open(uri, text.to_string());
+                }
+            }
+            "textDocument/didChange" => {
+                let path = self.document_path(params).ok()?;
+
+                // We only support synchronising full documents, so the last
+                // change contains the entire document.
+                let text = params
+                    .get("contentChanges")
+                    .as_array()
+                    .last()
+                    .and_then(|c| c.get("text").as_str())?;
+
+                if let Some(doc) = self.documents.get_mut(&path) {
+                    doc.text = text.to_string();
+                    doc.dirty = true;
+                }
+            }
+            "textDocument/didClose" => {
+                let path = self.document_path(params).ok()?;
+
+                self.documents.remove(&path);
+                self.diagnostics.remove(&path);
+                self.publish_diagnostics();
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    fn initialize(&mut self, params: &Json) -> Json {
+        let root = params
+            .get("rootUri")
+            .as_str()
+            .and_then(uri_to_path)
+            .or_else(|| params.get("rootPath").as_str().map(PathBuf::from));
+
+        // The compiler determines the project directories relative to the
+        // working directory, so we change it to the project's directory.
+        if let Some(root) = root {
+            let _ = set_current_dir(root);
+        }
+
+        Json::object(vec![
+            (
+                "capabilities",
+                Json::object(vec![
+                    (
+                        "textDocumentSync",
+                        Json::object(vec![
+                            ("openClose", Json::Bool(true)),
+                            ("change", Json::Int(1)),
+                        ]),
+                    ),
+                    ("hoverProvider", Json::Bool(true)),
+                    ("definitionProvider", Json::Bool(true)),
+                    (
+                        "completionProvider",
+                        Json::object(vec![(
+                            "triggerCharacters",
+                            Json::Array(vec![Json::string(".")]),
+                        )]),
+                    ),
+                ]),
+            ),
+            (
+                "serverInfo",
+                Json::object(vec![
+                    ("name", Json::string("inko")),
+                    ("version", Json::string(env!("CARGO_PKG_VERSION"))),
+                ]),
+            ),
+        ])
+    }
+
+    fn open(&mut self, uri: &str, text: String) {
+        if let Some(path) = uri_to_path(uri) {
+            let path = path.canonicalize().unwrap_or(path);
+            let doc = Document { uri: uri.to_string(), text, dirty: true };
+
+            self.documents.insert(path, doc);
+        }
+    }
+
+    fn hover(&mut self, params: &Json) -> Result<Json, RequestError> {
+        let (path, pos) = self.position(params)?;
+        self.prepare(&path);
+
+        let analysis = match self.analysis.as_ref() {
+            Some(val) => val,
+            None => return Ok(Json::Null),
+        };
+        let (loc, item) =
+            match analysis.index.file(&path).and_then(|f| f.symbol(pos)) {
+                Some(val) => val,
+                None => return Ok(Json::Null),
+            };
+        let value = format!("```inko\n{}\n```", describe(&analysis.db, *item));
+
+        Ok(Json::object(vec![
+            (
+                "contents",
+                Json::object(vec![
+                    ("kind", Json::string("markdown")),
+                    ("value", Json::String(value)),
+                ]),
+            ),
+            ("range", range(&self.source(&path), loc)),
+        ]))
+    }
+
+    fn definition(&mut self, params: &Json) -> Result<Json, RequestError> {
+        let (path, pos) = self.position(params)?;
+        self.prepare(&path);
+
+        let analysis = match self.analysis.as_ref() {
+            Some(val) => val,
+            None => return Ok(Json::Null),
+        };
+        let item = match analysis.index.file(&path).and_then(|f| f.symbol(pos))
+        {
+            Some((_, item)) => *item,
+            None => return Ok(Json::Null),
+        };
+
+        Ok(match analysis.index.definition(&analysis.db, item) {
+            Some((file, loc)) => Json::object(vec![
+                ("uri", Json::String(self.uri(file))),
+                ("range", range(&self.source(file), loc)),
+            ]),
+            None => Json::Null,
+        })
+    }
+
+    fn completion(&mut self, params: &Json) -> Result<Json, RequestError> {
+        let (path, (line_num, column)) = self.position(params)?;
+        let text = self.source(&path);
+        self.prepare(&path);
+
+        let analysis = match self.analysis.as_ref() {
+            Some(val) => val,
+            None => return Ok(Json::Array(Vec::new())),
+        };
+        let file = match analysis.index.file(&path) {
+            Some(val) => val,
+            None => return Ok(Json::Array(Vec::new())),
+        };
+        let db = &analysis.db;
+        let graphemes: Vec<_> = line(&text, line_num).graphemes(true).collect();
+        let mut start = column.saturating_sub(1).min(graphemes.len());
+
+        while start > 0 && is_identifier(graphemes[start - 1]) {
+            start -= 1;
+        }
+
+        let method = file.method((line_num, column));
+        let mut items = if start > 1 && graphemes[start - 1] == "." {
+            // The column of the dot is equal to `start`, so the receiver ends
+            // in the column before it.
+            match file.expression_ending_at((line_num, start - 1)) {
+                Some(typ) => {
+                    let own = method
+                        .and_then(|m| m.receiver(db).class_id(db))
+                        .map_or(false, |id| typ.class_id(db) == Some(id));
+
+                    members(db, file.module, typ, own, true)
+                }
+                None => Vec::new(),
+            }
+        } else {
+            scope_completions(db, file.module, file, (line_num, column))
+        };
+
+        items.sort_by(|a, b| {
+            a.get("label").as_str().cmp(&b.get("label").as_str())
+        });
+        items.dedup_by(|a, b| a.get("label") == b.get("label"));
+        Ok(Json::Array(items))
+    }
+
+    /// Returns the path and position (as a line and column) of a request.
+    fn position(
+        &self,
+        params: &Json,
+    ) -> Result<(PathBuf, Position), RequestError> {
+        let path = self.document_path(params)?;
+        let pos = params.get("position");
+        let (line_num, character) =
+            match (pos.get("line").as_int(), pos.get("character").as_int()) {
+                (Some(l), Some(c)) if l >= 0 && c >= 0 => {
+                    (l as usize + 1, c as usize)
+                }
+                _ => return Err(invalid_params()),
+            };
+        let text = self.source(&path);
+        let column = grapheme_length(line(&text, line_num), character) + 1;
+
+        Ok((path, (line_num, column)))
+    }
+
+    fn document_path(&self, params: &Json) -> Result<PathBuf, RequestError> {
+        params
+            .get("textDocument")
+            .get("uri")
+            .as_str()
+            .and_then(uri_to_path)
+            .map(|p| p.canonicalize().unwrap_or(p))
+            .ok_or_else(invalid_params)
+    }
+
+    /// Returns the URI to use for a file.
+    fn uri(&self, path: &Path) -> String {
+        self.documents
+            .get(path)
+            .map_or_else(|| path_to_uri(path), |d| d.uri.clone())
+    }
+
+    /// Returns the source code of a file, preferring the unsaved contents of
+    /// an open document over the contents of the file on disk.
+    fn source(&self, path: &Path) -> String {
+        self.documents
+            .get(path)
+            .map(|d| d.text.clone())
+            .or_else(|| read_to_string(path).ok())
+            .unwrap_or_default()
+    }
+
+    /// Type-checks the given file if necessary, such that requests for it use
+    /// up-to-date information.
+    fn prepare(&mut self, path: &Path) {
+        let dirty = self.documents.get(path).map_or(false, |d| d.dirty);
+        let indexed = self
+            .analysis
+            .as_ref()
+            .map_or(false, |a| a.index.file(path).is_some());
+
+        if dirty || !indexed {
+            self.check(path);
+        }
+    }
+
+    fn check_documents(&mut self) {
+        let mut dirty: Vec<_> = self
+            .documents
+            .iter()
+            .filter(|(_, doc)| doc.dirty)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        dirty.sort();
+
+        for path in dirty {
+            self.check(&path);
+        }
+    }
+
+    /// Type-checks a file and its dependencies, and publishes the resulting
+    /// diagnostics.
+    fn check(&mut self, path: &Path) {
+        let mut config = Config::default();
+
+        // Errors in the project file are reported when building the project,
+        // so we don't want to stop type-checking because of them.
+        let _ = config.load_project_file();
+
+        for dir in &self.sources {
+            config.add_source_directory(dir.clone());
+        }
+
+        if config.tests.is_dir() {
+            config.add_source_directory(config.tests.clone());
+        }
+
+        let mut compiler = Compiler::new(config);
+
+        compiler.state.sources = self
+            .documents
+            .iter()
+            .map(|(path, doc)| (path.clone(), doc.text.clone().into_bytes()))
+            .collect();
+
+        let result = compiler.analyse(path.to_path_buf());
+        let mut by_file: HashMap<PathBuf, Vec<&Diagnostic>> = HashMap::new();
+
+        for diag in compiler.state.diagnostics.iter() {
+            by_file.entry(diag.file().clone()).or_default().push(diag);
+        }
+
+        let diagnostics = by_file
+            .into_iter()
+            .map(|(file, diags)| {
+                let text = self.source(&file);
+                let values = diags
+                    .into_iter()
+                    .map(|diag| {
+                        let mut message = diag.message().to_string();
+
+                        for (kind, note) in diag.notes() {
+                            message.push_str(&format!("\n{}: {}", kind, note));
+                        }
+
+                        let related = diag
+                            .labels()
+                            .iter()
+                            .map(|label| {
+                                let text = self.source(&label.file);
+
+                                Json::object(vec![
+                                    (
+                                        "location",
+                                        Json::object(vec![
+                                            (
+                                                "uri",
+                                                Json::String(
+                                                    self.uri(&label.file),
+                                                ),
+                                            ),
+                                            (
+                                                "range",
+                                                range(&text, &label.location),
+                                            ),
+                                        ]),
+                                    ),
+                                    ("message", Json::string(&label.message)),
+                                ])
+                            })
+                            .collect();
+
+                        Json::object(vec![
+                            ("range", range(&text, diag.location())),
+                            (
+                                "severity",
+                                Json::Int(if diag.is_error() {
+                                    ERROR
+                                } else {
+                                    WARNING
+                                }),
+                            ),
+                            ("code", Json::String(diag.id().to_string())),
+                            ("source", Json::string("inko")),
+                            ("message", Json::String(message)),
+                            ("relatedInformation", Json::Array(related)),
+                        ])
+                    })
+                    .collect();
+
+                (file, values)
+            })
+            .collect();
+
+        self.diagnostics.insert(path.to_path_buf(), diagnostics);
+
+        if let Some(doc) = self.documents.get_mut(path) {
+            doc.dirty = false;
+        }
+
+        // If the code couldn't be parsed we keep the previous results, such
+        // that e.g. completing a method call still works while typing. If
+        // there are no previous results, we use the parts of the code that
+        // could be parsed.
+        let syntax_errors = compiler.state.diagnostics.iter().any(|diag| {
+            diag.is_error() && diag.id() == DiagnosticId::InvalidSyntax
+        });
+
+        match result {
+            Ok(modules) if !syntax_errors || self.analysis.is_none() => {
+                let db = compiler.state.db;
+                let index = Index::new(&db, &modules);
+
+                self.analysis = Some(Analysis { db, index });
+            }
+            _ => {}
+        }
+
+        self.publish_diagnostics();
+    }
+
+    /// Publishes the diagnostics of the files that changed since the last
+    /// time diagnostics were published.
+    fn publish_diagnostics(&mut self) {
+        let mut current: HashMap<PathBuf, Vec<Json>> = HashMap::new();
+
+        // Multiple documents may depend on the same file, producing the same
+        // diagnostics multiple times.
+        for files in self.diagnostics.values() {
+            for (file, diags) in files {
+                let list = current.entry(file.clone()).or_default();
+
+                for diag in diags {
+                    if !list.contains(diag) {
+                        list.push(diag.clone());
+                    }
+                }
+            }
+        }
+
+        let mut paths: Vec<_> =
+            current.keys().chain(self.published.keys()).cloned().collect();
+
+        paths.sort();
+        paths.dedup();
+
+        for path in paths {
+            let new = current.get(&path);
+
+            if self.published.get(&path) == new {
+                continue;
+            }
+
+            let diags = new.cloned().unwrap_or_default();
+            let params = Json::object(vec![
+                ("uri", Json::String(self.uri(&path))),
+                ("diagnostics", Json::Array(diags)),
+            ]);
+
+            self.output
+                .push(notification("textDocument/publishDiagnostics", params));
+        }
+
+        self.published = current;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+    use std::io::Cursor;
+
+    fn request(id: i64, method: &str, params: Json) -> String {
+        Json::object(vec![
+            ("jsonrpc", Json::string("2.0")),
+            ("id", Json::Int(id)),
+            ("method", Json::string(method)),
+            ("params", params),
+        ])
+        .to_string()
+    }
+
+    fn text_position(uri: &str, line: i64, character: i64) -> Json {
+        Json::object(vec![
+            ("textDocument", Json::object(vec![("uri", Json::string(uri))])),
+            (
+                "position",
+                Json::object(vec![
+                    ("line", Json::Int(line)),
+                    ("character", Json::Int(character)),
+                ]),
+            ),
+        ])
+    }
+
+    fn result(server: &mut Server, message: String) -> Json {
+        assert_eq!(server.receive(&message), None);
+
+        let output = server.output.pop().unwrap();
+
+        output.get("result").clone()
+    }
+
+    #[test]
+    fn test_read_message() {
+        let mut input = Cursor::new(
+            "Content-Length: 2\r\nContent-Type: foo\r\n\r\n{}Content-Length: 1\r\n\r\n1"
+                .as_bytes(),
+        );
+
+        assert_eq!(read_message(&mut input), Ok(Some("{}".to_string())));
+        assert_eq!(read_message(&mut input), Ok(Some("1".to_string())));
+        assert_eq!(read_message(&mut input), Ok(None));
+        assert!(read_message(&mut Cursor::new("Foo: 1\r\n\r\n")).is_err());
+    }
+
+    #[test]
+    fn test_write_message() {
+        let mut output = Vec::new();
+
+        write_message(&mut output, &Json::Array(Vec::new())).unwrap();
+        assert_eq!(output, b"Content-Length: 2\r\n\r\n[]".to_vec());
+    }
+
+    #[test]
+    fn test_uris() {
+        let path = PathBuf::from("/a b/ä.inko");
+        let uri = path_to_uri(&path);
+
+        assert_eq!(uri, "file:///a%20b/%C3%A4.inko");
+        assert_eq!(uri_to_path(&uri), Some(path));
+        assert_eq!(uri_to_path("http://foo"), None);
+        assert_eq!(uri_to_path("file:///a%2"), None);
+    }
+
+    #[test]
+    fn test_columns() {
+        let line = "a😀é b";
+
+        assert_eq!(utf16_length(line, 2), 3);
+        assert_eq!(utf16_length(line, 10), 6);
+        assert_eq!(grapheme_length(line, 3), 2);
+        assert_eq!(grapheme_length(line, 4), 3);
+    }
+
+    #[test]
+    fn test_unsupported_requests() {
+        let mut server = Server::default();
+
+        server.receive(&request(1, "foo", Json::Null));
+        assert_eq!(
+            server.output[0].get("error").get("code"),
+            &Json::Int(METHOD_NOT_FOUND)
+        );
+
+        server.output.clear();
+        server.receive("{");
+        assert_eq!(
+            server.output[0].get("error").get("code"),
+            &Json::Int(PARSE_ERROR)
+        );
+    }
+
+    #[test]
+    fn test_shutdown_and_exit() {
+        let mut server = Server::default();
+        let exit = r#"{"jsonrpc":"2.0","method":"exit"}"#;
+
+        assert_eq!(server.receive(exit), Some(1));
+        assert_eq!(
+            result(&mut server, request(1, "shutdown", Json::Null)),
+            Json::Null
+        );
+        assert_eq!(server.receive(exit), Some(0));
+    }
+
+    #[test]
+    fn test_analysis() {
+        let dir = temp_dir().join("inko-lsp-test").join("src");
+        let file = dir.join("main.inko");
+        let source = "\
+class pub Person {
+  let @name: String
+
+  fn greet(other: String) -> String {
+    let greeting = 'Hello ' + other
+
+    greeting.
+  }
+}
+
+fn pub example(person: Person) {
+  person.greet('Alice')
+}
+";
+
+        create_dir_all(&dir).unwrap();
+        write(&file, source).unwrap();
+
+        let file = file.canonicalize().unwrap();
+        let uri = path_to_uri(&file);
+        let mut server = Server::default();
+
+        server.open(&uri, source.replace("greeting.\n", "greeting\n"));
+
+        // Hovering over the call to `greet`.
+        let hover = result(
+            &mut server,
+            request(1, "textDocument/hover", text_position(&uri, 11, 10)),
+        );
+
+        assert_eq!(
+            hover.get("contents").get("value").as_str(),
+            Some("```inko\nfn greet (other: String) -> String\n```")
+        );
+        assert!(server.output.iter().all(|m| m
+            .get("params")
+            .get("diagnostics")
+            .as_array()
+            .is_empty()));
+
+        // Jumping to the definition of `greet`.
+        let definition = result(
+            &mut server,
+            request(2, "textDocument/definition", text_position(&uri, 11, 10)),
+        );
+
+        assert_eq!(definition.get("uri").as_str(), Some(uri.as_str()));
+        assert_eq!(
+            definition.get("range").get("start"),
+            &Json::object(vec![
+                ("line", Json::Int(3)),
+                ("character", Json::Int(5))
+            ])
+        );
+
+        // Completing a method call on a variable, using the results of the
+        // previous analysis as the new code is invalid.
+        server.receive(
+            &Json::object(vec![
+                ("jsonrpc", Json::string("2.0")),
+                ("method", Json::string("textDocument/didChange")),
+                (
+                    "params",
+                    Json::object(vec![
+                        (
+                            "textDocument",
+                            Json::object(vec![("uri", Json::string(&uri))]),
+                        ),
+                        (
+                            "contentChanges",
+                            Json::Array(vec![Json::object(vec![(
+                                "text",
+                                Json::string(source),
+                            )])]),
+                        ),
+                    ]),
+                ),
+            ])
+            .to_string(),
+        );
+
+        let items = result(
+            &mut server,
+            request(3, "textDocument/completion", text_position(&uri, 6, 13)),
+        );
+        let labels: Vec<_> = items
+            .as_array()
+            .iter()
+            .filter_map(|i| i.get("label").as_str())
+            .collect();
+
+        assert!(labels.contains(&"size"));
+        assert!(labels.contains(&"to_upper"));
+        assert!(!labels.contains(&"+"));
+        assert!(server.published.get(&file).map_or(false, |d| d.len() == 1));
+
+        // Completing names in the current scope.
+        let items = result(
+            &mut server,
+            request(4, "textDocument/completion", text_position(&uri, 6, 4)),
+        );
+        let labels: Vec<_> = items
+            .as_array()
+            .iter()
+            .filter_map(|i| i.get("label").as_str())
+            .collect();
+
+        assert!(labels.contains(&"greeting"));
+        assert!(labels.contains(&"other"));
+        assert!(labels.contains(&"greet"));
+        assert!(labels.contains(&"example"));
+        assert!(labels.contains(&"Person"));
+
+        remove_dir_all(dir.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,926 @@
+//! Indexing of type-checked modules for navigating source code.
+use crate::hir;
+use ast::source_location::SourceLocation;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use types::{
+    CallKind, ClassId, ConstantId, ConstantKind, ConstantPatternKind, Database,
+    FieldId, IdentifierKind, MethodId, MethodSource, ModuleId, Symbol, TraitId,
+    TypeId, TypeRef, VariableId,
+};
+
+/// A line and column number, both starting at 1.
+pub(crate) type Position = (usize, usize);
+
+fn start(location: &SourceLocation) -> Position {
+    (*location.line_range.start(), *location.column_range.start())
+}
+
+fn end(location: &SourceLocation) -> Position {
+    (*location.line_range.end(), *location.column_range.end())
+}
+
+fn contains(location: &SourceLocation, position: Position) -> bool {
+    start(location) <= position && position <= end(location)
+}
+
+/// The number of lines and columns a location spans, used to find the
+/// innermost of multiple locations.
+fn size(location: &SourceLocation) -> Position {
+    let (start_line, start_col) = start(location);
+    let (end_line, end_col) = end(location);
+
+    (end_line - start_line, end_col.saturating_sub(start_col))
+}
+
+/// A symbol defined in or referred to by source code.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub(crate) enum Item {
+    Class(ClassId),
+    Trait(TraitId),
+    Module(ModuleId),
+    Method(MethodId),
+    Field(FieldId),
+    Constant(ConstantId),
+    Variable(VariableId),
+}
+
+impl Item {
+    fn from_symbol(symbol: Symbol) -> Option<Item> {
+        match symbol {
+            Symbol::Class(id) => Some(Item::Class(id)),
+            Symbol::Trait(id) => Some(Item::Trait(id)),
+            Symbol::Module(id) => Some(Item::Module(id)),
+            Symbol::Constant(id) => Some(Item::Constant(id)),
+            Symbol::Method(id) => Some(Item::Method(id)),
+            Symbol::TypeParameter(_) => None,
+        }
+    }
+
+    fn from_type(db: &Database, typ: TypeRef) -> Option<Item> {
+        match typ.type_id(db).ok()? {
+            TypeId::Class(id) => Some(Item::Class(id)),
+            TypeId::ClassInstance(ins) => Some(Item::Class(ins.instance_of())),
+            TypeId::Trait(id) => Some(Item::Trait(id)),
+            TypeId::TraitInstance(ins) => Some(Item::Trait(ins.instance_of())),
+            TypeId::Module(id) => Some(Item::Module(id)),
+            _ => None,
+        }
+    }
+}
+
+/// A local variable and the range of source code it's available in.
+pub(crate) struct Local {
+    pub(crate) id: VariableId,
+    start: Position,
+    end: Position,
+}
+
+/// The information indexed for a single source file.
+pub(crate) struct File {
+    /// The module defined by the file.
+    pub(crate) module: ModuleId,
+
+    /// The symbols defined or referred to, and their locations.
+    symbols: Vec<(SourceLocation, Item)>,
+
+    /// The types of expressions, used for completing method calls.
+    expressions: Vec<(SourceLocation, TypeRef)>,
+
+    /// The methods defined in the file.
+    methods: Vec<(SourceLocation, MethodId)>,
+
+    /// The local variables defined in the file.
+    locals: Vec<Local>,
+}
+
+impl File {
+    /// Returns the innermost symbol at the given position.
+    pub(crate) fn symbol(
+        &self,
+        position: Position,
+    ) -> Option<&(SourceLocation, Item)> {
+        self.symbols
+            .iter()
+            .filter(|(loc, _)| contains(loc, position))
+            .min_by_key(|(loc, _)| size(loc))
+    }
+
+    /// Returns the type of the outermost expression that ends at the given
+    /// position.
+    pub(crate) fn expression_ending_at(
+        &self,
+        position: Position,
+    ) -> Option<TypeRef> {
+        self.expressions
+            .iter()
+            .filter(|(loc, _)| end(loc) == position)
+            .max_by_key(|(loc, _)| size(loc))
+            .map(|(_, typ)| *typ)
+    }
+
+    /// Returns the innermost method that contains the given position.
+    pub(crate) fn method(&self, position: Position) -> Option<MethodId> {
+        self.methods
+            .iter()
+            .filter(|(loc, _)| contains(loc, position))
+            .min_by_key(|(loc, _)| size(loc))
+            .map(|(_, id)| *id)
+    }
+
+    /// Returns the local variables available at the given position.
+    pub(crate) fn locals(&self, position: Position) -> Vec<VariableId> {
+        self.locals
+            .iter()
+            .filter(|l| l.start <= position && position <= l.end)
+            .map(|l| l.id)
+            .collect()
+    }
+}
+
+/// An index of the symbols of a program, and where they're defined.
+pub(crate) struct Index {
+    files: HashMap<PathBuf, File>,
+    definitions: HashMap<Item, (PathBuf, SourceLocation)>,
+}
+
+impl Index {
+    pub(crate) fn new(db: &Database, modules: &[hir::Module]) -> Index {
+        let mut index =
+            Index { files: HashMap::new(), definitions: HashMap::new() };
+        let names = modules
+            .iter()
+            .map(|m| (m.module_id.name(db).to_string(), m.module_id))
+            .collect();
+
+        for module in modules {
+            let path = module.module_id.file(db);
+            let mut indexer = Indexer {
+                db,
+                names: &names,
+                definitions: &mut index.definitions,
+                file: File {
+                    module: module.module_id,
+                    symbols: Vec::new(),
+                    expressions: Vec::new(),
+                    methods: Vec::new(),
+                    locals: Vec::new(),
+                },
+                path: path.clone(),
+                scope_end: (usize::MAX, usize::MAX),
+            };
+
+            indexer.module(module);
+            index.files.insert(path, indexer.file);
+        }
+
+        index
+    }
+
+    pub(crate) fn file(&self, path: &Path) -> Option<&File> {
+        self.files.get(path)
+    }
+
+    /// Returns the file and location at which the given item is defined.
+    pub(crate) fn definition(
+        &self,
+        db: &Database,
+        item: Item,
+    ) -> Option<&(PathBuf, SourceLocation)> {
+        self.definitions.get(&item).or_else(|| match item {
+            // Default methods copied into a class don't have a location of
+            // their own, so we use the location of the trait method instead.
+            Item::Method(id) => match id.source(db) {
+                MethodSource::Implementation(_, orig) => {
+                    self.definition(db, Item::Method(orig))
+                }
+                MethodSource::Direct => None,
+            },
+            _ => None,
+        })
+    }
+}
+
+/// A type for walking a HIR module and indexing its symbols.
+struct Indexer<'a> {
+    db: &'a Database,
+    names: &'a HashMap<String, ModuleId>,
+    definitions: &'a mut HashMap<Item, (PathBuf, SourceLocation)>,
+    file: File,
+    path: PathBuf,
+
+    /// The end of the scope local variables are available in.
+    scope_end: Position,
+}
+
+impl<'a> Indexer<'a> {
+    fn module(&mut self, module: &hir::Module) {
+        self.definitions.insert(
+            Item::Module(module.module_id),
+            (self.path.clone(), SourceLocation::new(1..=1, 1..=1)),
+        );
+
+        for expr in &module.expressions {
+            match expr {
+                hir::TopLevelExpression::Class(n) => self.define_class(n),
+                hir::TopLevelExpression::ExternClass(n) => {
+                    self.define_extern_class(n)
+                }
+                hir::TopLevelExpression::Constant(n) => self.define_constant(n),
+                hir::TopLevelExpression::ModuleMethod(n) => self.method(
+                    n.method_id,
+                    &n.name,
+                    &n.type_parameters,
+                    &n.arguments,
+                    &n.return_type,
+                    &n.body,
+                    &n.location,
+                ),
+                hir::TopLevelExpression::ExternFunction(n) => {
+                    self.define_extern_function(n)
+                }
+                hir::TopLevelExpression::Trait(n) => self.define_trait(n),
+                hir::TopLevelExpression::Implement(n) => {
+                    self.implement_trait(n)
+                }
+                hir::TopLevelExpression::Import(n) => self.import(n),
+                hir::TopLevelExpression::Reopen(n) => self.reopen_class(n),
+                hir::TopLevelExpression::ExternImport(_) => {}
+            }
+        }
+    }
+
+    fn define(&mut self, item: Item, location: &SourceLocation) {
+        self.definitions.insert(item, (self.path.clone(), location.clone()));
+        self.refer(item, location);
+    }
+
+    fn refer(&mut self, item: Item, location: &SourceLocation) {
+        self.file.symbols.push((location.clone(), item));
+    }
+
+    fn define_local(
+        &mut self,
+        id: Option<VariableId>,
+        name: &hir::Identifier,
+        start: Position,
+    ) {
+        if let Some(id) = id {
+            self.define(Item::Variable(id), &name.location);
+            self.file.locals.push(Local { id, start, end: self.scope_end });
+        }
+    }
+
+    fn define_class(&mut self, node: &hir::DefineClass) {
+        if let Some(id) = node.class_id {
+            self.define(Item::Class(id), &node.name.location);
+        }
+
+        self.type_parameters(&node.type_parameters);
+
+        for expr in &node.body {
+            match expr {
+                hir::ClassExpression::InstanceMethod(n) => self.method(
+                    n.method_id,
+                    &n.name,
+                    &n.type_parameters,
+                    &n.arguments,
+                    &n.return_type,
+                    &n.body,
+                    &n.location,
+                ),
+                hir::ClassExpression::StaticMethod(n) => self.method(
+                    n.method_id,
+                    &n.name,
+                    &n.type_parameters,
+                    &n.arguments,
+                    &n.return_type,
+                    &n.body,
+                    &n.location,
+                ),
+                hir::ClassExpression::AsyncMethod(n) => self.method(
+                    n.method_id,
+                    &n.name,
+                    &n.type_parameters,
+                    &n.arguments,
+                    &n.return_type,
+                    &n.body,
+                    &n.location,
+                ),
+                hir::ClassExpression::Field(n) => self.define_field(n),
+                hir::ClassExpression::Variant(n) => {
+                    if let Some(id) = n.method_id {
+                        self.define(Item::Method(id), &n.name.location);
+                    }
+
+                    self.types(&n.members);
+                }
+            }
+        }
+    }
+
+    fn define_extern_class(&mut self, node: &hir::DefineExternClass) {
+        if let Some(id) = node.class_id {
+            self.define(Item::Class(id), &node.name.location);
+        }
+
+        for field in &node.fields {
+            self.define_field(field);
+        }
+    }
+
+    fn define_field(&mut self, node: &hir::DefineField) {
+        if let Some(id) = node.field_id {
+            self.define(Item::Field(id), &node.name.location);
+        }
+
+        self.type_signature(&node.value_type);
+    }
+
+    fn define_constant(&mut self, node: &hir::DefineConstant) {
+        if let Some(id) = node.constant_id {
+            self.define(Item::Constant(id), &node.name.location);
+        }
+
+        self.const_expression(&node.value);
+    }
+
+    fn define_extern_function(&mut self, node: &hir::DefineExternFunction) {
+        if let Some(id) = node.method_id {
+            self.define(Item::Method(id), &node.name.location);
+        }
+
+        for arg in &node.arguments {
+            self.type_signature(&arg.value_type);
+        }
+
+        if let Some(typ) = &node.return_type {
+            self.type_signature(typ);
+        }
+    }
+
+    fn define_trait(&mut self, node: &hir::DefineTrait) {
+        if let Some(id) = node.trait_id {
+            self.define(Item::Trait(id), &node.name.location);
+        }
+
+        self.type_parameters(&node.type_parameters);
+
+        for req in &node.requirements {
+            self.type_name(req);
+        }
+
+        for expr in &node.body {
+            match expr {
+                hir::TraitExpression::InstanceMethod(n) => self.method(
+                    n.method_id,
+                    &n.name,
+                    &n.type_parameters,
+                    &n.arguments,
+                    &n.return_type,
+                    &n.body,
+                    &n.location,
+                ),
+                hir::TraitExpression::RequiredMethod(n) => self.method(
+                    n.method_id,
+                    &n.name,
+                    &n.type_parameters,
+                    &n.arguments,
+                    &n.return_type,
+                    &[],
+                    &n.location,
+                ),
+            }
+        }
+    }
+
+    fn implement_trait(&mut self, node: &hir::ImplementTrait) {
+        if let Some(ins) = node.trait_instance {
+            self.refer(
+                Item::Trait(ins.instance_of()),
+                &node.trait_name.name.location,
+            );
+        }
+
+        for arg in &node.trait_name.arguments {
+            self.type_signature(arg);
+        }
+
+        if let Some(ins) = node.class_instance {
+            self.refer(
+                Item::Class(ins.instance_of()),
+                &node.class_name.location,
+            );
+        }
+
+        self.type_bounds(&node.bounds);
+
+        for n in &node.body {
+            self.method(
+                n.method_id,
+                &n.name,
+                &n.type_parameters,
+                &n.arguments,
+                &n.return_type,
+                &n.body,
+                &n.location,
+            );
+        }
+    }
+
+    fn reopen_class(&mut self, node: &hir::ReopenClass) {
+        if let Some(id) = node.class_id {
+            self.refer(Item::Class(id), &node.class_name.location);
+        }
+
+        self.type_bounds(&node.bounds);
+
+        for expr in &node.body {
+            match expr {
+                hir::ReopenClassExpression::InstanceMethod(n) => self.method(
+                    n.method_id,
+                    &n.name,
+                    &n.type_parameters,
+                    &n.arguments,
+                    &n.return_type,
+                    &n.body,
+                    &n.location,
+                ),
+                hir::ReopenClassExpression::StaticMethod(n) => self.method(
+                    n.method_id,
+                    &n.name,
+                    &n.type_parameters,
+                    &n.arguments,
+                    &n.return_type,
+                    &n.body,
+                    &n.location,
+                ),
+                hir::ReopenClassExpression::AsyncMethod(n) => self.method(
+                    n.method_id,
+                    &n.name,
+                    &n.type_parameters,
+                    &n.arguments,
+                    &n.return_type,
+                    &n.body,
+                    &n.location,
+                ),
+            }
+        }
+    }
+
+    fn import(&mut self, node: &hir::Import) {
+        let name = node
+            .source
+            .iter()
+            .map(|i| i.name.as_str())
+            .collect::<Vec<_>>()
+            .join(".");
+        let module = match self.names.get(&name) {
+            Some(&id) => id,
+            None => return,
+        };
+
+        if let (Some(first), Some(last)) =
+            (node.source.first(), node.source.last())
+        {
+            let loc =
+                SourceLocation::start_end(&first.location, &last.location);
+
+            self.refer(Item::Module(module), &loc);
+        }
+
+        for symbol in &node.symbols {
+            let item = if symbol.name.name == "self" {
+                Some(Item::Module(module))
+            } else {
+                module
+                    .symbol(self.db, &symbol.name.name)
+                    .and_then(Item::from_symbol)
+            };
+
+            if let Some(item) = item {
+                self.refer(item, &symbol.name.location);
+
+                if symbol.import_as.location != symbol.name.location {
+                    self.refer(item, &symbol.import_as.location);
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn method(
+        &mut self,
+        id: Option<MethodId>,
+        name: &hir::Identifier,
+        type_parameters: &[hir::TypeParameter],
+        arguments: &[hir::MethodArgument],
+        return_type: &Option<hir::Type>,
+        body: &[hir::Expression],
+        location: &SourceLocation,
+    ) {
+        let scope_end = self.scope_end;
+
+        self.scope_end = end(location);
+
+        if let Some(id) = id {
+            let args = id.arguments(self.db);
+
+            self.define(Item::Method(id), &name.location);
+            self.file.methods.push((location.clone(), id));
+
+            for (node, arg) in arguments.iter().zip(args) {
+                self.define_local(
+                    Some(arg.variable),
+                    &node.name,
+                    start(location),
+                );
+            }
+        }
+
+        self.type_parameters(type_parameters);
+
+        for arg in arguments {
+            self.type_signature(&arg.value_type);
+        }
+
+        if let Some(typ) = return_type {
+            self.type_signature(typ);
+        }
+
+        self.expressions(body);
+        self.scope_end = scope_end;
+    }
+
+    fn type_parameters(&mut self, nodes: &[hir::TypeParameter]) {
+        for node in nodes {
+            for req in &node.requirements {
+                self.type_name(req);
+            }
+        }
+    }
+
+    fn type_bounds(&mut self, nodes: &[hir::TypeBound]) {
+        for node in nodes {
+            for req in &node.requirements {
+                self.type_name(req);
+            }
+        }
+    }
+
+    fn types(&mut self, nodes: &[hir::Type]) {
+        for node in nodes {
+            self.type_signature(node);
+        }
+    }
+
+    fn type_signature(&mut self, node: &hir::Type) {
+        match node {
+            hir::Type::Named(n) => self.type_name(n),
+            hir::Type::Ref(n) | hir::Type::Mut(n) | hir::Type::Uni(n) => {
+                match &n.type_reference {
+                    hir::ReferrableType::Named(n) => self.type_name(n),
+                    hir::ReferrableType::Closure(n) => self.closure_type(n),
+                    hir::ReferrableType::Tuple(n) => self.types(&n.values),
+                }
+            }
+            hir::Type::Closure(n) => self.closure_type(n),
+            hir::Type::Tuple(n) => self.types(&n.values),
+        }
+    }
+
+    fn type_name(&mut self, node: &hir::TypeName) {
+        if let Some(item) = Item::from_type(self.db, node.resolved_type) {
+            self.refer(item, &node.name.location);
+        }
+
+        self.types(&node.arguments);
+    }
+
+    fn closure_type(&mut self, node: &hir::ClosureType) {
+        self.types(&node.arguments);
+
+        if let Some(typ) = &node.return_type {
+            self.type_signature(typ);
+        }
+    }
+
+    fn const_expression(&mut self, node: &hir::ConstExpression) {
+        match node {
+            hir::ConstExpression::Binary(n) => {
+                self.const_expression(&n.left);
+                self.const_expression(&n.right);
+            }
+            hir::ConstExpression::ConstantRef(n) => {
+                if let ConstantKind::Constant(id) = n.kind {
+                    self.refer(Item::Constant(id), &n.location);
+                }
+            }
+            hir::ConstExpression::Array(n) => {
+                for value in &n.values {
+                    self.const_expression(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Indexes a body of expressions, limiting the scope of the variables
+    /// defined in the body to the given location.
+    fn body(&mut self, nodes: &[hir::Expression], location: &SourceLocation) {
+        let scope_end = self.scope_end;
+
+        self.scope_end = end(location);
+        self.expressions(nodes);
+        self.scope_end = scope_end;
+    }
+
+    fn expressions(&mut self, nodes: &[hir::Expression]) {
+        for node in nodes {
+            self.expression(node);
+        }
+    }
+
+    fn expression(&mut self, node: &hir::Expression) {
+        if let Some(typ) = self.expression_type(node) {
+            self.file.expressions.push((node.location().clone(), typ));
+        }
+
+        match node {
+            hir::Expression::And(n) => {
+                self.expression(&n.left);
+                self.expression(&n.right);
+            }
+            hir::Expression::Or(n) => {
+                self.expression(&n.left);
+                self.expression(&n.right);
+            }
+            hir::Expression::AssignField(n) => {
+                self.field(n.field_id, &n.field);
+                self.expression(&n.value);
+            }
+            hir::Expression::ReplaceField(n) => {
+                self.field(n.field_id, &n.field);
+                self.expression(&n.value);
+            }
+            hir::Expression::AssignSetter(n) => {
+                self.expression(&n.receiver);
+                self.call_kind(&n.kind, &n.name.location);
+                self.expression(&n.value);
+            }
+            hir::Expression::AssignVariable(n) => {
+                if let Some(id) = n.variable_id {
+                    self.refer(Item::Variable(id), &n.variable.location);
+                }
+
+                self.expression(&n.value);
+            }
+            hir::Expression::ReplaceVariable(n) => {
+                if let Some(id) = n.variable_id {
+                    self.refer(Item::Variable(id), &n.variable.location);
+                }
+
+                self.expression(&n.value);
+            }
+            hir::Expression::BuiltinCall(n) => self.expressions(&n.arguments),
+            hir::Expression::Call(n) => self.call(n),
+            hir::Expression::Closure(n) => {
+                let scope_end = self.scope_end;
+
+                self.scope_end = end(&n.location);
+
+                for arg in &n.arguments {
+                    self.define_local(
+                        arg.variable_id,
+                        &arg.name,
+                        start(&n.location),
+                    );
+
+                    if let Some(typ) = &arg.value_type {
+                        self.type_signature(typ);
+                    }
+                }
+
+                if let Some(typ) = &n.return_type {
+                    self.type_signature(typ);
+                }
+
+                self.expressions(&n.body);
+                self.scope_end = scope_end;
+            }
+            hir::Expression::ConstantRef(n) => match &n.kind {
+                ConstantKind::Constant(id) => {
+                    self.refer(Item::Constant(*id), &n.location)
+                }
+                ConstantKind::Method(info) => {
+                    self.refer(Item::Method(info.id), &n.location)
+                }
+                _ => {}
+            },
+            hir::Expression::DefineVariable(n) => {
+                if let Some(typ) = &n.value_type {
+                    self.type_signature(typ);
+                }
+
+                self.expression(&n.value);
+                self.define_local(n.variable_id, &n.name, end(&n.location));
+            }
+            hir::Expression::FieldRef(n) => {
+                if let Some(id) = n.field_id {
+                    self.refer(Item::Field(id), &n.location);
+                }
+            }
+            hir::Expression::IdentifierRef(n) => match &n.kind {
+                IdentifierKind::Variable(id) => {
+                    self.refer(Item::Variable(*id), &n.location)
+                }
+                IdentifierKind::Method(info) => {
+                    self.refer(Item::Method(info.id), &n.location)
+                }
+                IdentifierKind::Field(info) => {
+                    self.refer(Item::Field(info.id), &n.location)
+                }
+                IdentifierKind::Unknown => {}
+            },
+            hir::Expression::ClassLiteral(n) => {
+                if let Some(id) = n.class_id {
+                    self.refer(Item::Class(id), &n.class_name.location);
+                }
+
+                for field in &n.fields {
+                    self.field(field.field_id, &field.field);
+                    self.expression(&field.value);
+                }
+            }
+            hir::Expression::Loop(n) => self.body(&n.body, &n.location),
+            hir::Expression::Match(n) => {
+                self.expression(&n.expression);
+
+                for case in &n.cases {
+                    let scope_end = self.scope_end;
+
+                    self.scope_end = end(&case.location);
+                    self.pattern(&case.pattern, start(&case.location));
+
+                    if let Some(guard) = &case.guard {
+                        self.expression(guard);
+                    }
+
+                    self.expressions(&case.body);
+                    self.scope_end = scope_end;
+                }
+            }
+            hir::Expression::Mut(n) => self.expression(&n.value),
+            hir::Expression::Ref(n) => self.expression(&n.value),
+            hir::Expression::Recover(n) => self.body(&n.body, &n.location),
+            hir::Expression::Return(n) => {
+                if let Some(value) = &n.value {
+                    self.expression(value);
+                }
+            }
+            hir::Expression::Scope(n) => self.body(&n.body, &n.location),
+            hir::Expression::String(n) => {
+                for value in &n.values {
+                    if let hir::StringValue::Expression(call) = value {
+                        self.call(call);
+                    }
+                }
+            }
+            hir::Expression::Throw(n) => self.expression(&n.value),
+            hir::Expression::Tuple(n) => self.expressions(&n.values),
+            hir::Expression::TypeCast(n) => {
+                self.expression(&n.value);
+                self.type_signature(&n.cast_to);
+            }
+            hir::Expression::Try(n) => self.expression(&n.expression),
+            hir::Expression::Break(_)
+            | hir::Expression::Next(_)
+            | hir::Expression::False(_)
+            | hir::Expression::True(_)
+            | hir::Expression::Nil(_)
+            | hir::Expression::Float(_)
+            | hir::Expression::Int(_)
+            | hir::Expression::SelfObject(_) => {}
+        }
+    }
+
+    fn call(&mut self, node: &hir::Call) {
+        if let Some(rec) = &node.receiver {
+            self.expression(rec);
+        }
+
+        self.call_kind(&node.kind, &node.name.location);
+
+        for arg in &node.arguments {
+            match arg {
+                hir::Argument::Positional(n) => self.expression(n),
+                hir::Argument::Named(n) => self.expression(&n.value),
+            }
+        }
+    }
+
+    fn call_kind(&mut self, kind: &CallKind, location: &SourceLocation) {
+        match kind {
+            CallKind::Call(info) => self.refer(Item::Method(info.id), location),
+            CallKind::GetField(info) | CallKind::SetField(info) => {
+                self.refer(Item::Field(info.id), location)
+            }
+            CallKind::GetConstant(id) => {
+                self.refer(Item::Constant(*id), location)
+            }
+            _ => {}
+        }
+    }
+
+    fn field(&mut self, id: Option<FieldId>, node: &hir::Field) {
+        if let Some(id) = id {
+            self.refer(Item::Field(id), &node.location);
+        }
+    }
+
+    fn pattern(&mut self, node: &hir::Pattern, scope_start: Position) {
+        match node {
+            hir::Pattern::Class(n) => {
+                for field in &n.values {
+                    self.field(field.field_id, &field.field);
+                    self.pattern(&field.pattern, scope_start);
+                }
+            }
+            hir::Pattern::Constant(n) => match n.kind {
+                ConstantPatternKind::String(id)
+                | ConstantPatternKind::Int(id) => {
+                    self.refer(Item::Constant(id), &n.location)
+                }
+                _ => {}
+            },
+            hir::Pattern::Identifier(n) => {
+                if let Some(typ) = &n.value_type {
+                    self.type_signature(typ);
+                }
+
+                self.define_local(n.variable_id, &n.name, scope_start);
+            }
+            hir::Pattern::Tuple(n) => {
+                for value in &n.values {
+                    self.pattern(value, scope_start);
+                }
+            }
+            hir::Pattern::Variant(n) => {
+                for value in &n.values {
+                    self.pattern(value, scope_start);
+                }
+            }
+            hir::Pattern::Or(n) => {
+                for value in &n.patterns {
+                    self.pattern(value, scope_start);
+                }
+            }
+            hir::Pattern::Int(_)
+            | hir::Pattern::String(_)
+            | hir::Pattern::Wildcard(_)
+            | hir::Pattern::True(_)
+            | hir::Pattern::False(_) => {}
+        }
+    }
+
+    fn expression_type(&self, node: &hir::Expression) -> Option<TypeRef> {
+        let typ = match node {
+            hir::Expression::And(n) => n.resolved_type,
+            hir::Expression::Or(n) => n.resolved_type,
+            hir::Expression::Call(n) => match &n.kind {
+                CallKind::Call(info) => info.returns,
+                CallKind::CallClosure(info) => info.returns,
+                CallKind::GetField(info) => info.variable_type,
+                CallKind::ReadPointer(typ) => *typ,
+                _ => return None,
+            },
+            hir::Expression::IdentifierRef(n) => match &n.kind {
+                IdentifierKind::Variable(id) => id.value_type(self.db),
+                IdentifierKind::Method(info) => info.returns,
+                IdentifierKind::Field(info) => info.variable_type,
+                IdentifierKind::Unknown => return None,
+            },
+            hir::Expression::Closure(n) => n.resolved_type,
+            hir::Expression::ConstantRef(n) => n.resolved_type,
+            hir::Expression::FieldRef(n) => n.resolved_type,
+            hir::Expression::ClassLiteral(n) => n.resolved_type,
+            hir::Expression::Float(n) => n.resolved_type,
+            hir::Expression::Int(n) => n.resolved_type,
+            hir::Expression::String(n) => n.resolved_type,
+            hir::Expression::True(n) => n.resolved_type,
+            hir::Expression::False(n) => n.resolved_type,
+            hir::Expression::Nil(n) => n.resolved_type,
+            hir::Expression::SelfObject(n) => n.resolved_type,
+            hir::Expression::Tuple(n) => n.resolved_type,
+            hir::Expression::Match(n) => n.resolved_type,
+            hir::Expression::Mut(n) => n.resolved_type,
+            hir::Expression::Ref(n) => n.resolved_type,
+            hir::Expression::Recover(n) => n.resolved_type,
+            hir::Expression::Scope(n) => n.resolved_type,
+            hir::Expression::TypeCast(n) => n.resolved_type,
+            _ => return None,
+        };
+
+        Some(typ)
+    }
+}
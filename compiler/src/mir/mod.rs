@@ -12,7 +12,7 @@ use ast::source_location::SourceLocation;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::hash::{Hash, Hasher};
-use std::rc::Rc;
+use std::sync::Arc;
 use types::collections::IndexMap;
 use types::{
     BuiltinFunction, Database, ForeignType, MethodId, Shape, TypeArguments,
@@ -665,8 +665,8 @@ impl Block {
 pub(crate) enum Constant {
     Int(i64),
     Float(f64),
-    String(Rc<String>),
-    Array(Rc<Vec<Constant>>),
+    String(Arc<String>),
+    Array(Arc<Vec<Constant>>),
     Bool(bool),
 }
 
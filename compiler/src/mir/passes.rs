@@ -12,7 +12,7 @@ use std::collections::{HashMap, HashSet};
 use std::iter::repeat_with;
 use std::mem::swap;
 use std::path::PathBuf;
-use std::rc::Rc;
+use std::sync::Arc;
 use types::format::format_type;
 use types::{
     self, Block as _, ClassId, ConstantId, MethodId, ModuleId, TypeBounds,
@@ -656,7 +656,7 @@ impl<'a> DefineConstants<'a> {
                 let val = match n.value {
                     hir::ConstExpression::Int(ref n) => Constant::Int(n.value),
                     hir::ConstExpression::String(ref n) => {
-                        Constant::String(Rc::new(n.value.clone()))
+                        Constant::String(Arc::new(n.value.clone()))
                     }
                     hir::ConstExpression::Float(ref n) => {
                         Constant::Float(n.value)
@@ -685,7 +685,7 @@ impl<'a> DefineConstants<'a> {
         match node {
             hir::ConstExpression::Int(ref n) => Constant::Int(n.value),
             hir::ConstExpression::String(ref n) => {
-                Constant::String(Rc::new(n.value.clone()))
+                Constant::String(Arc::new(n.value.clone()))
             }
             hir::ConstExpression::Float(ref n) => Constant::Float(n.value),
             hir::ConstExpression::Binary(ref n) => self.binary(n),
@@ -696,19 +696,19 @@ impl<'a> DefineConstants<'a> {
                     self.mir.constants.get(&id).cloned().unwrap()
                 }
                 types::ConstantKind::Builtin(id) => match id {
-                    types::BuiltinConstant::Arch => Constant::String(Rc::new(
+                    types::BuiltinConstant::Arch => Constant::String(Arc::new(
                         self.state.config.target.arch_name().to_string(),
                     )),
-                    types::BuiltinConstant::Os => Constant::String(Rc::new(
+                    types::BuiltinConstant::Os => Constant::String(Arc::new(
                         self.state.config.target.os_name().to_string(),
                     )),
-                    types::BuiltinConstant::Abi => Constant::String(Rc::new(
+                    types::BuiltinConstant::Abi => Constant::String(Arc::new(
                         self.state.config.target.abi_name().to_string(),
                     )),
                 },
                 _ => unreachable!(),
             },
-            hir::ConstExpression::Array(ref n) => Constant::Array(Rc::new(
+            hir::ConstExpression::Array(ref n) => Constant::Array(Arc::new(
                 n.values.iter().map(|n| self.expression(n)).collect(),
             )),
             hir::ConstExpression::Invalid(_) => unreachable!(),
@@ -784,10 +784,10 @@ impl<'a> DefineConstants<'a> {
                 }
 
                 if let Some(val) = res {
-                    Constant::String(Rc::new(val))
+                    Constant::String(Arc::new(val))
                 } else {
                     self.const_expr_error(&left, op, &right, loc);
-                    Constant::String(Rc::new(String::new()))
+                    Constant::String(Arc::new(String::new()))
                 }
             }
             Constant::Array(_) | Constant::Bool(_) => {
@@ -1198,6 +1198,10 @@ pub(crate) struct LowerMethod<'a> {
     /// Variables to remap to field reads, and the types to expose the fields
     /// as.
     variable_fields: HashMap<types::VariableId, types::FieldId>,
+
+    /// The locations at which registers were last moved, used to point to
+    /// these locations when producing diagnostics about moved values.
+    move_locations: HashMap<RegisterId, LocationId>,
 }
 
 impl<'a> LowerMethod<'a> {
@@ -1224,6 +1228,7 @@ impl<'a> LowerMethod<'a> {
             surrounding_type_register: RegisterId(SELF_ID),
             self_register: RegisterId(SELF_ID),
             variable_fields: HashMap::new(),
+            move_locations: HashMap::new(),
         }
     }
 
@@ -3353,10 +3358,13 @@ impl<'a> LowerMethod<'a> {
             let self_reg = self.self_register;
 
             if !self.register_is_available(self_reg) {
+                let moved = self.move_location(self_reg);
+
                 self.state.diagnostics.moved_while_captured(
                     SELF_NAME,
                     self.file(),
                     node.location.clone(),
+                    moved,
                 );
             }
 
@@ -3389,10 +3397,13 @@ impl<'a> LowerMethod<'a> {
             let raw = self.get_local(var, loc);
 
             if !self.register_is_available(raw) {
+                let moved = self.move_location(raw);
+
                 self.state.diagnostics.moved_while_captured(
                     &name,
                     self.file(),
                     node.location.clone(),
+                    moved,
                 );
             }
 
@@ -3575,14 +3586,26 @@ impl<'a> LowerMethod<'a> {
             return;
         }
 
+        let moved = self.move_location(register);
+
         self.state.diagnostics.moved_variable(
             name,
             self.file(),
             location.clone(),
+            moved,
         );
     }
 
-    fn record_loop_move(&mut self, register: RegisterId, location: LocationId) {
+    /// Returns the location at which a register was last moved, if known.
+    fn move_location(&self, register: RegisterId) -> Option<SourceLocation> {
+        self.move_locations
+            .get(&register)
+            .map(|&loc| self.mir.location(loc).clone())
+    }
+
+    fn record_move(&mut self, register: RegisterId, location: LocationId) {
+        self.move_locations.insert(register, location);
+
         if self.scope.loop_depth == 0 {
             return;
         }
@@ -3651,7 +3674,7 @@ impl<'a> LowerMethod<'a> {
         self.check_field_move(register, location);
         self.mark_register_as_moved(register);
         self.partially_move_self_if_field(register);
-        self.record_loop_move(register, location);
+        self.record_move(register, location);
 
         if self.register_kind(register).is_field() {
             self.mark_register_as_partially_moved(self.self_register);
@@ -3718,7 +3741,7 @@ impl<'a> LowerMethod<'a> {
                 );
             }
 
-            self.record_loop_move(register, location);
+            self.record_move(register, location);
             self.partially_move_self_if_field(register);
             self.mark_register_as_moved(register);
 
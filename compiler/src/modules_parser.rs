@@ -75,6 +75,7 @@ impl<'a> ModulesParser<'a> {
 
         for (_, path) in &pending {
             scheduled.insert(path.clone());
+            self.state.input_files.insert(path.clone());
         }
 
         for name in &self.state.config.implicit_imports {
@@ -127,8 +128,32 @@ impl<'a> ModulesParser<'a> {
         result
     }
 
+    /// Registers the diagnostics silenced using `# inko:allow(...)` comments.
+    fn suppress_diagnostics(&mut self, ast: &Module) {
+        for sup in &ast.suppressions {
+            if let Some(id) = DiagnosticId::from_str(&sup.id) {
+                self.state.diagnostics.suppress(
+                    id,
+                    ast.file.clone(),
+                    sup.location.clone(),
+                );
+            } else {
+                self.state.diagnostics.unknown_diagnostic(
+                    &sup.id,
+                    ast.file.clone(),
+                    sup.comment.clone(),
+                );
+            }
+        }
+    }
+
     fn parse(&mut self, file: &PathBuf) -> Option<Module> {
-        let input = match read(file) {
+        let input = match self
+            .state
+            .sources
+            .get(file)
+            .map_or_else(|| read(file), |src| Ok(src.clone()))
+        {
             Ok(result) => result,
             Err(err) => {
                 self.state.diagnostics.error(
@@ -147,20 +172,21 @@ impl<'a> ModulesParser<'a> {
         };
 
         let mut parser = Parser::new(input, file.clone());
-
-        match parser.parse() {
-            Ok(ast) => Some(ast),
-            Err(err) => {
-                self.state.diagnostics.error(
-                    DiagnosticId::InvalidSyntax,
-                    err.message,
-                    file.clone(),
-                    err.location,
-                );
-
-                None
-            }
+        let (ast, errors) = parser.parse_with_recovery();
+
+        // The parser recovers from syntax errors, so we report all of them
+        // and continue with the parts of the module that are valid.
+        for err in errors {
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidSyntax,
+                err.message,
+                file.clone(),
+                err.location,
+            );
         }
+
+        self.suppress_diagnostics(&ast);
+        Some(ast)
     }
 }
 
@@ -222,7 +248,7 @@ mod tests {
         let file2 = TempFile::new("parsing2b");
 
         write(file1.path(), "import parsing2b").unwrap();
-        write(file2.path(), "10").unwrap();
+        write(file2.path(), "10\nfn a {}\n20").unwrap();
 
         let mut state = State::new(Config::new());
 
@@ -232,9 +258,9 @@ mod tests {
         let mut pass = ModulesParser::new(&mut state);
         let mods = pass.run(vec![(ModuleName::main(), file1.path().clone())]);
 
-        assert_eq!(mods.len(), 1);
-        assert_eq!(mods[0].name, ModuleName::main());
-        assert_eq!(state.diagnostics.iter().count(), 1);
+        assert_eq!(mods.len(), 2);
+        assert_eq!(mods[1].ast.expressions.len(), 1);
+        assert_eq!(state.diagnostics.iter().count(), 2);
     }
 
     #[test]
@@ -256,6 +282,24 @@ mod tests {
         assert_eq!(state.diagnostics.iter().count(), 1);
     }
 
+    #[test]
+    fn test_run_with_unsaved_source() {
+        let file1 = TempFile::new("parsing1e");
+
+        write(file1.path(), "10").unwrap();
+
+        let mut state = State::new(Config::new());
+
+        state.config.implicit_imports = Vec::new();
+        state.sources.insert(file1.path().clone(), b"let A = 10".to_vec());
+
+        let mut pass = ModulesParser::new(&mut state);
+        let mods = pass.run(vec![(ModuleName::main(), file1.path().clone())]);
+
+        assert_eq!(mods.len(), 1);
+        assert_eq!(state.diagnostics.iter().count(), 0);
+    }
+
     #[test]
     fn test_run_with_implicit_imports() {
         let file1 = TempFile::new("parsing1d");
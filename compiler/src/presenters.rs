@@ -1,19 +1,121 @@
 //! Formatters for diagnostics.
-use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::diagnostics::{Diagnostic, DiagnosticId, Diagnostics};
+use crate::json::Json;
+use ast::source_location::SourceLocation;
+use std::collections::HashMap;
 use std::env::current_dir;
-use std::path::PathBuf;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
 
 /// A type used for presenting diagnostics to the user.
 pub(crate) trait Presenter {
     fn present(&self, diagnostics: &Diagnostics);
 }
 
-/// Print diagnostics in a compact text form, optionally enabling the use of
-/// colors.
+/// The lines of the source files that diagnostics are produced for.
+///
+/// Files are only read once, and files that can't be read are ignored.
+struct Sources {
+    files: HashMap<PathBuf, Option<Vec<String>>>,
+}
+
+impl Sources {
+    fn new() -> Sources {
+        Sources { files: HashMap::new() }
+    }
+
+    /// Returns the given line (starting at 1) of a file.
+    fn line(&mut self, file: &Path, line: usize) -> Option<&str> {
+        self.files
+            .entry(file.to_path_buf())
+            .or_insert_with(|| {
+                read_to_string(file)
+                    .ok()
+                    .map(|text| text.lines().map(String::from).collect())
+            })
+            .as_ref()
+            .and_then(|lines| lines.get(line.checked_sub(1)?))
+            .map(|line| line.as_str())
+    }
+
+    /// Converts a column (starting at 1) counted in grapheme clusters, as
+    /// produced by the lexer, to a column counted in Unicode code points.
+    ///
+    /// If the line can't be read, the column is returned as-is.
+    fn code_point_column(
+        &mut self,
+        file: &Path,
+        line: usize,
+        column: usize,
+    ) -> usize {
+        let Some(text) = self.line(file, line) else { return column };
+        let mut graphemes = text.graphemes(true);
+        let mut result = 1;
+
+        for _ in 1..column {
+            match graphemes.next() {
+                Some(g) => result += g.chars().count(),
+                // Columns may point past the end of the line (e.g. for
+                // diagnostics about a missing token).
+                None => result += 1,
+            }
+        }
+
+        result
+    }
+}
+
+/// A location to highlight in a source snippet.
+struct Span<'a> {
+    location: &'a SourceLocation,
+
+    /// The message to display next to the highlighted code.
+    message: &'a str,
+
+    /// If the span is the primary location of a diagnostic.
+    primary: bool,
+}
+
+/// Returns the text to display below a line to highlight the given columns
+/// (starting at 1).
+///
+/// The returned value is a tuple containing the indentation and the markers.
+/// Tabs in the line are retained in the indentation, such that the markers line
+/// up with the code.
+fn underline(
+    line: &str,
+    start: usize,
+    end: usize,
+    marker: char,
+) -> (String, String) {
+    let graphemes: Vec<_> = line.graphemes(true).collect();
+    let start = start.max(1);
+    let end = end.min(graphemes.len()).max(start);
+    let indent = graphemes
+        .iter()
+        .take(start - 1)
+        .map(|&g| if g == "\t" { '\t' } else { ' ' })
+        .collect();
+
+    (indent, marker.to_string().repeat(end - start + 1))
+}
+
+/// Print diagnostics in a human readable text form, optionally enabling the use
+/// of colors.
 ///
 /// The resulting output looks like this:
 ///
-///     path/to/file.inko:line:column warning(example): this is a warning
+///     path/to/file.inko:2:7 error(duplicate-symbol): the symbol 'A' is already defined
+///       |
+///     1 | class A {}
+///       |       - 'A' is first defined here
+///     2 | class A {}
+///       |       ^
+///
+/// Any notes of the diagnostic are displayed after the source code, in the
+/// form `= note: MESSAGE`. The source code is only displayed if the source
+/// file can be read.
 pub(crate) struct TextPresenter {
     working_directory: PathBuf,
     colors: bool,
@@ -35,14 +137,12 @@ impl TextPresenter {
         Self::new(true)
     }
 
-    fn present_diagnostic(&self, diagnostic: &Diagnostic) {
+    fn present_diagnostic(
+        &self,
+        diagnostic: &Diagnostic,
+        sources: &mut Sources,
+    ) {
         let loc = &diagnostic.location();
-        let abs_path = diagnostic.file().as_path();
-        let rel_path = abs_path
-            .strip_prefix(&self.working_directory)
-            .unwrap_or(abs_path)
-            .to_string_lossy();
-
         let kind = if diagnostic.is_error() {
             format!("{}({})", self.red(self.bold("error")), diagnostic.id())
         } else {
@@ -55,12 +155,146 @@ impl TextPresenter {
 
         eprintln!(
             "{}:{}:{} {}: {}",
-            rel_path,
+            self.relative_path(diagnostic.file()),
             loc.line_range.start(),
             loc.column_range.start(),
             kind,
             diagnostic.message()
         );
+
+        // Labels are grouped per file, such that labels in the same file as
+        // the diagnostic are displayed in the same snippet.
+        let mut files: Vec<(&PathBuf, Vec<Span>)> = vec![(
+            diagnostic.file(),
+            vec![Span { location: loc, message: "", primary: true }],
+        )];
+
+        for label in diagnostic.labels() {
+            let span = Span {
+                location: &label.location,
+                message: &label.message,
+                primary: false,
+            };
+
+            if let Some((_, spans)) =
+                files.iter_mut().find(|(file, _)| *file == &label.file)
+            {
+                spans.push(span);
+            } else {
+                files.push((&label.file, vec![span]));
+            }
+        }
+
+        let width = files
+            .iter()
+            .flat_map(|(_, spans)| spans.iter())
+            .map(|span| span.location.line_range.start().to_string().len())
+            .max()
+            .unwrap_or(1);
+
+        for (index, (file, mut spans)) in files.into_iter().enumerate() {
+            if index > 0 {
+                let (line, column) = spans[0].location.line_column();
+
+                eprintln!(
+                    "{}{} {}:{}:{}",
+                    " ".repeat(width),
+                    self.blue("-->"),
+                    self.relative_path(file),
+                    line,
+                    column
+                );
+            }
+
+            spans.sort_by_key(|span| span.location.line_column());
+            self.present_snippet(
+                diagnostic.is_error(),
+                file,
+                &spans,
+                width,
+                sources,
+            );
+        }
+
+        for (kind, message) in diagnostic.notes() {
+            eprintln!(
+                "{} {} {}: {}",
+                " ".repeat(width),
+                self.blue("="),
+                self.bold(kind.to_string()),
+                message
+            );
+        }
+    }
+
+    fn present_snippet(
+        &self,
+        error: bool,
+        file: &Path,
+        spans: &[Span],
+        width: usize,
+        sources: &mut Sources,
+    ) {
+        let gutter = format!("{} {}", " ".repeat(width), self.blue("|"));
+        let mut previous = None;
+
+        for span in spans {
+            let number = *span.location.line_range.start();
+            let Some(line) = sources.line(file, number) else { continue };
+
+            if previous.is_none() {
+                eprintln!("{}", gutter);
+            }
+
+            if previous != Some(number) {
+                if previous.map_or(false, |p| number > p + 1) {
+                    eprintln!("{}", self.blue("..."));
+                }
+
+                eprintln!(
+                    "{} {}",
+                    self.blue(format!("{:>width$} |", number, width = width)),
+                    line
+                );
+            }
+
+            // For locations spanning multiple lines we only highlight the
+            // first line, as highlighting all lines produces too much noise.
+            let start = *span.location.column_range.start();
+            let end = if span.location.line_range.start()
+                == span.location.line_range.end()
+            {
+                *span.location.column_range.end()
+            } else {
+                usize::MAX
+            };
+            let (indent, markers) = if span.primary {
+                underline(line, start, end, '^')
+            } else {
+                underline(line, start, end, '-')
+            };
+            let markers = if span.message.is_empty() {
+                markers
+            } else {
+                format!("{} {}", markers, span.message)
+            };
+            let markers = if !span.primary {
+                self.blue(markers)
+            } else if error {
+                self.red(markers)
+            } else {
+                self.yellow(markers)
+            };
+
+            eprintln!("{} {}{}", gutter, indent, markers);
+            previous = Some(number);
+        }
+    }
+
+    fn relative_path<'a>(&self, path: &'a Path) -> std::borrow::Cow<'a, str> {
+        path.strip_prefix(&self.working_directory)
+            .unwrap_or(path)
+            .to_string_lossy()
     }
 
     fn red<S: Into<String>>(&self, text: S) -> String {
@@ -71,6 +305,10 @@ impl TextPresenter {
         self.color(33, text)
     }
 
+    fn blue<S: Into<String>>(&self, text: S) -> String {
+        self.color(34, text)
+    }
+
     fn bold<S: Into<String>>(&self, text: S) -> String {
         self.color(1, text)
     }
@@ -86,8 +324,10 @@ impl TextPresenter {
 
 impl Presenter for TextPresenter {
     fn present(&self, diagnostics: &Diagnostics) {
+        let mut sources = Sources::new();
+
         for diag in diagnostics.iter() {
-            self.present_diagnostic(diag);
+            self.present_diagnostic(diag, &mut sources);
         }
     }
 }
@@ -100,31 +340,427 @@ impl JSONPresenter {
         Self {}
     }
 
-    fn to_json(&self, diagnostic: &Diagnostic) -> String {
-        let loc = diagnostic.location();
+    fn location(
+        file: &Path,
+        location: &SourceLocation,
+    ) -> Vec<(&'static str, Json)> {
+        vec![
+            ("file", Json::string(file.to_string_lossy())),
+            (
+                "lines",
+                Json::Array(vec![
+                    Json::Int(*location.line_range.start() as i64),
+                    Json::Int(*location.line_range.end() as i64),
+                ]),
+            ),
+            (
+                "columns",
+                Json::Array(vec![
+                    Json::Int(*location.column_range.start() as i64),
+                    Json::Int(*location.column_range.end() as i64),
+                ]),
+            ),
+        ]
+    }
+
+    fn to_json(&self, diagnostic: &Diagnostic) -> Json {
+        let mut pairs = vec![
+            ("id", Json::string(diagnostic.id().to_string())),
+            ("level", Json::string(diagnostic.kind().to_string())),
+        ];
 
-        format!(
-            "{{\"id\": {:?}, \"level\": {:?}, \"file\": {:?}, \"lines\": [{}, {}], \"columns\": [{}, {}], \"message\": {:?}}}",
-            diagnostic.id().to_string(),
-            diagnostic.kind().to_string(),
-            diagnostic.file().to_string_lossy(),
-            loc.line_range.start(),
-            loc.line_range.end(),
-            loc.column_range.start(),
-            loc.column_range.end(),
-            diagnostic.message()
-        )
+        pairs.append(&mut Self::location(
+            diagnostic.file(),
+            diagnostic.location(),
+        ));
+        pairs.push(("message", Json::string(diagnostic.message())));
+        pairs.push((
+            "labels",
+            Json::Array(
+                diagnostic
+                    .labels()
+                    .iter()
+                    .map(|label| {
+                        let mut pairs =
+                            Self::location(&label.file, &label.location);
+
+                        pairs.push(("message", Json::string(&label.message)));
+                        Json::object(pairs)
+                    })
+                    .collect(),
+            ),
+        ));
+        pairs.push((
+            "notes",
+            Json::Array(
+                diagnostic
+                    .notes()
+                    .iter()
+                    .map(|(kind, message)| {
+                        Json::object(vec![
+                            ("kind", Json::string(kind.to_string())),
+                            ("message", Json::string(message)),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ));
+
+        Json::object(pairs)
     }
 }
 
 impl Presenter for JSONPresenter {
     fn present(&self, diagnostics: &Diagnostics) {
-        let mut entries = Vec::new();
+        let entries =
+            Json::Array(diagnostics.iter().map(|d| self.to_json(d)).collect());
 
-        for diag in diagnostics.iter() {
-            entries.push(self.to_json(diag));
+        eprintln!("{}", entries);
+    }
+}
+
+/// A type that presents diagnostics using the Static Analysis Results
+/// Interchange Format (SARIF), version 2.1.0.
+///
+/// Each diagnostic ID is presented as a rule, using the explanation of the ID
+/// as its description. Paths are relative to the working directory where
+/// possible, using `%SRCROOT%` as the base URI.
+pub(crate) struct SARIFPresenter {
+    working_directory: PathBuf,
+}
+
+impl SARIFPresenter {
+    pub(crate) fn new() -> Self {
+        let working_directory =
+            current_dir().unwrap_or_else(|_| PathBuf::new());
+
+        Self { working_directory }
+    }
+
+    fn rule(id: DiagnosticId) -> Json {
+        let text = id.explanation().trim();
+        let summary =
+            text.split("\n\n").next().unwrap_or("").replace('\n', " ");
+
+        Json::object(vec![
+            ("id", Json::string(id.to_string())),
+            (
+                "shortDescription",
+                Json::object(vec![("text", Json::string(summary))]),
+            ),
+            (
+                "fullDescription",
+                Json::object(vec![("text", Json::string(text))]),
+            ),
+        ])
+    }
+
+    fn location(
+        &self,
+        file: &Path,
+        location: &SourceLocation,
+        sources: &mut Sources,
+    ) -> Json {
+        let mut artifact = Vec::new();
+
+        if let Ok(path) = file.strip_prefix(&self.working_directory) {
+            artifact.push(("uri", Json::String(encode_path("", path))));
+            artifact.push(("uriBaseId", Json::string("%SRCROOT%")));
+        } else {
+            artifact.push(("uri", Json::String(encode_path("file://", file))));
+        }
+
+        // SARIF uses exclusive end columns, while our end columns are
+        // inclusive.
+        let start_line = *location.line_range.start();
+        let end_line = *location.line_range.end();
+        let start_col = sources.code_point_column(
+            file,
+            start_line,
+            *location.column_range.start(),
+        );
+        let end_col = sources.code_point_column(
+            file,
+            end_line,
+            *location.column_range.end() + 1,
+        );
+        let region = vec![
+            ("startLine", Json::Int(start_line as i64)),
+            ("startColumn", Json::Int(start_col as i64)),
+            ("endLine", Json::Int(end_line as i64)),
+            ("endColumn", Json::Int(end_col as i64)),
+        ];
+
+        Json::object(vec![
+            ("artifactLocation", Json::object(artifact)),
+            ("region", Json::object(region)),
+        ])
+    }
+
+    fn result(&self, diagnostic: &Diagnostic, sources: &mut Sources) -> Json {
+        let id = diagnostic.id();
+        let index = DiagnosticId::ALL
+            .iter()
+            .position(|&v| v == id)
+            .expect("DiagnosticId::ALL must contain every diagnostic ID");
+        let mut message = diagnostic.message().to_string();
+
+        for (kind, note) in diagnostic.notes() {
+            message.push_str(&format!("\n{}: {}", kind, note));
         }
 
-        eprintln!("[{}]", entries.join(","));
+        let related = diagnostic
+            .labels()
+            .iter()
+            .enumerate()
+            .map(|(index, label)| {
+                Json::object(vec![
+                    ("id", Json::Int(index as i64)),
+                    (
+                        "physicalLocation",
+                        self.location(&label.file, &label.location, sources),
+                    ),
+                    (
+                        "message",
+                        Json::object(vec![(
+                            "text",
+                            Json::string(&label.message),
+                        )]),
+                    ),
+                ])
+            })
+            .collect();
+
+        Json::object(vec![
+            ("ruleId", Json::string(id.to_string())),
+            ("ruleIndex", Json::Int(index as i64)),
+            ("level", Json::string(diagnostic.kind().to_string())),
+            ("message", Json::object(vec![("text", Json::String(message))])),
+            (
+                "locations",
+                Json::Array(vec![Json::object(vec![(
+                    "physicalLocation",
+                    self.location(
+                        diagnostic.file(),
+                        diagnostic.location(),
+                        sources,
+                    ),
+                )])]),
+            ),
+            ("relatedLocations", Json::Array(related)),
+        ])
+    }
+
+    fn to_json(
+        &self,
+        diagnostics: &Diagnostics,
+        sources: &mut Sources,
+    ) -> Json {
+        let driver = Json::object(vec![
+            ("name", Json::string("inko")),
+            ("version", Json::string(env!("CARGO_PKG_VERSION"))),
+            ("informationUri", Json::string("https://inko-lang.org")),
+            (
+                "rules",
+                Json::Array(
+                    DiagnosticId::ALL.into_iter().map(Self::rule).collect(),
+                ),
+            ),
+        ]);
+        let mut root = encode_path("file://", &self.working_directory);
+
+        if !root.ends_with('/') {
+            root.push('/');
+        }
+
+        let run = Json::object(vec![
+            ("tool", Json::object(vec![("driver", driver)])),
+            (
+                "originalUriBaseIds",
+                Json::object(vec![(
+                    "%SRCROOT%",
+                    Json::object(vec![("uri", Json::String(root))]),
+                )]),
+            ),
+            ("columnKind", Json::string("unicodeCodePoints")),
+            (
+                "results",
+                Json::Array(
+                    diagnostics
+                        .iter()
+                        .map(|d| self.result(d, sources))
+                        .collect(),
+                ),
+            ),
+        ]);
+
+        Json::object(vec![
+            (
+                "$schema",
+                Json::string("https://json.schemastore.org/sarif-2.1.0.json"),
+            ),
+            ("version", Json::string("2.1.0")),
+            ("runs", Json::Array(vec![run])),
+        ])
+    }
+}
+
+impl Presenter for SARIFPresenter {
+    fn present(&self, diagnostics: &Diagnostics) {
+        eprintln!("{}", self.to_json(diagnostics, &mut Sources::new()));
+    }
+}
+
+/// Percent-encodes a path for use in a URI, prefixing it with the given
+/// scheme.
+pub(crate) fn encode_path(scheme: &str, path: &Path) -> String {
+    let mut uri = scheme.to_string();
+
+    for &byte in path.to_string_lossy().as_bytes() {
+        match byte {
+            b'a'..=b'z'
+            | b'A'..=b'Z'
+            | b'0'..=b'9'
+            | b'/'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::{DiagnosticId, Level};
+
+    #[test]
+    fn test_underline() {
+        assert_eq!(
+            underline("let a = b", 9, 9, '^'),
+            (" ".repeat(8), "^".to_string())
+        );
+        assert_eq!(
+            underline("\tfoo(bar)", 2, 4, '-'),
+            ("\t".to_string(), "---".to_string())
+        );
+        assert_eq!(
+            underline("äö = 10", 1, usize::MAX, '^'),
+            (String::new(), "^^^^^^^".to_string())
+        );
+        assert_eq!(underline("", 1, 1, '^'), (String::new(), "^".to_string()));
+    }
+
+    #[test]
+    fn test_json_presenter() {
+        let mut diags = Diagnostics::new(HashMap::new(), Level::Warn);
+        let loc = SourceLocation::new(2..=2, 9..=9);
+
+        diags
+            .error(
+                DiagnosticId::Moved,
+                "'a' can't be used as it has been moved",
+                PathBuf::from("test.inko"),
+                loc.clone(),
+            )
+            .label(
+                "'a' is moved here",
+                PathBuf::from("test.inko"),
+                SourceLocation::new(1..=1, 9..=9),
+            )
+            .help("use a \"reference\"");
+
+        let json = JSONPresenter::new().to_json(diags.iter().next().unwrap());
+
+        assert_eq!(
+            json.to_string(),
+            "{\"id\":\"moved\",\"level\":\"error\",\"file\":\"test.inko\",\
+            \"lines\":[2,2],\"columns\":[9,9],\
+            \"message\":\"'a' can't be used as it has been moved\",\
+            \"labels\":[{\"file\":\"test.inko\",\"lines\":[1,1],\
+            \"columns\":[9,9],\"message\":\"'a' is moved here\"}],\
+            \"notes\":[{\"kind\":\"help\",\
+            \"message\":\"use a \\\"reference\\\"\"}]}"
+        );
+    }
+
+    #[test]
+    fn test_sources_code_point_column() {
+        let mut sources = Sources::new();
+        let file = PathBuf::from("a.inko");
+
+        sources.files.insert(
+            file.clone(),
+            Some(vec!["let a = 'e\u{301}🙂' + b".to_string()]),
+        );
+
+        assert_eq!(sources.code_point_column(&file, 1, 1), 1);
+        assert_eq!(sources.code_point_column(&file, 1, 10), 10);
+        assert_eq!(sources.code_point_column(&file, 1, 11), 12);
+        assert_eq!(sources.code_point_column(&file, 1, 16), 17);
+        assert_eq!(sources.code_point_column(&file, 1, 20), 21);
+        assert_eq!(sources.code_point_column(&file, 2, 5), 5);
+        assert_eq!(
+            sources.code_point_column(Path::new("missing.inko"), 1, 5),
+            5
+        );
+    }
+
+    #[test]
+    fn test_sarif_presenter() {
+        let mut diags = Diagnostics::new(HashMap::new(), Level::Warn);
+        let presenter =
+            SARIFPresenter { working_directory: PathBuf::from("/src") };
+
+        diags
+            .error(
+                DiagnosticId::Moved,
+                "'a' can't be used as it has been moved",
+                PathBuf::from("/src/a b.inko"),
+                SourceLocation::new(2..=2, 9..=10),
+            )
+            .label(
+                "'a' is moved here",
+                PathBuf::from("/lib/test.inko"),
+                SourceLocation::new(1..=1, 9..=9),
+            );
+
+        let json = presenter.to_json(&diags, &mut Sources::new());
+        let run = &json.get("runs").as_array()[0];
+        let rules = run.get("tool").get("driver").get("rules").as_array();
+        let result = &run.get("results").as_array()[0];
+        let index = result.get("ruleIndex").as_int().unwrap() as usize;
+        let loc = result.get("locations").as_array()[0].get("physicalLocation");
+        let related = result.get("relatedLocations").as_array()[0]
+            .get("physicalLocation");
+
+        assert_eq!(json.get("version").as_str(), Some("2.1.0"));
+        assert_eq!(rules.len(), DiagnosticId::ALL.len());
+        assert_eq!(rules[index].get("id").as_str(), Some("moved"));
+        assert_eq!(
+            rules[index].get("shortDescription").get("text").as_str(),
+            Some("A value is used after it has been moved.")
+        );
+        assert_eq!(result.get("ruleId").as_str(), Some("moved"));
+        assert_eq!(result.get("level").as_str(), Some("error"));
+        assert_eq!(
+            loc.get("artifactLocation").get("uri").as_str(),
+            Some("a%20b.inko")
+        );
+        assert_eq!(
+            loc.get("artifactLocation").get("uriBaseId").as_str(),
+            Some("%SRCROOT%")
+        );
+        assert_eq!(loc.get("region").get("startColumn").as_int(), Some(9));
+        assert_eq!(loc.get("region").get("endColumn").as_int(), Some(11));
+        assert_eq!(
+            related.get("artifactLocation").get("uri").as_str(),
+            Some("file:///lib/test.inko")
+        );
+        assert!(related.get("artifactLocation").get("uriBaseId").is_null());
     }
 }
@@ -0,0 +1,417 @@
+//! Parsing of project settings files.
+//!
+//! Settings files use a subset of TOML: sections, and keys assigned strings,
+//! booleans, integers or arrays of these values. Other TOML features (e.g.
+//! tables, dotted keys and dates) aren't needed for the compiler's settings,
+//! and thus aren't supported.
+
+/// The value of a setting.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Value {
+    Bool(bool),
+    Int(i64),
+    String(String),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    pub(crate) fn as_str(&self) -> Result<&str, String> {
+        match self {
+            Value::String(val) => Ok(val),
+            _ => Err("expected a string".to_string()),
+        }
+    }
+
+    pub(crate) fn as_bool(&self) -> Result<bool, String> {
+        match self {
+            Value::Bool(val) => Ok(*val),
+            _ => Err("expected a boolean".to_string()),
+        }
+    }
+
+    pub(crate) fn as_int(&self) -> Result<i64, String> {
+        match self {
+            Value::Int(val) => Ok(*val),
+            _ => Err("expected an integer".to_string()),
+        }
+    }
+
+    pub(crate) fn as_strings(&self) -> Result<Vec<&str>, String> {
+        match self {
+            Value::Array(vals) => vals.iter().map(|v| v.as_str()).collect(),
+            _ => Err("expected an array of strings".to_string()),
+        }
+    }
+}
+
+/// A single setting, such as `opt = 'none'` in the section `[build]`.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Setting {
+    pub(crate) section: String,
+    pub(crate) key: String,
+    pub(crate) value: Value,
+
+    /// The line the setting is defined on.
+    pub(crate) line: usize,
+}
+
+/// Parses a settings file.
+///
+/// Errors are returned in the form `LINE: MESSAGE`.
+pub(crate) fn parse(input: &str) -> Result<Vec<Setting>, String> {
+    let mut parser =
+        Parser { chars: input.chars().collect(), index: 0, line: 1 };
+
+    parser.settings().map_err(|err| format!("{}: {}", parser.line, err))
+}
+
+fn is_name(chr: char) -> bool {
+    chr.is_ascii_alphanumeric() || chr == '_' || chr == '-'
+}
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn settings(&mut self) -> Result<Vec<Setting>, String> {
+        let mut settings = Vec::new();
+        let mut section = String::new();
+
+        loop {
+            self.whitespace(true);
+
+            match self.peek() {
+                None => break,
+                Some('[') => {
+                    self.index += 1;
+                    self.whitespace(false);
+                    section = self.name("a section name")?;
+                    self.whitespace(false);
+                    self.expect(']')?;
+                }
+                Some(_) => {
+                    let line = self.line;
+                    let key = self.name("a setting name")?;
+
+                    if section.is_empty() {
+                        return Err(format!(
+                            "the setting '{}' must be defined in a section",
+                            key
+                        ));
+                    }
+
+                    if settings
+                        .iter()
+                        .any(|s: &Setting| s.section == section && s.key == key)
+                    {
+                        return Err(format!(
+                            "the setting '{}.{}' is already defined",
+                            section, key
+                        ));
+                    }
+
+                    self.whitespace(false);
+                    self.expect('=')?;
+                    self.whitespace(false);
+
+                    let value = self.value()?;
+
+                    settings.push(Setting {
+                        section: section.clone(),
+                        key,
+                        value,
+                        line,
+                    });
+                }
+            }
+
+            // Every section and setting must be followed by a new line (or the
+            // end of the input).
+            self.whitespace(false);
+
+            match self.peek() {
+                None | Some('\n') => {}
+                Some(chr) => {
+                    return Err(format!("expected a new line, found '{}'", chr))
+                }
+            }
+        }
+
+        Ok(settings)
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some('"') => self.double_string().map(Value::String),
+            Some('\'') => self.single_string().map(Value::String),
+            Some('[') => self.array(),
+            Some(chr) if chr.is_ascii_digit() || chr == '-' || chr == '+' => {
+                self.int()
+            }
+            Some(chr) if chr.is_ascii_alphabetic() => {
+                match self.name("a value")?.as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    name => Err(format!("the value '{}' is invalid", name)),
+                }
+            }
+            Some(chr) => Err(format!("expected a value, found '{}'", chr)),
+            None => Err("expected a value".to_string()),
+        }
+    }
+
+    fn int(&mut self) -> Result<Value, String> {
+        let start = self.index;
+
+        self.index += 1;
+
+        while self.peek().map_or(false, |c| c.is_ascii_digit() || c == '_') {
+            self.index += 1;
+        }
+
+        let text: String = self.chars[start..self.index]
+            .iter()
+            .filter(|&&c| c != '_')
+            .collect();
+
+        text.parse::<i64>()
+            .map(Value::Int)
+            .map_err(|_| format!("the integer '{}' is invalid", text))
+    }
+
+    fn double_string(&mut self) -> Result<String, String> {
+        let mut value = String::new();
+
+        self.index += 1;
+
+        loop {
+            match self.next() {
+                Some('"') => break,
+                Some('\\') => value.push(match self.next() {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some(chr) => {
+                        return Err(format!(
+                            "the escape sequence '\\{}' is invalid",
+                            chr
+                        ))
+                    }
+                    None => return Err("expected an escape sequence".into()),
+                }),
+                Some('\n') | None => {
+                    return Err("expected a closing '\"'".to_string())
+                }
+                Some(chr) => value.push(chr),
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn single_string(&mut self) -> Result<String, String> {
+        let mut value = String::new();
+
+        self.index += 1;
+
+        loop {
+            match self.next() {
+                Some('\'') => break,
+                Some('\n') | None => {
+                    return Err("expected a closing \"'\"".to_string())
+                }
+                Some(chr) => value.push(chr),
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        let mut values = Vec::new();
+
+        self.index += 1;
+
+        loop {
+            self.whitespace(true);
+
+            if self.peek() == Some(']') {
+                self.index += 1;
+                break;
+            }
+
+            values.push(self.value()?);
+            self.whitespace(true);
+
+            match self.next() {
+                Some(',') => {}
+                Some(']') => break,
+                _ => return Err("expected a ',' or ']'".to_string()),
+            }
+        }
+
+        Ok(Value::Array(values))
+    }
+
+    fn name(&mut self, expected: &str) -> Result<String, String> {
+        let start = self.index;
+
+        while self.peek().map_or(false, is_name) {
+            self.index += 1;
+        }
+
+        if start == self.index {
+            Err(format!("expected {}", expected))
+        } else {
+            Ok(self.chars[start..self.index].iter().collect())
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.peek() {
+            Some(chr) if chr == expected => {
+                self.index += 1;
+                Ok(())
+            }
+            Some(chr) => {
+                Err(format!("expected '{}', found '{}'", expected, chr))
+            }
+            None => Err(format!("expected '{}'", expected)),
+        }
+    }
+
+    /// Skips whitespace and comments, optionally including new lines.
+    fn whitespace(&mut self, newlines: bool) {
+        while let Some(chr) = self.peek() {
+            match chr {
+                ' ' | '\t' | '\r' => self.index += 1,
+                '\n' if newlines => {
+                    self.index += 1;
+                    self.line += 1;
+                }
+                '#' => {
+                    while self.peek().map_or(false, |c| c != '\n') {
+                        self.index += 1;
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).cloned()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let chr = self.peek();
+
+        if chr.is_some() {
+            self.index += 1;
+        }
+
+        chr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setting(section: &str, key: &str, value: Value, line: usize) -> Setting {
+        Setting {
+            section: section.to_string(),
+            key: key.to_string(),
+            value,
+            line,
+        }
+    }
+
+    #[test]
+    fn test_parse_valid_input() {
+        let input = "\
+# Settings for building executables.
+[build]
+opt = \"aggressive\" # A comment
+static = true
+jobs = 1_0
+include = [
+  'vendor',
+  \"lib\\t\", # Another comment
+]
+
+[ other ]
+empty = []
+";
+
+        assert_eq!(
+            parse(input),
+            Ok(vec![
+                setting(
+                    "build",
+                    "opt",
+                    Value::String("aggressive".to_string()),
+                    3
+                ),
+                setting("build", "static", Value::Bool(true), 4),
+                setting("build", "jobs", Value::Int(10), 5),
+                setting(
+                    "build",
+                    "include",
+                    Value::Array(vec![
+                        Value::String("vendor".to_string()),
+                        Value::String("lib\t".to_string()),
+                    ]),
+                    6
+                ),
+                setting("other", "empty", Value::Array(Vec::new()), 12),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_input() {
+        assert_eq!(
+            parse("opt = 'none'"),
+            Err("1: the setting 'opt' must be defined in a section".to_string())
+        );
+        assert_eq!(
+            parse("[build]\nopt = 'none' static = true"),
+            Err("2: expected a new line, found 's'".to_string())
+        );
+        assert_eq!(
+            parse("[build]\nopt = none"),
+            Err("2: the value 'none' is invalid".to_string())
+        );
+        assert_eq!(
+            parse("[build]\nopt = 'a'\nopt = 'b'"),
+            Err("3: the setting 'build.opt' is already defined".to_string())
+        );
+        assert_eq!(
+            parse("[build]\nopt = \"a"),
+            Err("2: expected a closing '\"'".to_string())
+        );
+        assert_eq!(
+            parse("[build\n"),
+            Err("1: expected ']', found '\n'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_value_conversions() {
+        assert_eq!(Value::Bool(true).as_bool(), Ok(true));
+        assert_eq!(Value::Int(4).as_int(), Ok(4));
+        assert_eq!(Value::String("a".to_string()).as_str(), Ok("a"));
+        assert!(Value::Int(4).as_str().is_err());
+        assert_eq!(
+            Value::Array(vec![Value::String("a".to_string())]).as_strings(),
+            Ok(vec!["a"])
+        );
+        assert!(Value::Array(vec![Value::Int(1)]).as_strings().is_err());
+    }
+}
@@ -1,12 +1,13 @@
 //! Compiler state accessible to compiler passes.
 use crate::config::{Config, SOURCE, TESTS};
-use crate::diagnostics::Diagnostics;
+use crate::diagnostics::{Diagnostics, Level};
 use crate::pkg::manifest::{Manifest, MANIFEST_FILE};
 use crate::target::{OperatingSystem, Target};
+use ast::source_location::SourceLocation;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use types::module_name::ModuleName;
-use types::Database;
+use types::{Database, MethodId, ModuleId, Symbol};
 
 pub(crate) struct BuildTags {
     values: HashSet<String>,
@@ -168,13 +169,37 @@ pub(crate) struct State {
     pub(crate) db: Database,
     pub(crate) build_tags: BuildTags,
     pub(crate) libraries: HashSet<String>,
+
+    /// Source code to use instead of the contents of the files on disk.
+    ///
+    /// This is used when checking source code that's not yet saved, such as
+    /// when type-checking the buffers of a text editor.
+    pub(crate) sources: HashMap<PathBuf, Vec<u8>>,
+
+    /// The locations at which module-level symbols are defined, such that
+    /// diagnostics about duplicate symbols can point to the existing symbol.
+    symbol_locations: HashMap<(ModuleId, String), SourceLocation>,
+
+    /// The names of the symbols that are referred to in each module, used to
+    /// detect unused imports.
+    used_symbols: HashMap<ModuleId, HashSet<String>>,
+
+    /// The methods that are called, used to detect unused private methods.
+    used_methods: HashSet<MethodId>,
+
+    /// The source files explicitly given to the compiler, such as the main
+    /// module or the file to type-check.
+    pub(crate) input_files: HashSet<PathBuf>,
     packages: Packages,
     exists: Exists,
 }
 
 impl State {
     pub(crate) fn new(config: Config) -> Self {
-        let diagnostics = Diagnostics::new();
+        let diagnostics = Diagnostics::new(
+            config.diagnostic_levels.clone(),
+            if config.deny_warnings { Level::Deny } else { Level::Warn },
+        );
         let db = Database::new();
         let build_tags = BuildTags::new(&config.target);
 
@@ -184,11 +209,75 @@ impl State {
             db,
             build_tags,
             libraries: HashSet::new(),
+            sources: HashMap::new(),
+            symbol_locations: HashMap::new(),
+            used_symbols: HashMap::new(),
+            used_methods: HashSet::new(),
+            input_files: HashSet::new(),
             packages: Packages::new(),
             exists: Exists::new(),
         }
     }
 
+    pub(crate) fn add_symbol_location(
+        &mut self,
+        module: ModuleId,
+        name: String,
+        location: SourceLocation,
+    ) {
+        self.symbol_locations.insert((module, name), location);
+    }
+
+    pub(crate) fn symbol_location(
+        &self,
+        module: ModuleId,
+        name: &str,
+    ) -> Option<SourceLocation> {
+        self.symbol_locations.get(&(module, name.to_string())).cloned()
+    }
+
+    /// Returns the symbol with the given name in a module, marking the name as
+    /// used in that module.
+    pub(crate) fn symbol(
+        &mut self,
+        module: ModuleId,
+        name: &str,
+    ) -> Option<Symbol> {
+        self.use_symbol(module, name);
+        module.symbol(&self.db, name)
+    }
+
+    pub(crate) fn use_symbol(&mut self, module: ModuleId, name: &str) {
+        let names = self.used_symbols.entry(module).or_default();
+
+        if !names.contains(name) {
+            names.insert(name.to_string());
+        }
+    }
+
+    pub(crate) fn symbol_is_used(&self, module: ModuleId, name: &str) -> bool {
+        self.used_symbols.get(&module).map_or(false, |n| n.contains(name))
+    }
+
+    /// Returns `true` if the source file is part of the project being
+    /// compiled, instead of the standard library or a dependency.
+    ///
+    /// Warnings about unused code are only produced for such files, as the
+    /// user can't fix these warnings in code they don't own.
+    pub(crate) fn is_project_file(&self, file: &Path) -> bool {
+        file.starts_with(&self.config.source)
+            || file.starts_with(&self.config.tests)
+            || self.input_files.contains(file)
+    }
+
+    pub(crate) fn use_method(&mut self, method: MethodId) {
+        self.used_methods.insert(method);
+    }
+
+    pub(crate) fn method_is_used(&self, method: MethodId) -> bool {
+        self.used_methods.contains(&method)
+    }
+
     pub(crate) fn module_path(
         &mut self,
         importing: PathBuf,
@@ -231,6 +320,7 @@ impl State {
 mod tests {
     use super::*;
     use crate::target::{Abi, Architecture, Target};
+    use types::{Method, MethodKind, Module, Visibility};
 
     #[test]
     fn test_build_tags() {
@@ -252,6 +342,12 @@ mod tests {
             abi: Abi::Native,
         });
 
+        let musl = BuildTags::new(&Target {
+            arch: Architecture::Arm64,
+            os: OperatingSystem::Linux,
+            abi: Abi::Musl,
+        });
+
         assert!(linux.is_defined("amd64"));
         assert!(linux.is_defined("linux"));
         assert!(linux.is_defined("unix"));
@@ -268,5 +364,55 @@ mod tests {
         assert!(mac.is_defined("unix"));
         assert!(!mac.is_defined("bsd"));
         assert!(!mac.is_defined("linux"));
+
+        assert!(musl.is_defined("arm64"));
+        assert!(musl.is_defined("linux"));
+        assert!(musl.is_defined("musl"));
+        assert!(!musl.is_defined("gnu"));
+    }
+
+    #[test]
+    fn test_used_symbols_and_methods() {
+        let mut state = State::new(Config::new());
+        let module = Module::alloc(
+            &mut state.db,
+            ModuleName::new("foo"),
+            "foo.inko".into(),
+        );
+        let method = Method::alloc(
+            &mut state.db,
+            module,
+            "bar".to_string(),
+            Visibility::Private,
+            MethodKind::Instance,
+        );
+
+        assert!(!state.symbol_is_used(module, "bar"));
+        assert!(!state.method_is_used(method));
+        assert!(state.symbol(module, "bar").is_none());
+        assert!(state.symbol_is_used(module, "bar"));
+        assert!(!state.symbol_is_used(module, "baz"));
+
+        state.use_method(method);
+        assert!(state.method_is_used(method));
+    }
+
+    #[test]
+    fn test_is_project_file() {
+        let mut config = Config::new();
+
+        config.source = PathBuf::from("/foo/src");
+        config.tests = PathBuf::from("/foo/test");
+        config.dependencies = PathBuf::from("/foo/dep");
+
+        let mut state = State::new(config);
+
+        state.input_files.insert(PathBuf::from("/bar/main.inko"));
+
+        assert!(state.is_project_file(Path::new("/foo/src/a/b.inko")));
+        assert!(state.is_project_file(Path::new("/foo/test/test_b.inko")));
+        assert!(state.is_project_file(Path::new("/bar/main.inko")));
+        assert!(!state.is_project_file(Path::new("/foo/dep/x/src/c.inko")));
+        assert!(!state.is_project_file(Path::new("/usr/lib/inko/std/int.inko")));
     }
 }
@@ -63,6 +63,7 @@ impl OperatingSystem {
 pub(crate) enum Abi {
     Native,
     Gnu,
+    Musl,
 }
 
 impl Abi {
@@ -70,6 +71,7 @@ impl Abi {
         match input {
             "native" => Some(Abi::Native),
             "gnu" => Some(Abi::Gnu),
+            "musl" => Some(Abi::Musl),
             _ => None,
         }
     }
@@ -77,6 +79,8 @@ impl Abi {
     pub(crate) fn native() -> Abi {
         if cfg!(target_env = "gnu") {
             Abi::Gnu
+        } else if cfg!(target_env = "musl") {
+            Abi::Musl
         } else {
             Abi::Native
         }
@@ -121,10 +125,11 @@ impl Target {
             Architecture::Arm64 => "aarch64",
         };
 
-        let os = match self.os {
-            OperatingSystem::Freebsd => "unknown-freebsd",
-            OperatingSystem::Mac => "apple-darwin",
-            OperatingSystem::Linux => "unknown-linux-gnu",
+        let os = match (&self.os, &self.abi) {
+            (OperatingSystem::Freebsd, _) => "unknown-freebsd",
+            (OperatingSystem::Mac, _) => "apple-darwin",
+            (OperatingSystem::Linux, Abi::Musl) => "unknown-linux-musl",
+            (OperatingSystem::Linux, _) => "unknown-linux-gnu",
         };
 
         format!("{}-{}", arch, os)
@@ -152,9 +157,15 @@ impl Target {
                 _ => "native",
             },
             Abi::Gnu => "gnu",
+            Abi::Musl => "musl",
         }
     }
 
+    /// Returns `true` if the target uses musl as its C standard library.
+    pub(crate) fn is_musl(&self) -> bool {
+        self.abi == Abi::Musl
+    }
+
     pub(crate) fn is_native(&self) -> bool {
         self == &Target::native()
     }
@@ -231,6 +242,14 @@ mod tests {
                 target(Architecture::Arm64, OperatingSystem::Linux, Abi::Gnu,)
             )
         );
+        assert_eq!(
+            Target::from_str("amd64-linux-musl"),
+            Some(target(
+                Architecture::Amd64,
+                OperatingSystem::Linux,
+                Abi::Musl
+            ))
+        );
 
         assert_eq!(Target::from_str("bla-linux-native"), None);
         assert_eq!(Target::from_str("amd64-bla-native"), None);
@@ -262,6 +281,11 @@ mod tests {
                 .llvm_triple(),
             "aarch64-apple-darwin"
         );
+        assert_eq!(
+            target(Architecture::Arm64, OperatingSystem::Linux, Abi::Musl)
+                .llvm_triple(),
+            "aarch64-unknown-linux-musl"
+        );
     }
 
     #[test]
@@ -281,6 +305,11 @@ mod tests {
                 .to_string(),
             "arm64-mac-native"
         );
+        assert_eq!(
+            target(Architecture::Amd64, OperatingSystem::Linux, Abi::Musl)
+                .to_string(),
+            "amd64-linux-musl"
+        );
     }
 
     #[test]
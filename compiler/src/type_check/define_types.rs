@@ -115,12 +115,20 @@ impl<'a> DefineTypes<'a> {
         };
 
         if self.module.symbol_exists(self.db(), &name) {
+            let existing = self.state.symbol_location(module, &name);
+
             self.state.diagnostics.duplicate_symbol(
                 &name,
                 self.file(),
                 node.name.location.clone(),
+                existing,
             );
         } else {
+            self.state.add_symbol_location(
+                module,
+                name.clone(),
+                node.name.location.clone(),
+            );
             self.module.new_symbol(self.db_mut(), name, Symbol::Class(id));
         }
 
@@ -140,12 +148,20 @@ impl<'a> DefineTypes<'a> {
         );
 
         if self.module.symbol_exists(self.db(), &name) {
+            let existing = self.state.symbol_location(module, &name);
+
             self.state.diagnostics.duplicate_symbol(
                 &name,
                 self.file(),
                 node.name.location.clone(),
+                existing,
             );
         } else {
+            self.state.add_symbol_location(
+                module,
+                name.clone(),
+                node.name.location.clone(),
+            );
             self.module.new_symbol(self.db_mut(), name, Symbol::Class(id));
         }
 
@@ -163,12 +179,20 @@ impl<'a> DefineTypes<'a> {
         );
 
         if self.module.symbol_exists(self.db(), &name) {
+            let existing = self.state.symbol_location(module, &name);
+
             self.state.diagnostics.duplicate_symbol(
                 &name,
                 self.file(),
                 node.name.location.clone(),
+                existing,
             );
         } else {
+            self.state.add_symbol_location(
+                module,
+                name.clone(),
+                node.name.location.clone(),
+            );
             self.module.new_symbol(self.db_mut(), name, Symbol::Trait(id));
         }
 
@@ -180,15 +204,24 @@ impl<'a> DefineTypes<'a> {
         let module = self.module;
 
         if module.symbol_exists(self.db(), &name) {
+            let existing = self.state.symbol_location(module, &name);
+
             self.state.diagnostics.duplicate_symbol(
                 &name,
                 self.file(),
                 node.name.location.clone(),
+                existing,
             );
 
             return;
         }
 
+        self.state.add_symbol_location(
+            module,
+            name.clone(),
+            node.name.location.clone(),
+        );
+
         let db = self.db_mut();
         let vis = Visibility::public(node.public);
         let id = Constant::alloc(db, module, name, vis, TypeRef::Unknown);
@@ -241,7 +274,7 @@ impl<'a> ImplementTraits<'a> {
 
     fn implement_trait(&mut self, node: &mut hir::ImplementTrait) {
         let class_name = &node.class_name.name;
-        let class_id = match self.module.symbol(self.db(), class_name) {
+        let class_id = match self.state.symbol(self.module, class_name) {
             Some(Symbol::Class(id)) => id,
             Some(_) => {
                 self.state.diagnostics.not_a_class(
@@ -296,6 +329,21 @@ impl<'a> ImplementTraits<'a> {
         {
             let name = &node.trait_name.name.name;
 
+            if self.db().process_trait() == Some(instance.instance_of()) {
+                self.state.diagnostics.error(
+                    DiagnosticId::InvalidImplementation,
+                    format!(
+                        "the trait '{}' is implemented by all async classes, \
+                        and can't be implemented manually",
+                        name
+                    ),
+                    self.file(),
+                    node.location.clone(),
+                );
+
+                return;
+            }
+
             if class_id
                 .trait_implementation(self.db(), instance.instance_of())
                 .is_some()
@@ -1157,8 +1205,8 @@ mod tests {
     use std::fmt::Write as _;
     use types::module_name::ModuleName;
     use types::{
-        ClassId, ConstantId, TraitId, TraitInstance, TypeBounds,
-        FIRST_USER_CLASS_ID,
+        ClassId, ConstantId, Module, TraitId, TraitInstance, TypeBounds,
+        FIRST_USER_CLASS_ID, PROCESS_MODULE, PROCESS_TRAIT,
     };
 
     fn get_trait(db: &Database, module: ModuleId, name: &str) -> TraitId {
@@ -1480,6 +1528,56 @@ mod tests {
         assert_eq!(error.location(), &cols(19, 24));
     }
 
+    #[test]
+    fn test_implement_process_trait() {
+        let mut state = State::new(Config::new());
+        let mut modules = parse(&mut state, "impl Process for String {}");
+        let module = ModuleId(0);
+        let process_mod = Module::alloc(
+            &mut state.db,
+            ModuleName::new(PROCESS_MODULE),
+            "process.inko".into(),
+        );
+        let process = Trait::alloc(
+            &mut state.db,
+            PROCESS_TRAIT.to_string(),
+            Visibility::Public,
+            process_mod,
+        );
+        let string = Class::alloc(
+            &mut state.db,
+            "String".to_string(),
+            ClassKind::Regular,
+            Visibility::Private,
+            module,
+        );
+
+        process_mod.new_symbol(
+            &mut state.db,
+            PROCESS_TRAIT.to_string(),
+            Symbol::Trait(process),
+        );
+        module.new_symbol(
+            &mut state.db,
+            PROCESS_TRAIT.to_string(),
+            Symbol::Trait(process),
+        );
+        module.new_symbol(
+            &mut state.db,
+            "String".to_string(),
+            Symbol::Class(string),
+        );
+
+        define_drop_trait(&mut state);
+        assert!(!ImplementTraits::run_all(&mut state, &mut modules));
+
+        let error = state.diagnostics.iter().next().unwrap();
+
+        assert_eq!(error.id(), DiagnosticId::InvalidImplementation);
+        assert_eq!(error.location(), &cols(1, 26));
+        assert!(string.trait_implementation(&state.db, process).is_none());
+    }
+
     #[test]
     fn test_implement_trait_with_invalid_class() {
         let mut state = State::new(Config::new());
@@ -6,6 +6,7 @@ use crate::type_check::{DefineAndCheckTypeSignature, Rules, TypeScope};
 use ast::source_location::SourceLocation;
 use std::cell::Cell;
 use std::collections::{HashMap, HashSet};
+use std::mem::take;
 use std::path::PathBuf;
 use types::check::{Environment, TypeChecker};
 use types::format::{format_type, format_type_with_arguments};
@@ -57,11 +58,18 @@ struct Pattern<'a> {
 
     /// The variables introduced by this pattern.
     variables: HashMap<String, VariableId>,
+
+    /// The locations at which the variables of this pattern are defined.
+    locations: HashMap<String, SourceLocation>,
 }
 
 impl<'a> Pattern<'a> {
     fn new(variable_scope: &'a mut VariableScope) -> Self {
-        Self { variable_scope, variables: HashMap::new() }
+        Self {
+            variable_scope,
+            variables: HashMap::new(),
+            locations: HashMap::new(),
+        }
     }
 }
 
@@ -277,6 +285,8 @@ impl MethodCall {
         receiver_id: TypeId,
         method: MethodId,
     ) -> Self {
+        state.use_method(method);
+
         // When checking arguments we need access to the type arguments of the
         // receiver, along with any type arguments introduced by the method
         // itself.
@@ -774,6 +784,7 @@ impl<'a> Expressions<'a> {
             &mut scope,
             &node.location,
         );
+        checker.check_unused_variables(&node.arguments);
     }
 
     fn define_instance_method(&mut self, node: &mut hir::DefineInstanceMethod) {
@@ -807,6 +818,7 @@ impl<'a> Expressions<'a> {
             &mut scope,
             &node.location,
         );
+        checker.check_unused_variables(&node.arguments);
     }
 
     fn define_async_method(&mut self, node: &mut hir::DefineAsyncMethod) {
@@ -839,6 +851,7 @@ impl<'a> Expressions<'a> {
             &mut scope,
             &node.location,
         );
+        checker.check_unused_variables(&node.arguments);
     }
 
     fn define_static_method(&mut self, node: &mut hir::DefineStaticMethod) {
@@ -870,6 +883,7 @@ impl<'a> Expressions<'a> {
             &mut scope,
             &node.location,
         );
+        checker.check_unused_variables(&node.arguments);
     }
 
     fn define_field_types(
@@ -1057,7 +1071,7 @@ impl<'a> CheckConstant<'a> {
         let name = &node.name;
         let symbol = if let Some(src) = node.source.as_ref() {
             if let Some(Symbol::Module(module)) =
-                self.module.symbol(self.db(), &src.name)
+                self.state.symbol(self.module, &src.name)
             {
                 module.symbol(self.db(), name)
             } else {
@@ -1070,7 +1084,7 @@ impl<'a> CheckConstant<'a> {
                 return TypeRef::Error;
             }
         } else {
-            self.module.symbol(self.db(), name)
+            self.state.symbol(self.module, name)
         };
 
         match symbol {
@@ -1252,6 +1266,16 @@ struct CheckMethodBody<'a> {
 
     /// Any bounds to apply to type parameters.
     bounds: &'a TypeBounds,
+
+    /// The local variables defined in the method, and the locations at which
+    /// they are defined.
+    variables: Vec<(VariableId, SourceLocation)>,
+
+    /// The variables and arguments that are used.
+    used_variables: HashSet<VariableId>,
+
+    /// The number of errors produced before checking the method body.
+    errors: usize,
 }
 
 impl<'a> CheckMethodBody<'a> {
@@ -1262,7 +1286,77 @@ impl<'a> CheckMethodBody<'a> {
         self_type: TypeId,
         bounds: &'a TypeBounds,
     ) -> Self {
-        Self { state, module, method, self_type, bounds }
+        let errors = state.diagnostics.error_count();
+
+        Self {
+            state,
+            module,
+            method,
+            self_type,
+            bounds,
+            variables: Vec::new(),
+            used_variables: HashSet::new(),
+            errors,
+        }
+    }
+
+    /// Produces warnings for the variables and arguments that are never used.
+    ///
+    /// Variables and arguments of which the name starts with an underscore
+    /// are ignored, as are the arguments of methods of which the signature is
+    /// dictated by a trait. Warnings are only produced for the project's own
+    /// source files.
+    ///
+    /// If checking the method body produced any errors, no warnings are
+    /// produced, as variables may then be used in expressions that failed to
+    /// type-check, and thus aren't marked as used.
+    fn check_unused_variables(&mut self, arguments: &[hir::MethodArgument]) {
+        if self.state.diagnostics.error_count() > self.errors
+            || !self.state.is_project_file(&self.file())
+        {
+            self.variables.clear();
+            return;
+        }
+
+        let from_trait = matches!(
+            self.self_type,
+            TypeId::Trait(_) | TypeId::TraitInstance(_)
+        ) || matches!(
+            self.method.source(self.db()),
+            MethodSource::Implementation(_, _)
+        );
+
+        if !from_trait {
+            for (arg, node) in
+                self.method.arguments(self.db()).into_iter().zip(arguments)
+            {
+                if self.used_variables.contains(&arg.variable)
+                    || arg.name.starts_with('_')
+                {
+                    continue;
+                }
+
+                self.state.diagnostics.unused_argument(
+                    &arg.name,
+                    self.file(),
+                    node.name.location.clone(),
+                );
+            }
+        }
+
+        for (var, location) in take(&mut self.variables) {
+            let name = var.name(self.db());
+
+            if self.used_variables.contains(&var) || name.starts_with('_') {
+                continue;
+            }
+
+            self.state.diagnostics.unused_variable(
+                &name.clone(),
+                self.file(),
+                location,
+            );
+        }
     }
 
     fn expressions(
@@ -1538,7 +1632,7 @@ impl<'a> CheckMethodBody<'a> {
     ) -> TypeRef {
         let name = &node.class_name.name;
         let class = if let Some(Symbol::Class(id)) =
-            self.module.symbol(self.db(), name)
+            self.state.symbol(self.module, name)
         {
             id
         } else {
@@ -1770,6 +1864,7 @@ impl<'a> CheckMethodBody<'a> {
             node.mutable,
         );
 
+        self.variables.push((id, node.name.location.clone()));
         node.variable_id = Some(id);
         rtype
     }
@@ -1851,6 +1946,7 @@ impl<'a> CheckMethodBody<'a> {
                 &name,
                 self.file(),
                 node.location.clone(),
+                pattern.locations.get(&name).cloned(),
             );
         }
 
@@ -1896,6 +1992,8 @@ impl<'a> CheckMethodBody<'a> {
 
         node.variable_id = Some(id);
 
+        self.variables.push((id, node.location.clone()));
+        pattern.locations.insert(name.clone(), node.location.clone());
         pattern.variables.insert(name, id);
     }
 
@@ -2595,7 +2693,7 @@ impl<'a> CheckMethodBody<'a> {
                     return TypeRef::Error;
                 }
                 _ => {
-                    match self.module.symbol(self.db(), &node.name) {
+                    match self.state.symbol(self.module, &node.name) {
                         Some(Symbol::Constant(id)) => {
                             node.resolved_type = id.value_type(self.db());
                             node.kind = ConstantKind::Constant(id);
@@ -2620,7 +2718,7 @@ impl<'a> CheckMethodBody<'a> {
                     }
 
                     if let Some(Symbol::Method(method)) =
-                        module.symbol(self.db(), &node.name)
+                        self.state.symbol(module, &node.name)
                     {
                         let id = method.module(self.db());
 
@@ -2726,7 +2824,7 @@ impl<'a> CheckMethodBody<'a> {
                 }
                 _ => {
                     if let Some(Symbol::Module(id)) =
-                        module.symbol(self.db(), name)
+                        self.state.symbol(module, name)
                     {
                         if !receiver {
                             self.state.diagnostics.symbol_not_a_value(
@@ -2742,7 +2840,7 @@ impl<'a> CheckMethodBody<'a> {
                     }
 
                     if let Some(Symbol::Method(method)) =
-                        module.symbol(self.db(), name)
+                        self.state.symbol(module, name)
                     {
                         let id = method.module(self.db());
 
@@ -3775,7 +3873,7 @@ impl<'a> CheckMethodBody<'a> {
                 }
                 MethodLookup::None => {
                     if let Some(Symbol::Method(method)) =
-                        self.module.symbol(self.db(), name)
+                        self.state.symbol(self.module, name)
                     {
                         // The receiver of imported module methods is the module
                         // they are defined in.
@@ -4101,7 +4199,7 @@ impl<'a> CheckMethodBody<'a> {
     ) -> Result<Option<Symbol>, ()> {
         if let Some(src) = source {
             if let Some(Symbol::Module(module)) =
-                self.module.symbol(self.db(), &src.name)
+                self.state.symbol(self.module, &src.name)
             {
                 Ok(module.symbol(self.db(), name))
             } else {
@@ -4114,7 +4212,7 @@ impl<'a> CheckMethodBody<'a> {
                 Err(())
             }
         } else {
-            Ok(self.module.symbol(self.db(), name))
+            Ok(self.state.symbol(self.module, name))
         }
     }
 
@@ -4391,6 +4489,9 @@ impl<'a> CheckMethodBody<'a> {
         }
 
         let var = var?;
+
+        self.used_variables.insert(var);
+
         let mut capture_as = var.value_type(self.db());
         let mut expose_as = capture_as;
         let mut captured = false;
@@ -87,12 +87,20 @@ impl<'a> DefineImportedTypes<'a> {
         };
 
         if self.module.symbol_exists(self.db(), &name) {
+            let existing = self.state.symbol_location(self.module, &name);
+
             self.state.diagnostics.duplicate_symbol(
                 &name,
                 self.file(),
                 location.clone(),
+                existing,
             );
         } else {
+            self.state.add_symbol_location(
+                self.module,
+                name.clone(),
+                location.clone(),
+            );
             self.module.new_symbol(self.db_mut(), name, Symbol::Module(source));
         }
     }
@@ -103,10 +111,14 @@ impl<'a> DefineImportedTypes<'a> {
 
         if let Some(symbol) = source.symbol(self.db(), name) {
             if self.module.symbol_exists(self.db(), import_as) {
+                let existing =
+                    self.state.symbol_location(self.module, import_as);
+
                 self.state.diagnostics.duplicate_symbol(
                     import_as,
                     self.file(),
                     node.import_as.location.clone(),
+                    existing,
                 );
             } else if !symbol.is_visible_to(self.db(), self.module) {
                 self.state.diagnostics.error(
@@ -119,6 +131,11 @@ impl<'a> DefineImportedTypes<'a> {
                     node.name.location.clone(),
                 );
             } else {
+                self.state.add_symbol_location(
+                    self.module,
+                    import_as.clone(),
+                    node.import_as.location.clone(),
+                );
                 self.module.new_symbol(
                     self.db_mut(),
                     import_as.clone(),
@@ -179,6 +196,74 @@ impl<'a> CollectExternImports<'a> {
     }
 }
 
+/// A compiler pass that produces warnings for imported symbols that are never
+/// used.
+pub(crate) struct CheckUnusedImports<'a> {
+    state: &'a mut State,
+    module: ModuleId,
+}
+
+impl<'a> CheckUnusedImports<'a> {
+    pub(crate) fn run_all(
+        state: &'a mut State,
+        modules: &[hir::Module],
+    ) -> bool {
+        for module in modules {
+            CheckUnusedImports { state, module: module.module_id }.run(module);
+        }
+
+        !state.diagnostics.has_errors()
+    }
+
+    fn run(mut self, module: &hir::Module) {
+        // Implicitly imported modules (e.g. std.init) import other modules
+        // only to ensure they're processed, so we don't check these.
+        if self
+            .state
+            .config
+            .implicit_imports
+            .contains(self.module.name(&self.state.db))
+            || !self.state.is_project_file(&self.module.file(&self.state.db))
+        {
+            return;
+        }
+
+        for expr in &module.expressions {
+            let hir::TopLevelExpression::Import(ref node) = expr else {
+                continue;
+            };
+            let tail = node.source.last().unwrap();
+
+            if node.symbols.is_empty() {
+                self.check(&tail.name, &tail.location);
+                continue;
+            }
+
+            for symbol in &node.symbols {
+                let name = if symbol.import_as.name == IMPORT_MODULE_ITSELF_NAME
+                {
+                    &tail.name
+                } else {
+                    &symbol.import_as.name
+                };
+
+                self.check(name, &symbol.import_as.location);
+            }
+        }
+    }
+
+    fn check(&mut self, name: &str, location: &SourceLocation) {
+        if name.starts_with('_') || self.state.symbol_is_used(self.module, name)
+        {
+            return;
+        }
+
+        let file = self.module.file(&self.state.db);
+
+        self.state.diagnostics.unused_import(name, file, location.clone());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
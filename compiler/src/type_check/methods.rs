@@ -297,12 +297,20 @@ impl<'a> DefineModuleMethodNames<'a> {
         );
 
         if self.module.symbol_exists(self.db(), name) {
+            let existing = self.state.symbol_location(module, name);
+
             self.state.diagnostics.duplicate_symbol(
                 name,
                 self.file(),
                 node.location.clone(),
+                existing,
             );
         } else {
+            self.state.add_symbol_location(
+                module,
+                name.clone(),
+                node.name.location.clone(),
+            );
             self.module.new_symbol(
                 self.db_mut(),
                 name.clone(),
@@ -331,12 +339,20 @@ impl<'a> DefineModuleMethodNames<'a> {
         }
 
         if self.module.symbol_exists(self.db(), name) {
+            let existing = self.state.symbol_location(module, name);
+
             self.state.diagnostics.duplicate_symbol(
                 name,
                 self.file(),
                 node.location.clone(),
+                existing,
             );
         } else {
+            self.state.add_symbol_location(
+                module,
+                name.clone(),
+                node.name.location.clone(),
+            );
             self.module.new_symbol(
                 self.db_mut(),
                 name.clone(),
@@ -476,7 +492,7 @@ impl<'a> DefineMethods<'a> {
 
     fn reopen_class(&mut self, node: &mut hir::ReopenClass) {
         let class_name = &node.class_name.name;
-        let class_id = match self.module.symbol(self.db(), class_name) {
+        let class_id = match self.state.symbol(self.module, class_name) {
             Some(Symbol::Class(id)) => id,
             Some(_) => {
                 self.state.diagnostics.not_a_class(
@@ -1119,6 +1135,107 @@ impl<'a> CheckMainMethod<'a> {
     }
 }
 
+/// A compiler pass that produces warnings for private methods that are never
+/// called.
+///
+/// Methods implemented from traits and methods defined in traits are ignored,
+/// as these may be called through the trait. Methods of which the name starts
+/// with an underscore are also ignored.
+pub(crate) struct CheckUnusedMethods<'a> {
+    state: &'a mut State,
+    module: ModuleId,
+}
+
+impl<'a> CheckUnusedMethods<'a> {
+    pub(crate) fn run_all(
+        state: &'a mut State,
+        modules: &Vec<hir::Module>,
+    ) -> bool {
+        for module in modules {
+            CheckUnusedMethods { state, module: module.module_id }.run(module);
+        }
+
+        !state.diagnostics.has_errors()
+    }
+
+    fn run(&mut self, module: &hir::Module) {
+        if !self.state.is_project_file(&self.module.file(&self.state.db)) {
+            return;
+        }
+
+        for expr in &module.expressions {
+            match expr {
+                hir::TopLevelExpression::Class(ref node) => {
+                    for expr in &node.body {
+                        match expr {
+                            // The main method isn't marked as such when
+                            // checking a single file, so we skip it based on
+                            // its name.
+                            hir::ClassExpression::AsyncMethod(ref n)
+                                if node.name.name == MAIN_CLASS
+                                    && n.name.name == MAIN_METHOD => {}
+                            hir::ClassExpression::AsyncMethod(ref n) => {
+                                self.check(n.method_id, &n.name);
+                            }
+                            hir::ClassExpression::InstanceMethod(ref n) => {
+                                self.check(n.method_id, &n.name);
+                            }
+                            hir::ClassExpression::StaticMethod(ref n) => {
+                                self.check(n.method_id, &n.name);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                hir::TopLevelExpression::Reopen(ref node) => {
+                    for expr in &node.body {
+                        match expr {
+                            hir::ReopenClassExpression::AsyncMethod(ref n) => {
+                                self.check(n.method_id, &n.name);
+                            }
+                            hir::ReopenClassExpression::InstanceMethod(
+                                ref n,
+                            ) => {
+                                self.check(n.method_id, &n.name);
+                            }
+                            hir::ReopenClassExpression::StaticMethod(ref n) => {
+                                self.check(n.method_id, &n.name);
+                            }
+                        }
+                    }
+                }
+                hir::TopLevelExpression::ModuleMethod(ref node) => {
+                    self.check(node.method_id, &node.name);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn check(&mut self, method: Option<MethodId>, name: &hir::Identifier) {
+        let Some(id) = method else { return };
+
+        if id.is_public(self.db())
+            || id.is_main(self.db())
+            || name.name.starts_with('_')
+            || self.state.method_is_used(id)
+            || matches!(id.source(self.db()), MethodSource::Implementation(..))
+        {
+            return;
+        }
+
+        self.state.diagnostics.unused_method(
+            &name.name,
+            self.module.file(self.db()),
+            name.location.clone(),
+        );
+    }
+
+    fn db(&self) -> &Database {
+        &self.state.db
+    }
+}
+
 /// A compiler pass that defines methods implemented from traits
 pub(crate) struct ImplementTraitMethods<'a> {
     state: &'a mut State,
@@ -223,9 +223,7 @@ impl<'a> DefineTypeSignature<'a> {
     ) -> TypeRef {
         let name = &node.name.name;
         let symbol = if let Some(source) = node.source.as_ref() {
-            if let Some(Symbol::Module(module)) =
-                self.scope.symbol(self.db(), &source.name)
-            {
+            if let Some(Symbol::Module(module)) = self.symbol(&source.name) {
                 module.symbol(self.db(), name)
             } else {
                 self.state.diagnostics.error(
@@ -238,7 +236,7 @@ impl<'a> DefineTypeSignature<'a> {
                 return TypeRef::Error;
             }
         } else {
-            self.scope.symbol(self.db(), name)
+            self.symbol(name)
         };
 
         node.resolved_type = if let Some(symbol) = symbol {
@@ -530,7 +528,7 @@ impl<'a> DefineTypeSignature<'a> {
                     _ => Some(arg),
                 }
             }
-            name => match self.scope.symbol(self.db(), name) {
+            name => match self.symbol(name) {
                 Some(Symbol::Class(id)) => Some(TypeRef::Owned(
                     TypeId::ClassInstance(ClassInstance::new(id)),
                 )),
@@ -565,6 +563,11 @@ impl<'a> DefineTypeSignature<'a> {
         }
     }
 
+    fn symbol(&mut self, name: &str) -> Option<Symbol> {
+        self.state.use_symbol(self.scope.module, name);
+        self.scope.symbol(self.db(), name)
+    }
+
     fn db(&self) -> &Database {
         &self.state.db
     }
@@ -1,6 +1,12 @@
 pub(crate) mod build;
 pub(crate) mod check;
+pub(crate) mod doc;
+pub(crate) mod explain;
+pub(crate) mod fmt;
+pub(crate) mod init;
+pub(crate) mod lsp;
 pub(crate) mod main;
+pub(crate) mod new;
 pub(crate) mod pkg;
 pub(crate) mod print;
 pub(crate) mod run;
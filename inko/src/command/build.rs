@@ -1,8 +1,9 @@
 use crate::error::Error;
-use crate::options::print_usage;
+use crate::options::{diagnostic_options, print_usage, set_diagnostic_levels};
+use crate::watch::{directories, watch};
 use compiler::compiler::{CompileError, Compiler};
 use compiler::config::{Config, Output};
-use getopts::Options;
+use getopts::{Matches, Options};
 use std::path::PathBuf;
 
 const USAGE: &str = "Usage: inko build [OPTIONS] [FILE]
@@ -11,8 +12,10 @@ Compile a source file and its dependencies into an executable.
 
 Examples:
 
-    inko build             # Compile src/main.inko
-    inko build hello.inko  # Compile the file hello.inko";
+    inko build                           # Compile src/main.inko
+    inko build hello.inko                # Compile the file hello.inko
+    inko build --watch                   # Compile whenever a file changes
+    inko build --target arm64-linux-gnu  # Compile for 64-bits ARM Linux";
 
 pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
     let mut options = Options::new();
@@ -21,7 +24,7 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
     options.optopt(
         "f",
         "format",
-        "The output format to use for diagnostics",
+        "The format of diagnostics (text, plain, json or sarif)",
         "FORMAT",
     );
 
@@ -46,6 +49,13 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         "PATH",
     );
 
+    options.optopt(
+        "j",
+        "jobs",
+        "The number of threads to use for generating code",
+        "NUM",
+    );
+
     options.optmulti(
         "",
         "opt",
@@ -54,9 +64,31 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
     );
 
     options.optflag("", "static", "Statically link imported C libraries");
+    options.optopt("", "linker", "The program to use for linking", "PATH");
+    options.optopt(
+        "",
+        "sysroot",
+        "The directory containing the target's headers and libraries",
+        "DIR",
+    );
+
+    options.optmulti(
+        "",
+        "linker-arg",
+        "An extra argument to pass to the linker",
+        "ARG",
+    );
+
     options.optflag("", "dot", "Output the MIR of every module as DOT files");
     options.optflag("", "verify-llvm", "Verify LLVM IR when generating code");
     options.optflag("", "write-llvm", "Write LLVM IR files to disk");
+    options.optflag(
+        "",
+        "watch",
+        "Compile the code again whenever a source file changes",
+    );
+
+    diagnostic_options(&mut options);
 
     let matches = options.parse(arguments)?;
 
@@ -65,12 +97,24 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         return Ok(0);
     }
 
+    if matches.opt_present("watch") {
+        watch(directories(&config(&matches)?), || build(&matches))
+    } else {
+        build(&matches)
+    }
+}
+
+fn config(matches: &Matches) -> Result<Config, Error> {
     let mut config = Config::default();
 
+    config.load_project_file()?;
+
     if let Some(val) = matches.opt_str("f") {
         config.set_presenter(&val)?;
     }
 
+    set_diagnostic_levels(matches, &mut config)?;
+
     if let Some(val) = matches.opt_str("t") {
         config.set_target(&val)?;
     }
@@ -79,6 +123,10 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         config.set_opt(&val)?;
     }
 
+    if let Some(val) = matches.opt_str("j") {
+        config.set_jobs(&val)?;
+    }
+
     if matches.opt_present("dot") {
         config.dot = true;
     }
@@ -95,6 +143,16 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         config.static_linking = true;
     }
 
+    if let Some(val) = matches.opt_str("linker") {
+        config.linker = Some(PathBuf::from(val));
+    }
+
+    if let Some(val) = matches.opt_str("sysroot") {
+        config.sysroot = Some(PathBuf::from(val));
+    }
+
+    config.linker_arguments.extend(matches.opt_strs("linker-arg"));
+
     for path in matches.opt_strs("i") {
         config.add_source_directory(path.into());
     }
@@ -103,7 +161,11 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         config.output = Output::Path(PathBuf::from(path));
     }
 
-    let mut compiler = Compiler::new(config);
+    Ok(config)
+}
+
+fn build(matches: &Matches) -> Result<i32, Error> {
+    let mut compiler = Compiler::new(config(matches)?);
     let file = matches.free.get(0).map(PathBuf::from);
     let result = compiler.build(file);
 
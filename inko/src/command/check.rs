@@ -1,8 +1,9 @@
 use crate::error::Error;
-use crate::options::print_usage;
+use crate::options::{diagnostic_options, print_usage, set_diagnostic_levels};
+use crate::watch::{directories, watch};
 use compiler::compiler::{CompileError, Compiler};
 use compiler::config::Config as CompilerConfig;
-use getopts::Options;
+use getopts::{Matches, Options};
 use std::path::PathBuf;
 
 const USAGE: &str = "Usage: inko check [OPTIONS] [FILE]
@@ -12,7 +13,10 @@ Check an entire project or a file for errors.
 Examples:
 
     inko check                   # Check all project files
-    inko check hello.inko        # Check the file hello.inko";
+    inko check hello.inko        # Check the file hello.inko
+    inko check --watch           # Check the project whenever a file changes
+    inko check -A unreachable    # Don't report unreachable code
+    inko check --deny-warnings   # Treat all warnings as errors";
 
 /// Type-checks Inko source code.
 pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
@@ -22,7 +26,7 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
     options.optopt(
         "f",
         "format",
-        "The output format to use for diagnostics",
+        "The format of diagnostics (text, plain, json or sarif)",
         "FORMAT",
     );
 
@@ -33,6 +37,14 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         "PATH",
     );
 
+    options.optflag(
+        "",
+        "watch",
+        "Check the code again whenever a source file changes",
+    );
+
+    diagnostic_options(&mut options);
+
     let matches = options.parse(arguments)?;
 
     if matches.opt_present("h") {
@@ -40,12 +52,24 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         return Ok(0);
     }
 
+    if matches.opt_present("watch") {
+        watch(directories(&config(&matches)?), || check(&matches))
+    } else {
+        check(&matches)
+    }
+}
+
+fn config(matches: &Matches) -> Result<CompilerConfig, Error> {
     let mut config = CompilerConfig::default();
 
+    config.load_project_file()?;
+
     if let Some(format) = matches.opt_str("f") {
         config.set_presenter(&format)?;
     }
 
+    set_diagnostic_levels(matches, &mut config)?;
+
     for path in matches.opt_strs("i") {
         config.add_source_directory(path.into());
     }
@@ -54,7 +78,11 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         config.add_source_directory(config.tests.clone());
     }
 
-    let mut compiler = Compiler::new(config);
+    Ok(config)
+}
+
+fn check(matches: &Matches) -> Result<i32, Error> {
+    let mut compiler = Compiler::new(config(matches)?);
     let file = matches.free.get(0).map(PathBuf::from);
     let result = compiler.check(file);
 
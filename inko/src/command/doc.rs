@@ -0,0 +1,70 @@
+use crate::error::Error;
+use crate::options::print_usage;
+use compiler::compiler::{CompileError, Compiler};
+use compiler::config::Config as CompilerConfig;
+use getopts::Options;
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: inko doc [OPTIONS]
+
+Generate documentation for the public types, methods and constants of a
+project, using the comments that precede their definitions.
+
+The documentation is written as a set of HTML pages, along with a docs.json
+file containing the same information in JSON format. Unless specified
+otherwise, the output is written to ./build/docs.
+
+Examples:
+
+    inko doc                 # Generate documentation for the project
+    inko doc --output docs   # Write the documentation to ./docs";
+
+/// Generates documentation from source code comments.
+pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
+    let mut options = Options::new();
+
+    options.optflag("h", "help", "Show this help message");
+    options.optopt(
+        "o",
+        "output",
+        "The directory to write the documentation to",
+        "DIR",
+    );
+
+    options.optmulti(
+        "i",
+        "include",
+        "A directory to add to the list of source directories",
+        "PATH",
+    );
+
+    let matches = options.parse(arguments)?;
+
+    if matches.opt_present("h") {
+        print_usage(&options, USAGE);
+        return Ok(0);
+    }
+
+    let mut config = CompilerConfig::default();
+
+    config.load_project_file()?;
+
+    for path in matches.opt_strs("i") {
+        config.add_source_directory(path.into());
+    }
+
+    let output = matches
+        .opt_str("o")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| config.build.join("docs"));
+    let mut compiler = Compiler::new(config);
+    let result = compiler.document(&output);
+
+    compiler.print_diagnostics();
+
+    match result {
+        Ok(_) => Ok(0),
+        Err(CompileError::Invalid) => Ok(1),
+        Err(CompileError::Internal(msg)) => Err(Error::generic(msg)),
+    }
+}
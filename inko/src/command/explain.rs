@@ -0,0 +1,53 @@
+use crate::error::Error;
+use crate::options::print_usage;
+use compiler::diagnostics::{diagnostic_ids, explain};
+use getopts::Options;
+
+const USAGE: &str = "Usage: inko explain [OPTIONS] ID
+
+Print a detailed explanation of a diagnostic.
+
+The ID is the name displayed between the parentheses of a diagnostic, such as
+'moved' for 'error(moved)'.
+
+Examples:
+
+    inko explain moved  # Explain the 'moved' diagnostic
+    inko explain --list # List all diagnostic IDs";
+
+/// Prints the explanation of a diagnostic.
+pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
+    let mut options = Options::new();
+
+    options.optflag("h", "help", "Show this help message");
+    options.optflag("l", "list", "List all diagnostic IDs");
+
+    let matches = options.parse(arguments)?;
+
+    if matches.opt_present("h") {
+        print_usage(&options, USAGE);
+        return Ok(0);
+    }
+
+    if matches.opt_present("l") {
+        for id in diagnostic_ids() {
+            println!("{}", id);
+        }
+
+        return Ok(0);
+    }
+
+    let id = matches.free.first().ok_or_else(|| {
+        Error::generic("You must specify a diagnostic ID".to_string())
+    })?;
+    let text = explain(id).ok_or_else(|| {
+        Error::generic(format!(
+            "The diagnostic ID '{}' is invalid, run `inko explain --list` to \
+            list all valid IDs",
+            id
+        ))
+    })?;
+
+    print!("{}", text);
+    Ok(0)
+}
@@ -0,0 +1,126 @@
+use crate::error::Error;
+use crate::options::print_usage;
+use compiler::config::{Config, SOURCE_EXT};
+use compiler::format::format;
+use getopts::Options;
+use std::ffi::OsStr;
+use std::fs::{read, write};
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: inko fmt [OPTIONS] [FILES]
+
+Format Inko source code.
+
+If no files are given, all the source and test files of the project are
+formatted. Directories are searched recursively for Inko source files.
+
+Examples:
+
+    inko fmt                # Format all project files
+    inko fmt hello.inko     # Format the file hello.inko
+    inko fmt --check        # Check if all project files are formatted";
+
+/// Formats Inko source code.
+pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
+    let mut options = Options::new();
+
+    options.optflag("h", "help", "Show this help message");
+    options.optflag(
+        "c",
+        "check",
+        "Check if the files are formatted, without changing them",
+    );
+
+    let matches = options.parse(arguments)?;
+
+    if matches.opt_present("h") {
+        print_usage(&options, USAGE);
+        return Ok(0);
+    }
+
+    let check = matches.opt_present("c");
+    let mut paths: Vec<PathBuf> =
+        matches.free.iter().map(PathBuf::from).collect();
+
+    if paths.is_empty() {
+        let config = Config::default();
+
+        paths.push(config.source);
+        paths.push(config.tests);
+        paths.retain(|p| p.is_dir());
+    }
+
+    let mut status = 0;
+
+    for path in source_files(paths)? {
+        let input = read(&path).map_err(|err| {
+            Error::generic(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+
+        let output = match format(&path, &input) {
+            Ok(output) => output,
+            Err(err) => {
+                eprintln!("{}", err);
+                status = 1;
+                continue;
+            }
+        };
+
+        if output.as_bytes() == input {
+            continue;
+        }
+
+        if check {
+            println!("{}", path.display());
+            status = 1;
+            continue;
+        }
+
+        write(&path, output).map_err(|err| {
+            Error::generic(format!(
+                "Failed to write {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+    }
+
+    Ok(status)
+}
+
+/// Returns the paths of the source files to format, searching directories
+/// recursively.
+fn source_files(mut paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, Error> {
+    let ext = OsStr::new(SOURCE_EXT);
+    let mut files = Vec::new();
+
+    while let Some(path) = paths.pop() {
+        if !path.is_dir() {
+            files.push(path);
+            continue;
+        }
+
+        let entries = path.read_dir().map_err(|err| {
+            Error::generic(format!(
+                "Failed to read directory {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+
+        for entry in entries {
+            let path = entry?.path();
+
+            if path.is_dir() || path.extension() == Some(ext) {
+                paths.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
@@ -0,0 +1,46 @@
+use crate::command::new::create;
+use crate::error::Error;
+use crate::options::print_usage;
+use getopts::Options;
+use std::env::current_dir;
+
+const USAGE: &str = "Usage: inko init [OPTIONS]
+
+Create a new project in the current working directory.
+
+The name of the project is the name of the current working directory. Files
+that already exist are left as-is.
+
+Examples:
+
+    inko init        # Create an executable project
+    inko init --lib  # Create a library project";
+
+/// Creates a new project in the current working directory.
+pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
+    let mut options = Options::new();
+
+    options.optflag("h", "help", "Show this help message");
+    options.optflag("", "lib", "Create a library instead of an executable");
+
+    let matches = options.parse(arguments)?;
+
+    if matches.opt_present("h") {
+        print_usage(&options, USAGE);
+        return Ok(0);
+    }
+
+    let directory = current_dir()?;
+    let name = directory
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    for path in create(&directory, &name, matches.opt_present("lib"))? {
+        if let Ok(path) = path.strip_prefix(&directory) {
+            println!("Created {}", path.display());
+        }
+    }
+
+    Ok(0)
+}
@@ -0,0 +1,45 @@
+use crate::error::Error;
+use crate::options::print_usage;
+use compiler::lsp::Server;
+use getopts::Options;
+
+const USAGE: &str = "Usage: inko lsp [OPTIONS]
+
+Start a language server that communicates using STDIN and STDOUT.
+
+The server implements the Language Server Protocol, and provides diagnostics,
+hover information, go-to-definition and completion to text editors. It's not
+meant to be run directly, instead your text editor starts it when needed.
+
+Examples:
+
+    inko lsp               # Start the language server
+    inko lsp --include foo # Also search the directory foo for modules";
+
+/// Starts a language server.
+pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
+    let mut options = Options::new();
+
+    options.optflag("h", "help", "Show this help message");
+    options.optmulti(
+        "i",
+        "include",
+        "A directory to add to the list of source directories",
+        "PATH",
+    );
+
+    let matches = options.parse(arguments)?;
+
+    if matches.opt_present("h") {
+        print_usage(&options, USAGE);
+        return Ok(0);
+    }
+
+    let mut server = Server::default();
+
+    for path in matches.opt_strs("i") {
+        server.add_source_directory(path.into());
+    }
+
+    Ok(server.run()?)
+}
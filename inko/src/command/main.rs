@@ -1,5 +1,11 @@
 use crate::command::build;
 use crate::command::check;
+use crate::command::doc;
+use crate::command::explain;
+use crate::command::fmt;
+use crate::command::init;
+use crate::command::lsp;
+use crate::command::new;
 use crate::command::pkg;
 use crate::command::print;
 use crate::command::run;
@@ -13,11 +19,17 @@ const USAGE: &str = "Usage: inko [OPTIONS] [COMMAND | FILE]
 
 Commands:
 
-    run    Compile and run Inko source code directly
-    build  Compile Inko source code
-    test   Run Inko unit tests
-    print  Print compiler details to STDOUT
-    pkg    Manage Inko packages
+    new      Create a new project
+    init     Create a new project in the current directory
+    run      Compile and run Inko source code directly
+    build    Compile Inko source code
+    test     Run Inko unit tests
+    fmt      Format Inko source code
+    doc      Generate documentation from source code comments
+    explain  Print the explanation of a diagnostic
+    lsp      Start a language server for text editors
+    print    Print compiler details to STDOUT
+    pkg      Manage Inko packages
 
 Examples:
 
@@ -47,10 +59,16 @@ pub(crate) fn run() -> Result<i32, Error> {
     }
 
     match matches.free.get(0).map(|s| s.as_str()) {
+        Some("new") => new::run(&matches.free[1..]),
+        Some("init") => init::run(&matches.free[1..]),
         Some("run") => run::run(&matches.free[1..]),
         Some("build") => build::run(&matches.free[1..]),
         Some("check") => check::run(&matches.free[1..]),
         Some("test") => test::run(&matches.free[1..]),
+        Some("fmt") => fmt::run(&matches.free[1..]),
+        Some("doc") => doc::run(&matches.free[1..]),
+        Some("explain") => explain::run(&matches.free[1..]),
+        Some("lsp") => lsp::run(&matches.free[1..]),
         Some("print") => print::run(&matches.free[1..]),
         Some("pkg") => pkg::run(&matches.free[1..]),
         Some(cmd) => {
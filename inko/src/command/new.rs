@@ -0,0 +1,238 @@
+use crate::error::Error;
+use crate::options::print_usage;
+use compiler::config::{BUILD, DEP, MAIN_MODULE, SOURCE, SOURCE_EXT, TESTS};
+use compiler::pkg::manifest::MANIFEST_FILE;
+use getopts::Options;
+use std::fs::{create_dir_all, write};
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "Usage: inko new [OPTIONS] NAME
+
+Create a new project in the directory NAME.
+
+The project contains a main module, an example unit test, an empty package
+manifest and a .gitignore file. When using --lib, a module named after the
+project is created instead of a main module.
+
+Examples:
+
+    inko new hello        # Create an executable project in ./hello
+    inko new hello --lib  # Create a library project in ./hello";
+
+const MAIN: &str = "\
+import std.stdio.STDOUT
+
+class async Main {
+  fn async main {
+    STDOUT.new.print('Hello, world!')
+  }
+}
+";
+
+const LIBRARY: &str = "\
+# The NAME library.
+
+# Returns a greeting for the given name.
+fn pub greet(name: String) -> String {
+  \"Hello, {name}!\"
+}
+";
+
+const TEST: &str = "\
+import std.test.Tests
+
+fn pub tests(t: mut Tests) {
+  t.test('Adding two numbers') fn (t) {
+    t.equal(1 + 2, 3)
+  }
+}
+";
+
+const LIBRARY_TEST: &str = "\
+import NAME.(greet)
+import std.test.Tests
+
+fn pub tests(t: mut Tests) {
+  t.test('NAME.greet') fn (t) {
+    t.equal(greet('Alice'), 'Hello, Alice!')
+  }
+}
+";
+
+/// Returns the name of the module to use for a project name.
+///
+/// Project names are allowed to contain dashes (e.g. `hello-world`), but these
+/// can't be used in module names.
+fn module_name(project: &str) -> Result<String, Error> {
+    let name = project.replace('-', "_");
+    let valid = name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+    if valid {
+        Ok(name)
+    } else {
+        Err(Error::generic(format!(
+            "The project name '{}' is invalid, as names must start with a \
+            lowercase letter and only contain lowercase letters, digits, \
+            underscores and dashes",
+            project
+        )))
+    }
+}
+
+fn source_file(directory: &Path, name: &str) -> PathBuf {
+    let mut path = directory.join(name);
+
+    path.set_extension(SOURCE_EXT);
+    path
+}
+
+/// Creates the files of a new project in the given directory.
+///
+/// Files that already exist are left as-is. The paths of the files that are
+/// created are returned.
+pub(crate) fn create(
+    directory: &Path,
+    name: &str,
+    library: bool,
+) -> Result<Vec<PathBuf>, Error> {
+    let module = module_name(name)?;
+    let src = directory.join(SOURCE);
+    let test = directory.join(TESTS);
+    let mut files = vec![
+        (directory.join(MANIFEST_FILE), String::new()),
+        (directory.join(".gitignore"), format!("/{}\n/{}\n", BUILD, DEP)),
+    ];
+
+    if library {
+        files.push((source_file(&src, &module), LIBRARY.replace("NAME", name)));
+        files.push((
+            source_file(&test, &format!("test_{}", module)),
+            LIBRARY_TEST.replace("NAME", &module),
+        ));
+    } else {
+        files.push((source_file(&src, MAIN_MODULE), MAIN.to_string()));
+        files.push((source_file(&test, "test_example"), TEST.to_string()));
+    }
+
+    let mut created = Vec::new();
+
+    for (path, content) in files {
+        if path.exists() {
+            continue;
+        }
+
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir).map_err(|err| {
+                Error::generic(format!(
+                    "Failed to create {}: {}",
+                    dir.display(),
+                    err
+                ))
+            })?;
+        }
+
+        write(&path, content).map_err(|err| {
+            Error::generic(format!(
+                "Failed to write {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+
+        created.push(path);
+    }
+
+    Ok(created)
+}
+
+/// Creates a new project in a new directory.
+pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
+    let mut options = Options::new();
+
+    options.optflag("h", "help", "Show this help message");
+    options.optflag("", "lib", "Create a library instead of an executable");
+
+    let matches = options.parse(arguments)?;
+
+    if matches.opt_present("h") {
+        print_usage(&options, USAGE);
+        return Ok(0);
+    }
+
+    let name = matches.free.first().ok_or_else(|| {
+        Error::generic("You must specify the name of the project".to_string())
+    })?;
+    let directory = PathBuf::from(name);
+
+    if directory.exists() {
+        return Err(Error::generic(format!(
+            "The directory {} already exists",
+            directory.display()
+        )));
+    }
+
+    // The project name is the last component, such that `inko new foo/bar`
+    // creates the project "bar" in the directory "foo/bar".
+    let name = directory
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    create(&directory, &name, matches.opt_present("lib"))?;
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+    use std::fs::{read_to_string, remove_dir_all};
+
+    #[test]
+    fn test_module_name() {
+        assert_eq!(module_name("hello").ok(), Some("hello".to_string()));
+        assert_eq!(
+            module_name("hello-world2").ok(),
+            Some("hello_world2".to_string())
+        );
+        assert!(module_name("Hello").is_err());
+        assert!(module_name("2hello").is_err());
+        assert!(module_name("hello.world").is_err());
+        assert!(module_name("").is_err());
+    }
+
+    #[test]
+    fn test_create_library() {
+        let dir = temp_dir().join("inko-new-test");
+
+        if dir.exists() {
+            remove_dir_all(&dir).unwrap();
+        }
+
+        let created = create(&dir, "hello-world", true).unwrap_or_default();
+
+        assert_eq!(created.len(), 4);
+        assert!(dir.join("inko.pkg").is_file());
+        assert!(dir.join("src").join("hello_world.inko").is_file());
+        assert_eq!(
+            read_to_string(dir.join(".gitignore")).unwrap(),
+            "/build\n/dep\n"
+        );
+        assert!(read_to_string(dir.join("test").join("test_hello_world.inko"))
+            .unwrap()
+            .starts_with("import hello_world.(greet)\n"));
+
+        // Existing files are left as-is.
+        write(dir.join(".gitignore"), "foo").unwrap();
+
+        let created = create(&dir, "hello-world", false).unwrap_or_default();
+
+        assert_eq!(created.len(), 2);
+        assert_eq!(read_to_string(dir.join(".gitignore")).unwrap(), "foo");
+
+        remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,5 +1,5 @@
 use crate::error::Error;
-use crate::options::print_usage;
+use crate::options::{diagnostic_options, print_usage, set_diagnostic_levels};
 use compiler::compiler::{CompileError, Compiler};
 use compiler::config::Config;
 use getopts::{Options, ParsingStyle};
@@ -33,7 +33,7 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
     options.optopt(
         "f",
         "format",
-        "The output format to use for diagnostics",
+        "The format of diagnostics (text, plain, json or sarif)",
         "FORMAT",
     );
 
@@ -44,8 +44,17 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         "PATH",
     );
 
+    options.optopt(
+        "j",
+        "jobs",
+        "The number of threads to use for generating code",
+        "NUM",
+    );
+
     options.optflag("", "static", "Statically link imported C libraries");
 
+    diagnostic_options(&mut options);
+
     let matches = options.parse(arguments)?;
 
     if matches.opt_present("h") {
@@ -57,14 +66,22 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
     let arguments =
         if matches.free.len() > 1 { &matches.free[1..] } else { &[] };
 
+    config.load_project_file()?;
+
     if let Some(format) = matches.opt_str("f") {
         config.set_presenter(&format)?;
     }
 
+    set_diagnostic_levels(&matches, &mut config)?;
+
     for path in matches.opt_strs("i") {
         config.add_source_directory(path.into());
     }
 
+    if let Some(val) = matches.opt_str("j") {
+        config.set_jobs(&val)?;
+    }
+
     if matches.opt_present("static") {
         config.static_linking = true;
     }
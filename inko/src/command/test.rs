@@ -1,29 +1,138 @@
 use crate::error::Error;
-use crate::options::print_usage;
+use crate::options::{diagnostic_options, print_usage, set_diagnostic_levels};
+use crate::watch::{directories, watch};
 use compiler::compiler::{CompileError, Compiler};
 use compiler::config::{Config, Output, SOURCE_EXT};
-use getopts::Options;
-use std::fs::{read_dir, write};
+use compiler::coverage::{Report as CoverageReport, DIRECTORY_VARIABLE};
+use getopts::{Matches, Options};
+use std::env::current_dir;
+use std::fs::{create_dir_all, read_dir, remove_dir_all, write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use types::module_name::ModuleName;
 
-const USAGE: &str = "Usage: inko test [OPTIONS]
+const USAGE: &str = "Usage: inko test [OPTIONS] [FILE...]
 
 Compiles and runs unit tests
 
 This command compiles your unit tests in ./test, then runs the resulting test
-executable.
+executable. If one or more test files or directories are given, only the tests
+in those files are compiled and run.
 
 Examples:
 
-    inko test    # Runs all unit tests in ./test";
+    inko test                    # Runs all unit tests in ./test
+    inko test test/test_foo.inko # Runs the unit tests in test/test_foo.inko
+    inko test --filter kittens   # Runs the tests of which the name contains 'kittens'
+    inko test --list             # Lists the names of all unit tests
+    inko test --coverage         # Writes a coverage report to ./build/coverage
+    inko test --watch            # Runs the tests whenever a source file changes
+    inko test --format junit     # Writes a JUnit XML report to STDOUT
+    inko test --format json --output report.json";
+
+/// The format to report test results in.
+#[derive(Copy, Clone)]
+enum Format {
+    Plain,
+    Junit,
+    Json,
+}
+
+impl Format {
+    fn parse(name: &str) -> Result<Format, Error> {
+        match name {
+            "plain" => Ok(Format::Plain),
+            "junit" => Ok(Format::Junit),
+            "json" => Ok(Format::Json),
+            _ => Err(Error::generic(format!(
+                "The test report format '{}' is invalid",
+                name
+            ))),
+        }
+    }
+}
+
+/// The settings for reporting test results.
+struct Report {
+    format: Format,
+
+    /// The file to write the results to, instead of STDOUT.
+    output: Option<PathBuf>,
+
+    /// The directory containing the tests, used for deriving module names.
+    directory: PathBuf,
+}
+
+/// The settings for selecting which tests to run.
+struct Selection {
+    /// The pattern test names must match.
+    filter: Option<String>,
+
+    /// If the names of tests must be equal to the filter.
+    exact: bool,
+
+    /// If the names of the tests are to be listed instead of running them.
+    list: bool,
+}
 
 /// Compiles and runs Inko unit tests.
 pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
     let mut options = Options::new();
 
     options.optflag("h", "help", "Show this help message");
+    options.optopt(
+        "j",
+        "jobs",
+        "The number of threads to use for generating code",
+        "NUM",
+    );
+
+    options.optflag(
+        "",
+        "watch",
+        "Run the tests again whenever a source file changes",
+    );
+
+    options.optopt(
+        "",
+        "filter",
+        "Only run tests of which the name contains PATTERN",
+        "PATTERN",
+    );
+
+    options.optflag(
+        "",
+        "exact",
+        "Only run tests of which the name is equal to the filter",
+    );
+
+    options.optflag(
+        "",
+        "list",
+        "List the names of the tests instead of running them",
+    );
+
+    options.optflag(
+        "",
+        "coverage",
+        "Write a code coverage report to ./build/coverage/lcov.info",
+    );
+
+    options.optopt(
+        "",
+        "format",
+        "The format of the results (plain, junit or json)",
+        "FORMAT",
+    );
+
+    options.optopt(
+        "o",
+        "output",
+        "The file to write the results to instead of STDOUT",
+        "FILE",
+    );
+
+    diagnostic_options(&mut options);
 
     let matches = options.parse(arguments)?;
 
@@ -32,9 +141,46 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         return Ok(0);
     }
 
+    if matches.opt_present("watch") {
+        watch(directories(&config(&matches)?), || test(&matches))
+    } else {
+        test(&matches)
+    }
+}
+
+fn config(matches: &Matches) -> Result<Config, Error> {
     let mut config = Config::default();
+
+    config.load_project_file()?;
+
+    if let Some(val) = matches.opt_str("j") {
+        config.set_jobs(&val)?;
+    }
+
+    set_diagnostic_levels(matches, &mut config)?;
+    Ok(config)
+}
+
+fn test(matches: &Matches) -> Result<i32, Error> {
+    let mut config = config(matches)?;
     let input = config.main_test_module();
 
+    let format = match matches.opt_str("format") {
+        Some(val) => Format::parse(&val)?,
+        None => Format::Plain,
+    };
+    let selection = Selection {
+        filter: matches.opt_str("filter"),
+        exact: matches.opt_present("exact"),
+        list: matches.opt_present("list"),
+    };
+
+    if selection.exact && selection.filter.is_none() {
+        return Err(Error::generic(
+            "The --exact option requires the --filter option".to_string(),
+        ));
+    }
+
     if !config.tests.is_dir() {
         return Err(Error::generic(format!(
             "The tests directory {:?} doesn't exist",
@@ -44,10 +190,29 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
 
     config.add_source_directory(config.tests.clone());
     config.output = Output::File("inko-tests".to_string());
-
-    let tests = test_module_names(&config.tests).map_err(|err| {
-        Error::generic(format!("Failed to find test modules: {}", err))
-    })?;
+    config.coverage = matches.opt_present("coverage");
+
+    let coverage_enabled = config.coverage;
+    let build = config.build.clone();
+
+    let tests = if matches.free.is_empty() {
+        test_module_names(&config.tests).map_err(|err| {
+            Error::generic(format!("Failed to find test modules: {}", err))
+        })?
+    } else {
+        selected_module_names(&config.tests, &matches.free)?
+    };
+    let report = Report {
+        format,
+        output: matches.opt_str("o").map(|path| {
+            let path = PathBuf::from(path);
+
+            // The path is relative to the working directory, not the directory
+            // the test executable is located in.
+            current_dir().map_or(path.clone(), |dir| dir.join(path))
+        }),
+        directory: config.tests.clone(),
+    };
 
     let mut compiler = Compiler::new(config);
 
@@ -55,26 +220,93 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
     // the generated file in it (if it doesn't already exist that is).
     compiler.create_build_directory()?;
 
-    write(&input, generate_main_test_module(tests)).map_err(|err| {
-        Error::generic(format!("Failed to write {}: {}", input.display(), err))
-    })?;
+    write(&input, generate_main_test_module(tests, &report, &selection))
+        .map_err(|err| {
+            Error::generic(format!(
+                "Failed to write {}: {}",
+                input.display(),
+                err
+            ))
+        })?;
 
     let result = compiler.build(Some(input));
 
     compiler.print_diagnostics();
 
-    match result {
-        Ok(exe) => Command::new(exe)
-            .args(matches.free)
-            .spawn()
-            .and_then(|mut child| child.wait())
-            .map_err(|err| {
-                Error::generic(format!("Failed to run the tests: {}", err))
-            })
-            .map(|status| status.code().unwrap_or(0)),
-        Err(CompileError::Invalid) => Ok(1),
-        Err(CompileError::Internal(msg)) => Err(Error::generic(msg)),
+    let exe = match result {
+        Ok(exe) => exe,
+        Err(CompileError::Invalid) => return Ok(1),
+        Err(CompileError::Internal(msg)) => return Err(Error::generic(msg)),
+    };
+    let mut cmd = Command::new(&exe);
+    let coverage = build.join("coverage");
+    let counters = coverage.join("counters");
+
+    if coverage_enabled {
+        if counters.is_dir() {
+            remove_dir_all(&counters).map_err(|err| {
+                Error::generic(format!(
+                    "Failed to remove {}: {}",
+                    counters.display(),
+                    err
+                ))
+            })?;
+        }
+
+        create_dir_all(&counters).map_err(|err| {
+            Error::generic(format!(
+                "Failed to create {}: {}",
+                counters.display(),
+                err
+            ))
+        })?;
+
+        cmd.env(DIRECTORY_VARIABLE, &counters);
+    }
+
+    let status = cmd
+        .spawn()
+        .and_then(|mut child| child.wait())
+        .map_err(|err| {
+            Error::generic(format!("Failed to run the tests: {}", err))
+        })?
+        .code()
+        .unwrap_or(0);
+
+    if coverage_enabled && !selection.list {
+        write_coverage_report(&exe, &counters, &coverage.join("lcov.info"))?;
     }
+
+    Ok(status)
+}
+
+/// Combines the coverage counters of a test run into an lcov report.
+fn write_coverage_report(
+    executable: &Path,
+    counters: &Path,
+    output: &Path,
+) -> Result<(), Error> {
+    let report =
+        CoverageReport::new(executable, counters).map_err(Error::generic)?;
+    let (hit, found) = report.totals();
+    let percentage =
+        if found > 0 { hit as f64 / found as f64 * 100.0 } else { 100.0 };
+
+    write(output, report.to_lcov()).map_err(|err| {
+        Error::generic(format!("Failed to write {}: {}", output.display(), err))
+    })?;
+
+    // The report is written to STDERR so it doesn't interfere with test reports
+    // written to STDOUT.
+    eprintln!(
+        "Line coverage: {:.2}% ({} of {} lines), written to {}",
+        percentage,
+        hit,
+        found,
+        output.display()
+    );
+
+    Ok(())
 }
 
 fn is_test_file(path: &Path) -> bool {
@@ -116,17 +348,86 @@ fn test_files(test_dir: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
 fn test_module_names(
     test_dir: &Path,
 ) -> Result<Vec<ModuleName>, std::io::Error> {
-    let test_modules = test_files(test_dir)?
+    let mut test_modules = test_files(test_dir)?
         .into_iter()
         .map(|file| {
             ModuleName::from_relative_path(file.strip_prefix(test_dir).unwrap())
         })
         .collect::<Vec<_>>();
 
+    test_modules.sort();
     Ok(test_modules)
 }
 
-fn generate_main_test_module(tests: Vec<ModuleName>) -> String {
+/// Returns the names of the test modules in the given files and directories.
+///
+/// Relative paths are resolved relative to the working directory.
+fn selected_module_names(
+    test_dir: &Path,
+    paths: &[String],
+) -> Result<Vec<ModuleName>, Error> {
+    let test_dir = test_dir.canonicalize().map_err(|err| {
+        Error::generic(format!(
+            "Failed to resolve the tests directory {:?}: {}",
+            test_dir, err
+        ))
+    })?;
+    let mut files = Vec::new();
+
+    for arg in paths {
+        let path = Path::new(arg).canonicalize().map_err(|_| {
+            Error::generic(format!(
+                "The test file or directory '{}' doesn't exist",
+                arg
+            ))
+        })?;
+
+        if !path.starts_with(&test_dir) {
+            return Err(Error::generic(format!(
+                "'{}' isn't located in the tests directory {:?}",
+                arg, test_dir
+            )));
+        }
+
+        if path.is_dir() {
+            files.append(&mut test_files(&path).map_err(|err| {
+                Error::generic(format!("Failed to find test modules: {}", err))
+            })?);
+        } else if is_test_file(&path) {
+            files.push(path);
+        } else {
+            return Err(Error::generic(format!(
+                "'{}' isn't a test file, as test files must be named \
+                'test_X.{}'",
+                arg, SOURCE_EXT
+            )));
+        }
+    }
+
+    let mut names = files
+        .into_iter()
+        .map(|file| {
+            ModuleName::from_relative_path(
+                file.strip_prefix(&test_dir).unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// Returns a single quoted Inko string literal containing the given value.
+fn string_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn generate_main_test_module(
+    tests: Vec<ModuleName>,
+    report: &Report,
+    selection: &Selection,
+) -> String {
     let mut imports = Vec::with_capacity(tests.len());
     let mut calls = Vec::with_capacity(tests.len());
 
@@ -135,8 +436,60 @@ fn generate_main_test_module(tests: Vec<ModuleName>) -> String {
         calls.push(format!("    tests{}.tests(tests)\n", idx));
     }
 
-    let mut source =
-        "import std.env\nimport std.test.(Filter, Tests)\n".to_string();
+    let mut source = String::new();
+    let mut symbols = vec!["Tests"];
+    let filter = selection.filter.as_ref().map(|pattern| {
+        let variant = if selection.exact { "Exact" } else { "Pattern" };
+
+        symbols.push("Filter");
+        format!("Filter.{}({})", variant, string_literal(pattern))
+    });
+    let reporter = match (report.format, &report.output) {
+        (Format::Plain, None) => None,
+        (format, output) => {
+            let out = if let Some(path) = output {
+                source.push_str("import std.fs.file.WriteOnlyFile\n");
+                format!(
+                    "WriteOnlyFile.new({}).expect('failed to open the output file')",
+                    string_literal(&path.to_string_lossy())
+                )
+            } else {
+                source.push_str("import std.stdio.STDOUT\n");
+                "STDOUT.new".to_string()
+            };
+
+            let dir = string_literal(&report.directory.to_string_lossy());
+            let reporter = match format {
+                Format::Plain => {
+                    symbols.push("Plain");
+                    format!("Plain.new(out: {}, colors: false)", out)
+                }
+                Format::Junit => {
+                    symbols.push("JUnit");
+                    format!(
+                        "JUnit.new(out: {}, directory: Path.new({}))",
+                        out, dir
+                    )
+                }
+                Format::Json => {
+                    symbols.push("Json");
+                    format!(
+                        "Json.new(out: {}, directory: Path.new({}))",
+                        out, dir
+                    )
+                }
+            };
+
+            if !matches!(format, Format::Plain) {
+                source.push_str("import std.fs.path.Path\n");
+            }
+
+            symbols.push("Reporter");
+            Some(reporter)
+        }
+    };
+
+    source.push_str(&format!("import std.test.({})\n", symbols.join(", ")));
 
     for line in imports {
         source.push_str(&line);
@@ -150,9 +503,185 @@ fn generate_main_test_module(tests: Vec<ModuleName>) -> String {
         source.push_str(&line);
     }
 
-    source.push_str("    tests.filter = Filter.from_string(env.arguments.opt(0).unwrap_or(''))\n");
-    source.push_str("    tests.run\n");
+    if let Some(reporter) = reporter {
+        source.push_str(&format!(
+            "    tests.reporter = {} as Reporter\n",
+            reporter
+        ));
+    }
+
+    if let Some(filter) = filter {
+        source.push_str(&format!("    tests.filter = {}\n", filter));
+    }
+
+    if selection.list {
+        source.push_str("    tests.list\n");
+    } else {
+        source.push_str("    tests.run\n");
+    }
+
     source.push_str("  }\n");
     source.push_str("}\n");
     source
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all};
+
+    fn report(format: Format, output: Option<&str>) -> Report {
+        Report {
+            format,
+            output: output.map(PathBuf::from),
+            directory: PathBuf::from("/foo/test"),
+        }
+    }
+
+    fn selection(filter: Option<&str>, exact: bool, list: bool) -> Selection {
+        Selection { filter: filter.map(|v| v.to_string()), exact, list }
+    }
+
+    #[test]
+    fn test_format_parse() {
+        assert!(matches!(Format::parse("plain"), Ok(Format::Plain)));
+        assert!(matches!(Format::parse("junit"), Ok(Format::Junit)));
+        assert!(matches!(Format::parse("json"), Ok(Format::Json)));
+        assert!(Format::parse("xml").is_err());
+    }
+
+    #[test]
+    fn test_string_literal() {
+        assert_eq!(string_literal("/foo"), "'/foo'");
+        assert_eq!(string_literal("/foo's"), "'/foo\\'s'");
+        assert_eq!(string_literal("a\\b"), "'a\\\\b'");
+    }
+
+    #[test]
+    fn test_generate_main_test_module_with_plain() {
+        let source = generate_main_test_module(
+            vec![ModuleName::new("test_foo")],
+            &report(Format::Plain, None),
+            &selection(None, false, false),
+        );
+
+        assert!(source.starts_with("import std.test.(Tests)\n"));
+        assert!(source.contains("import test_foo.(self as tests0)\n"));
+        assert!(!source.contains("tests.reporter"));
+        assert!(!source.contains("tests.filter"));
+        assert!(source.contains("    tests.run\n"));
+    }
+
+    #[test]
+    fn test_generate_main_test_module_with_junit() {
+        let source = generate_main_test_module(
+            Vec::new(),
+            &report(Format::Junit, None),
+            &selection(None, false, false),
+        );
+
+        assert!(source.contains("import std.stdio.STDOUT\n"));
+        assert!(source.contains("import std.fs.path.Path\n"));
+        assert!(source.contains("import std.test.(Tests, JUnit, Reporter)\n"));
+        assert!(source.contains(
+            "tests.reporter = JUnit.new(out: STDOUT.new, \
+            directory: Path.new('/foo/test')) as Reporter\n"
+        ));
+    }
+
+    #[test]
+    fn test_generate_main_test_module_with_output() {
+        let source = generate_main_test_module(
+            Vec::new(),
+            &report(Format::Plain, Some("/tmp/out.txt")),
+            &selection(None, false, false),
+        );
+
+        assert!(source.contains("import std.fs.file.WriteOnlyFile\n"));
+        assert!(!source.contains("import std.fs.path.Path\n"));
+        assert!(source.contains(
+            "tests.reporter = Plain.new(out: WriteOnlyFile.new('/tmp/out.txt')\
+            .expect('failed to open the output file'), colors: false) \
+            as Reporter\n"
+        ));
+    }
+
+    #[test]
+    fn test_generate_main_test_module_with_filter() {
+        let pattern = generate_main_test_module(
+            Vec::new(),
+            &report(Format::Plain, None),
+            &selection(Some("foo's"), false, false),
+        );
+        let exact = generate_main_test_module(
+            Vec::new(),
+            &report(Format::Plain, None),
+            &selection(Some("foo"), true, false),
+        );
+
+        assert!(pattern.starts_with("import std.test.(Tests, Filter)\n"));
+        assert!(
+            pattern.contains("    tests.filter = Filter.Pattern('foo\\'s')\n")
+        );
+        assert!(exact.contains("    tests.filter = Filter.Exact('foo')\n"));
+    }
+
+    #[test]
+    fn test_generate_main_test_module_with_list() {
+        let source = generate_main_test_module(
+            Vec::new(),
+            &report(Format::Plain, None),
+            &selection(None, false, true),
+        );
+
+        assert!(source.contains("    tests.list\n"));
+        assert!(!source.contains("tests.run"));
+    }
+
+    #[test]
+    fn test_selected_module_names() {
+        let dir = temp_dir().join("inko-test-selected-modules");
+
+        if dir.exists() {
+            remove_dir_all(&dir).unwrap();
+        }
+
+        create_dir_all(dir.join("test").join("std")).unwrap();
+        write(dir.join("test").join("std").join("test_a.inko"), "").unwrap();
+        write(dir.join("test").join("std").join("test_b.inko"), "").unwrap();
+        write(dir.join("test").join("test_c.inko"), "").unwrap();
+        write(dir.join("test").join("helper.inko"), "").unwrap();
+        write(dir.join("test_d.inko"), "").unwrap();
+
+        let tests = dir.join("test");
+        let path = |name: &str| tests.join(name).to_string_lossy().into_owned();
+        let names = |paths: &[String]| {
+            selected_module_names(&tests, paths).map(|names| {
+                names.iter().map(|n| n.to_string()).collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(
+            names(&[path("test_c.inko"), path("std")]).ok(),
+            Some(vec![
+                "std.test_a".to_string(),
+                "std.test_b".to_string(),
+                "test_c".to_string()
+            ])
+        );
+        assert_eq!(
+            names(&[path("test_c.inko"), path("test_c.inko")]).ok(),
+            Some(vec!["test_c".to_string()])
+        );
+        assert!(names(&[path("helper.inko")]).is_err());
+        assert!(names(&[path("test_e.inko")]).is_err());
+        assert!(names(&[dir
+            .join("test_d.inko")
+            .to_string_lossy()
+            .into_owned()])
+        .is_err());
+
+        remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,6 +2,7 @@ mod command;
 mod error;
 mod options;
 mod pkg;
+mod watch;
 
 use crate::command::main;
 use std::process::exit;
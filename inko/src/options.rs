@@ -1,5 +1,8 @@
 //! Generic helper functions that don't belong to any particular module.
-use getopts::Options;
+use crate::error::Error;
+use compiler::config::Config;
+use compiler::diagnostics::Level;
+use getopts::{Matches, Options};
 
 /// Prints a usage message for a set of CLI options.
 pub(crate) fn print_usage(options: &Options, brief: &str) {
@@ -13,3 +16,99 @@ pub(crate) fn print_usage(options: &Options, brief: &str) {
 
     println!("{}", out);
 }
+
+/// Adds the options used for changing the level of diagnostics.
+pub(crate) fn diagnostic_options(options: &mut Options) {
+    options.optmulti("W", "", "Report the diagnostic as a warning", "ID");
+    options.optmulti("D", "", "Report the diagnostic as an error", "ID");
+    options.optmulti("A", "", "Don't report the diagnostic", "ID");
+    options.optflag(
+        "",
+        "deny-warnings",
+        "Report all warnings as errors, unless changed using -W or -A",
+    );
+}
+
+/// Applies the options added by `diagnostic_options` to a compiler
+/// configuration.
+///
+/// When the same diagnostic is specified multiple times, the last option takes
+/// precedence.
+pub(crate) fn set_diagnostic_levels(
+    matches: &Matches,
+    config: &mut Config,
+) -> Result<(), Error> {
+    let mut levels = Vec::new();
+
+    for (opt, level) in
+        [("W", Level::Warn), ("D", Level::Deny), ("A", Level::Allow)]
+    {
+        for (pos, id) in matches.opt_strs_pos(opt) {
+            levels.push((pos, id, level));
+        }
+    }
+
+    levels.sort_by_key(|(pos, _, _)| *pos);
+
+    for (_, id, level) in levels {
+        config.set_diagnostic_level(&id, level)?;
+    }
+
+    if matches.opt_present("deny-warnings") {
+        config.deny_warnings = true;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Matches {
+        let mut options = Options::new();
+
+        diagnostic_options(&mut options);
+        options.parse(args).unwrap()
+    }
+
+    #[test]
+    fn test_set_diagnostic_levels() {
+        let mut config = Config::default();
+        let matches = parse(&[
+            "-D",
+            "unused-import",
+            "-A",
+            "unreachable",
+            "-W",
+            "unreachable",
+        ]);
+
+        assert!(set_diagnostic_levels(&matches, &mut config).is_ok());
+        assert!(!config.deny_warnings);
+    }
+
+    #[test]
+    fn test_set_diagnostic_levels_with_error() {
+        let mut config = Config::default();
+        let matches = parse(&["-A", "invalid-type"]);
+        let error = set_diagnostic_levels(&matches, &mut config).err().unwrap();
+
+        assert_eq!(
+            error.message,
+            Some(
+                "The diagnostic 'invalid-type' is an error, and only the level \
+                of warnings can be changed"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_set_diagnostic_levels_with_invalid_id() {
+        let mut config = Config::default();
+        let matches = parse(&["-W", "foo", "--deny-warnings"]);
+
+        assert!(set_diagnostic_levels(&matches, &mut config).is_err());
+    }
+}
@@ -0,0 +1,402 @@
+//! Running commands again when source files change.
+//!
+//! On Linux we use inotify to wait for changes. Other platforms periodically
+//! check the modification times of the source files instead.
+use crate::error::Error;
+use compiler::config::{Config, SOURCE_EXT};
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::io::{self, stdout, Write};
+use std::path::{Path, PathBuf};
+
+/// The ANSI sequence used for clearing the screen and moving the cursor to the
+/// top-left corner.
+const CLEAR: &str = "\x1b[2J\x1b[H";
+
+fn is_source_file(path: &Path) -> bool {
+    path.extension() == Some(OsStr::new(SOURCE_EXT))
+}
+
+/// Calls the closure for every directory (including `root`) and file in the
+/// given directory, recursively.
+///
+/// Symbolic links to directories are followed, but every directory is only
+/// visited once, such that symbolic links pointing to a parent directory don't
+/// result in an infinite loop.
+fn walk<F: FnMut(&Path, bool)>(root: &Path, mut func: F) {
+    let mut pending = vec![root.to_path_buf()];
+    let mut visited = HashSet::new();
+
+    while let Some(dir) = pending.pop() {
+        let Ok(real) = dir.canonicalize() else { continue };
+
+        if !visited.insert(real) {
+            continue;
+        }
+
+        let Ok(entries) = dir.read_dir() else { continue };
+
+        func(&dir, true);
+
+        for path in entries.flatten().map(|e| e.path()) {
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                func(&path, false);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use crate::watch::{is_source_file, walk};
+    use std::collections::HashMap;
+    use std::ffi::{CString, OsStr};
+    use std::io;
+    use std::mem::size_of;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+
+    /// The events we're interested in.
+    const MASK: u32 = libc::IN_CLOSE_WRITE
+        | libc::IN_CREATE
+        | libc::IN_DELETE
+        | libc::IN_MOVED_FROM
+        | libc::IN_MOVED_TO;
+
+    /// The time (in milliseconds) to wait for additional events after
+    /// receiving a change, such that saving multiple files at once only
+    /// triggers a single change.
+    const DEBOUNCE: i32 = 100;
+
+    /// A type that waits for source files to change, using inotify.
+    pub(crate) struct Changes {
+        fd: libc::c_int,
+
+        /// The directories watched, using their watch descriptors as the keys.
+        directories: HashMap<libc::c_int, PathBuf>,
+    }
+
+    impl Changes {
+        pub(crate) fn new(directories: &[PathBuf]) -> io::Result<Changes> {
+            let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC) };
+
+            if fd == -1 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut changes = Changes { fd, directories: HashMap::new() };
+
+            for dir in directories {
+                changes.watch(dir)?;
+            }
+
+            Ok(changes)
+        }
+
+        /// Blocks the calling thread until one or more source files change.
+        pub(crate) fn wait(&mut self) -> io::Result<()> {
+            while !self.read(-1)? {}
+
+            // Saving a file may produce multiple events, and editors may save
+            // multiple files at once, so we ignore events produced shortly
+            // after the first one.
+            while self.read(DEBOUNCE)? {}
+
+            Ok(())
+        }
+
+        /// Watches a directory and its sub directories.
+        fn watch(&mut self, root: &Path) -> io::Result<()> {
+            let mut result = Ok(());
+
+            walk(root, |path, dir| {
+                if !dir || result.is_err() {
+                    return;
+                }
+
+                let Ok(cpath) = CString::new(path.as_os_str().as_bytes())
+                else {
+                    return;
+                };
+                let wd = unsafe {
+                    libc::inotify_add_watch(self.fd, cpath.as_ptr(), MASK)
+                };
+
+                if wd == -1 {
+                    result = Err(io::Error::last_os_error());
+                } else {
+                    self.directories.insert(wd, path.to_path_buf());
+                }
+            });
+
+            result
+        }
+
+        /// Reads the pending events, returning `true` if any source files
+        /// changed.
+        ///
+        /// The `timeout` argument is the time in milliseconds to wait for
+        /// events, or -1 to wait forever.
+        fn read(&mut self, timeout: i32) -> io::Result<bool> {
+            let mut poll =
+                libc::pollfd { fd: self.fd, events: libc::POLLIN, revents: 0 };
+
+            match unsafe { libc::poll(&mut poll, 1, timeout) } {
+                -1 => {
+                    let err = io::Error::last_os_error();
+
+                    return if err.kind() == io::ErrorKind::Interrupted {
+                        Ok(false)
+                    } else {
+                        Err(err)
+                    };
+                }
+                0 => return Ok(false),
+                _ => {}
+            }
+
+            let mut buf = vec![0_u8; 64 * 1024];
+            let len = unsafe {
+                libc::read(self.fd, buf.as_mut_ptr() as *mut _, buf.len())
+            };
+
+            if len == -1 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut changed = false;
+            let mut offset = 0;
+            let header = size_of::<libc::inotify_event>();
+
+            while offset + header <= len as usize {
+                let event = unsafe {
+                    (buf.as_ptr().add(offset) as *const libc::inotify_event)
+                        .read_unaligned()
+                };
+                let start = offset + header;
+                let name = &buf[start..start + event.len as usize];
+                let name = OsStr::from_bytes(
+                    name.split(|&b| b == 0).next().unwrap_or(&[]),
+                );
+
+                offset = start + event.len as usize;
+
+                let Some(dir) = self.directories.get(&event.wd) else {
+                    continue;
+                };
+                let path = dir.join(name);
+
+                if event.mask & libc::IN_ISDIR != 0 {
+                    // New directories need to be watched as well, and may
+                    // already contain source files (e.g. when they are moved
+                    // into a watched directory).
+                    if event.mask & (libc::IN_CREATE | libc::IN_MOVED_TO) != 0 {
+                        self.watch(&path)?;
+                    }
+
+                    changed = true;
+                } else if is_source_file(&path) {
+                    changed = true;
+                }
+            }
+
+            Ok(changed)
+        }
+    }
+
+    impl Drop for Changes {
+        fn drop(&mut self) {
+            unsafe {
+                libc::close(self.fd);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use crate::watch::{is_source_file, walk};
+    use std::collections::HashMap;
+    use std::io;
+    use std::path::PathBuf;
+    use std::thread::sleep;
+    use std::time::{Duration, SystemTime};
+
+    /// The interval at which to check for changes.
+    const INTERVAL: Duration = Duration::from_millis(500);
+
+    /// A type that waits for source files to change, by periodically checking
+    /// their modification times.
+    pub(crate) struct Changes {
+        directories: Vec<PathBuf>,
+        files: HashMap<PathBuf, Option<SystemTime>>,
+    }
+
+    impl Changes {
+        pub(crate) fn new(directories: &[PathBuf]) -> io::Result<Changes> {
+            let directories = directories.to_vec();
+            let files = Changes::files(&directories);
+
+            Ok(Changes { directories, files })
+        }
+
+        /// Blocks the calling thread until one or more source files change.
+        pub(crate) fn wait(&mut self) -> io::Result<()> {
+            loop {
+                sleep(INTERVAL);
+
+                let files = Changes::files(&self.directories);
+
+                if files != self.files {
+                    self.files = files;
+                    return Ok(());
+                }
+            }
+        }
+
+        fn files(
+            directories: &[PathBuf],
+        ) -> HashMap<PathBuf, Option<SystemTime>> {
+            let mut files = HashMap::new();
+
+            for dir in directories {
+                walk(dir, |path, dir| {
+                    if !dir && is_source_file(path) {
+                        let time =
+                            path.metadata().and_then(|m| m.modified()).ok();
+
+                        files.insert(path.to_path_buf(), time);
+                    }
+                });
+            }
+
+            files
+        }
+    }
+}
+
+/// Runs a command, then runs it again whenever a source file in one of the
+/// given directories changes.
+///
+/// The screen is cleared before every run. Errors produced by the command are
+/// displayed, but don't stop the watching process.
+pub(crate) fn watch<F: FnMut() -> Result<i32, Error>>(
+    directories: Vec<PathBuf>,
+    mut command: F,
+) -> Result<i32, Error> {
+    let directories: Vec<_> =
+        directories.into_iter().filter(|d| d.is_dir()).collect();
+    let mut changes = platform::Changes::new(&directories).map_err(|err| {
+        Error::generic(format!("Failed to watch for changes: {}", err))
+    })?;
+
+    loop {
+        print!("{}", CLEAR);
+
+        let _ = stdout().flush();
+
+        if let Err(Error { message: Some(msg), .. }) = command() {
+            eprintln!("{}", msg);
+        }
+
+        changes.wait().map_err(|err: io::Error| {
+            Error::generic(format!("Failed to watch for changes: {}", err))
+        })?;
+    }
+}
+
+/// Returns the directories to watch for changes: the source, tests and
+/// dependencies directories of the project, and any additional source
+/// directories (e.g. those included using the project file).
+pub(crate) fn directories(config: &Config) -> Vec<PathBuf> {
+    let mut dirs = vec![
+        config.source.clone(),
+        config.tests.clone(),
+        config.dependencies.clone(),
+    ];
+
+    for dir in config.include_directories() {
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    #[test]
+    fn test_walk() {
+        let root = temp_dir().join("inko-test-watch-walk");
+        let _ = remove_dir_all(&root);
+
+        create_dir_all(root.join("a")).unwrap();
+        write(root.join("a").join("b.inko"), "").unwrap();
+
+        let mut found = Vec::new();
+
+        walk(&root, |path, dir| {
+            found.push((path.strip_prefix(&root).unwrap().to_path_buf(), dir))
+        });
+        found.sort();
+
+        assert_eq!(
+            found,
+            vec![
+                (PathBuf::new(), true),
+                (PathBuf::from("a"), true),
+                (PathBuf::from("a/b.inko"), false),
+            ]
+        );
+
+        remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_with_symlink_loop() {
+        let root = temp_dir().join("inko-test-watch-walk-loop");
+        let _ = remove_dir_all(&root);
+
+        create_dir_all(root.join("a")).unwrap();
+        std::os::unix::fs::symlink(&root, root.join("a").join("loop")).unwrap();
+
+        let mut dirs = 0;
+
+        walk(&root, |_, dir| {
+            if dir {
+                dirs += 1;
+            }
+        });
+
+        assert_eq!(dirs, 2);
+        remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_directories() {
+        let mut config = Config::default();
+
+        config.add_source_directory(PathBuf::from("/foo/vendor"));
+        config.add_source_directory(config.tests.clone());
+
+        let dirs = directories(&config);
+
+        assert_eq!(
+            dirs,
+            vec![
+                config.source.clone(),
+                config.tests.clone(),
+                config.dependencies.clone(),
+                PathBuf::from("/foo/vendor"),
+            ]
+        );
+    }
+}
@@ -33,6 +33,9 @@ const MAX_NETPOLL_THREADS: u8 = 127;
 /// cases, and to ensure foreign function calls don't overflow the stack.
 const DEFAULT_STACK_SIZE: u32 = 1024 * 1024;
 
+/// The default interval (in milliseconds) at which to write runtime metrics.
+const DEFAULT_METRICS_INTERVAL: u32 = 1000;
+
 /// Structure containing the configuration settings for the virtual machine.
 pub struct Config {
     /// The number of process threads to run.
@@ -51,6 +54,15 @@ pub struct Config {
     /// and use the value -1 to signal a file descriptor isn't registered with
     /// any poller.
     pub netpoll_threads: u8,
+
+    /// The path of the file to periodically write runtime metrics to.
+    pub metrics_file: Option<String>,
+
+    /// The interval (in milliseconds) at which to write runtime metrics.
+    pub metrics_interval: u32,
+
+    /// The path of the file to write a trace of scheduler events to.
+    pub trace_file: Option<String>,
 }
 
 impl Config {
@@ -62,6 +74,9 @@ impl Config {
             backup_threads: cpu_count * 4,
             netpoll_threads: DEFAULT_NETPOLL_THREADS,
             stack_size: DEFAULT_STACK_SIZE,
+            metrics_file: None,
+            metrics_interval: DEFAULT_METRICS_INTERVAL,
+            trace_file: None,
         }
     }
 
@@ -72,6 +87,11 @@ impl Config {
         set_from_env!(config, backup_threads, "BACKUP_THREADS", u16);
         set_from_env!(config, netpoll_threads, "NETPOLL_THREADS", u8);
         set_from_env!(config, stack_size, "STACK_SIZE", u32);
+        set_from_env!(config, metrics_interval, "METRICS_INTERVAL", u32);
+
+        config.metrics_file =
+            var("INKO_METRICS_FILE").ok().filter(|v| !v.is_empty());
+        config.trace_file = var("INKO_TRACE").ok().filter(|v| !v.is_empty());
 
         config.verify();
         config
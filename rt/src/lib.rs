@@ -10,15 +10,19 @@ pub mod config;
 pub mod context;
 pub mod mem;
 pub mod memory_map;
+pub(crate) mod metrics;
 pub mod network_poller;
 pub mod page;
 pub mod process;
 pub mod result;
 pub mod runtime;
 pub mod scheduler;
+pub(crate) mod signal;
 pub mod socket;
 pub mod stack;
+pub(crate) mod stack_overflow;
 pub mod state;
+pub(crate) mod trace;
 
 #[cfg(test)]
 pub mod test;
@@ -1,14 +1,18 @@
 use std::alloc::{alloc, alloc_zeroed, dealloc, handle_alloc_error, Layout};
 use std::mem::{align_of, forget, size_of, swap};
 use std::ops::Deref;
-use std::ptr::drop_in_place;
+use std::ptr::{addr_of, drop_in_place};
 use std::slice;
 use std::str;
 use std::string::String as RustString;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 /// The alignment to use for Inko objects.
 const ALIGNMENT: usize = align_of::<usize>();
 
+/// The method slot of the dropper of a class.
+const DROPPER_INDEX: usize = 0;
+
 pub(crate) fn allocate(layout: Layout) -> *mut u8 {
     unsafe {
         let ptr = alloc(layout);
@@ -71,6 +75,25 @@ impl Header {
     pub(crate) fn references(&self) -> u32 {
         self.references
     }
+
+    /// Returns the number of references to an object that uses atomic
+    /// reference counting.
+    pub(crate) fn atomic_references(&self) -> u32 {
+        self.atomic_count().load(Ordering::Acquire)
+    }
+
+    /// Removes the given number of references from an object that uses atomic
+    /// reference counting.
+    ///
+    /// The return value is `true` if no references remain.
+    pub(crate) fn decrement_atomic(&self, amount: u32) -> bool {
+        self.atomic_count().fetch_sub(amount, Ordering::AcqRel) == amount
+    }
+
+    fn atomic_count(&self) -> &AtomicU32 {
+        // Safety: AtomicU32 has the same in-memory representation as u32.
+        unsafe { &*(addr_of!(self.references) as *const AtomicU32) }
+    }
 }
 
 /// A function bound to an object.
@@ -164,6 +187,12 @@ impl Class {
         Self::alloc(name, methods, size)
     }
 
+    /// Returns the native function of the dropper of this class.
+    pub(crate) fn dropper(&self) -> extern "system" fn() {
+        // Safety: the compiler generates a dropper for every class it defines.
+        unsafe { (*self.methods.as_ptr().add(DROPPER_INDEX)).code }
+    }
+
     /// Returns the `Layout` for a class itself.
     unsafe fn layout(methods: u16) -> Layout {
         let size =
@@ -319,6 +348,15 @@ mod tests {
 
     extern "system" fn dummy() {}
 
+    #[test]
+    fn test_header_decrement_atomic() {
+        let header = Header { class: ClassPointer(0x7 as _), references: 2 };
+
+        assert!(!header.decrement_atomic(1));
+        assert!(header.decrement_atomic(1));
+        assert_eq!(header.atomic_references(), 0);
+    }
+
     #[test]
     fn test_header_field_offsets() {
         let header = Header { class: ClassPointer(0x7 as _), references: 42 };
@@ -0,0 +1,184 @@
+//! Snapshots of the runtime's internal statistics.
+//!
+//! These statistics are collected on demand from the scheduler, timeout worker
+//! and network pollers. Since these structures are modified concurrently, a
+//! snapshot isn't guaranteed to be consistent (e.g. a process may be counted
+//! as alive while it's in the middle of terminating), but it's good enough to
+//! get a sense of what the runtime is doing.
+use crate::state::{RcState, State};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Write as _;
+use std::thread::sleep;
+use std::time::Duration;
+
+/// A snapshot of various runtime statistics.
+#[derive(Eq, PartialEq, Debug)]
+pub(crate) struct Metrics {
+    /// The number of processes that are alive.
+    pub(crate) processes: usize,
+
+    /// The number of processes in the local queue of each process thread.
+    pub(crate) run_queues: Vec<usize>,
+
+    /// The number of processes in the global queue.
+    pub(crate) global_queue: usize,
+
+    /// The number of process threads performing a blocking operation.
+    pub(crate) blocking_threads: usize,
+
+    /// The number of backup threads waiting to replace a blocked thread.
+    pub(crate) backup_threads: usize,
+
+    /// The number of stacks available for reuse.
+    pub(crate) pooled_stacks: usize,
+
+    /// The number of processes waiting for a timeout to expire.
+    pub(crate) timeouts: usize,
+
+    /// The number of sockets registered with the network pollers.
+    pub(crate) sockets: usize,
+}
+
+impl Metrics {
+    pub(crate) fn new(state: &State) -> Metrics {
+        let sched = &state.scheduler;
+
+        Metrics {
+            processes: sched.processes(),
+            run_queues: sched.local_queue_sizes(),
+            global_queue: sched.global_queue_size(),
+            blocking_threads: sched.blocking_threads(),
+            backup_threads: sched.backup_threads(),
+            pooled_stacks: sched.pooled_stacks(),
+            timeouts: state.timeout_worker.len(),
+            sockets: state.network_pollers.iter().map(|p| p.sockets()).sum(),
+        }
+    }
+
+    /// Returns the metrics as a flat list of integers.
+    ///
+    /// The run queue sizes come last, such that the number of process threads
+    /// can be derived from the length of the list.
+    pub(crate) fn to_vec(&self) -> Vec<i64> {
+        let mut values = vec![
+            self.processes as i64,
+            self.global_queue as i64,
+            self.blocking_threads as i64,
+            self.backup_threads as i64,
+            self.pooled_stacks as i64,
+            self.timeouts as i64,
+            self.sockets as i64,
+        ];
+
+        values.extend(self.run_queues.iter().map(|&v| v as i64));
+        values
+    }
+
+    /// Returns the metrics as a single line of JSON.
+    ///
+    /// The `time` argument is the number of seconds since the program started.
+    pub(crate) fn to_json(&self, time: f64) -> String {
+        let mut buf = format!(
+            "{{\"time\":{:.3},\"processes\":{},\"run_queues\":[",
+            time, self.processes
+        );
+
+        for (index, size) in self.run_queues.iter().enumerate() {
+            if index > 0 {
+                buf.push(',');
+            }
+
+            let _ = write!(buf, "{}", size);
+        }
+
+        let _ = write!(
+            buf,
+            "],\"global_queue\":{},\"blocking_threads\":{},\
+            \"backup_threads\":{},\"pooled_stacks\":{},\"timeouts\":{},\
+            \"sockets\":{}}}",
+            self.global_queue,
+            self.blocking_threads,
+            self.backup_threads,
+            self.pooled_stacks,
+            self.timeouts,
+            self.sockets
+        );
+
+        buf
+    }
+}
+
+/// Periodically appends a snapshot of the metrics to the given file, one JSON
+/// object per line.
+///
+/// This function runs until the scheduler terminates. If the file can't be
+/// created or written to, we stop collecting metrics.
+pub(crate) fn write_periodically(state: RcState, path: String) {
+    let Ok(mut file) = File::create(&path) else {
+        eprintln!("Failed to create the metrics file {}", path);
+        return;
+    };
+    let interval = Duration::from_millis(state.config.metrics_interval as u64);
+
+    while state.scheduler.is_alive() {
+        let time = state.start_time.elapsed().as_secs_f64();
+        let mut line = Metrics::new(&state).to_json(time);
+
+        line.push('\n');
+
+        if file.write_all(line.as_bytes()).is_err() {
+            return;
+        }
+
+        sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::setup;
+
+    fn metrics() -> Metrics {
+        Metrics {
+            processes: 1,
+            run_queues: vec![2, 3],
+            global_queue: 4,
+            blocking_threads: 5,
+            backup_threads: 6,
+            pooled_stacks: 7,
+            timeouts: 8,
+            sockets: 9,
+        }
+    }
+
+    #[test]
+    fn test_new() {
+        let state = setup();
+        let metrics = Metrics::new(&state);
+
+        assert_eq!(metrics.processes, 0);
+        assert_eq!(
+            metrics.run_queues.len(),
+            state.config.process_threads as usize
+        );
+        assert_eq!(metrics.timeouts, 0);
+        assert_eq!(metrics.sockets, 0);
+    }
+
+    #[test]
+    fn test_to_vec() {
+        assert_eq!(metrics().to_vec(), vec![1, 4, 5, 6, 7, 8, 9, 2, 3]);
+    }
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(
+            metrics().to_json(1.5),
+            "{\"time\":1.500,\"processes\":1,\"run_queues\":[2,3],\
+            \"global_queue\":4,\"blocking_threads\":5,\"backup_threads\":6,\
+            \"pooled_stacks\":7,\"timeouts\":8,\"sockets\":9}"
+        );
+    }
+}
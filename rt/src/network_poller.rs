@@ -1,8 +1,10 @@
 //! Polling of non-blocking sockets using the system's polling mechanism.
 use crate::process::{ProcessPointer, RescheduleRights};
 use crate::state::RcState;
+use crate::trace::{self, Value};
 use polling::{Event, Poller, Source};
 use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// The type of event a poller should wait for.
 pub(crate) enum Interest {
@@ -16,15 +18,23 @@ pub(crate) enum Interest {
 /// A poller for non-blocking sockets.
 pub(crate) struct NetworkPoller {
     poller: Poller,
+
+    /// The number of sockets registered with this poller.
+    sockets: AtomicUsize,
 }
 
 impl NetworkPoller {
     pub(crate) fn new() -> Self {
         NetworkPoller {
             poller: Poller::new().expect("Failed to set up the network poller"),
+            sockets: AtomicUsize::new(0),
         }
     }
 
+    pub(crate) fn sockets(&self) -> usize {
+        self.sockets.load(Ordering::Relaxed)
+    }
+
     pub(crate) fn poll(&self, events: &mut Vec<Event>) -> io::Result<usize> {
         self.poller.wait(events, None)
     }
@@ -35,7 +45,13 @@ impl NetworkPoller {
         source: impl Source,
         interest: Interest,
     ) -> io::Result<()> {
-        self.poller.add(source, self.event(process, interest))
+        // We increment the counter first, as the process may be rescheduled
+        // and close the socket before we get a chance to increment it.
+        self.sockets.fetch_add(1, Ordering::Relaxed);
+        self.poller.add(source, self.event(process, interest)).map_err(|err| {
+            self.sockets.fetch_sub(1, Ordering::Relaxed);
+            err
+        })
     }
 
     pub(crate) fn modify(
@@ -48,9 +64,18 @@ impl NetworkPoller {
     }
 
     pub(crate) fn delete(&self, source: impl Source) -> io::Result<()> {
+        self.closed();
         self.poller.delete(source)
     }
 
+    /// Records that a registered socket is closed.
+    ///
+    /// Closing a socket removes it from the poller, so there's no need to
+    /// explicitly delete it.
+    pub(crate) fn closed(&self) {
+        self.sockets.fetch_sub(1, Ordering::Relaxed);
+    }
+
     fn event(&self, process: ProcessPointer, interest: Interest) -> Event {
         let key = process.identifier();
 
@@ -75,6 +100,7 @@ impl Worker {
     pub(crate) fn run(&self) {
         let mut events = Vec::new();
         let poller = &self.state.network_pollers[self.id];
+        let trace = trace::buffer();
 
         loop {
             if let Err(err) = poller.poll(&mut events) {
@@ -99,7 +125,8 @@ impl Worker {
                     // only reschedule the process if the timout thread didn't
                     // already do this for us.
                     match rights {
-                        RescheduleRights::Failed => None,
+                        RescheduleRights::Failed
+                        | RescheduleRights::Terminated => None,
                         RescheduleRights::Acquired => Some(proc),
                         RescheduleRights::AcquiredWithTimeout => {
                             self.state
@@ -109,7 +136,15 @@ impl Worker {
                         }
                     }
                 })
-                .collect();
+                .collect::<Vec<_>>();
+
+            if let Some(trace) = &trace {
+                trace.instant(
+                    "netpoll",
+                    "wakeup",
+                    vec![("processes", Value::Int(processes.len() as _))],
+                );
+            }
 
             self.state.scheduler.schedule_multiple(processes);
             events.clear();
@@ -132,6 +167,7 @@ mod tests {
         let poller = NetworkPoller::new();
 
         assert!(poller.add(*process, &output, Interest::Read).is_ok());
+        assert_eq!(poller.sockets(), 1);
     }
 
     #[test]
@@ -155,6 +191,7 @@ mod tests {
 
         assert!(poller.add(*process, &output, Interest::Write).is_ok());
         assert!(poller.delete(&output).is_ok());
+        assert_eq!(poller.sockets(), 0);
 
         let len = poller
             .poller
@@ -3,11 +3,12 @@ use crate::mem::{allocate, free, ClassPointer, Header};
 use crate::scheduler::process::Thread;
 use crate::scheduler::timeouts::Timeout;
 use crate::stack::Stack;
+use crate::state::State;
 use backtrace;
 use std::alloc::{alloc, dealloc, handle_alloc_error, Layout};
 use std::cell::UnsafeCell;
 use std::collections::VecDeque;
-use std::mem::{align_of, forget, size_of, ManuallyDrop};
+use std::mem::{align_of, forget, size_of, take, transmute, ManuallyDrop};
 use std::ops::Drop;
 use std::ops::{Deref, DerefMut};
 use std::ptr::{drop_in_place, null_mut, NonNull};
@@ -152,36 +153,46 @@ pub(crate) struct ProcessStatus {
     /// The bits used to indicate the status of the process.
     ///
     /// Multiple bits may be set in order to combine different statuses.
-    bits: u8,
+    bits: u16,
 }
 
 impl ProcessStatus {
     /// A regular process.
-    const NORMAL: u8 = 0b00_0000;
+    const NORMAL: u16 = 0b00_0000;
 
     /// The main process.
-    const MAIN: u8 = 0b00_0001;
+    const MAIN: u16 = 0b00_0001;
 
     /// The process is waiting for a message.
-    const WAITING_FOR_MESSAGE: u8 = 0b00_0010;
+    const WAITING_FOR_MESSAGE: u16 = 0b00_0010;
 
     /// The process is waiting for a channel.
-    const WAITING_FOR_CHANNEL: u8 = 0b00_0100;
+    const WAITING_FOR_CHANNEL: u16 = 0b00_0100;
 
     /// The process is waiting for an IO operation to complete.
-    const WAITING_FOR_IO: u8 = 0b00_1000;
+    const WAITING_FOR_IO: u16 = 0b00_1000;
 
     /// The process is simply sleeping for a certain amount of time.
-    const SLEEPING: u8 = 0b01_0000;
+    const SLEEPING: u16 = 0b01_0000;
 
     /// The process was rescheduled after a timeout expired.
-    const TIMEOUT_EXPIRED: u8 = 0b10_0000;
+    const TIMEOUT_EXPIRED: u16 = 0b10_0000;
 
     /// The process is running a message.
-    const RUNNING: u8 = 0b100_0000;
+    const RUNNING: u16 = 0b100_0000;
+
+    /// A panic terminates only this process, instead of the entire program.
+    const TERMINATE_ON_PANIC: u16 = 0b1000_0000;
+
+    /// The process panicked and won't run any more messages.
+    const TERMINATED: u16 = 0b1_0000_0000;
+
+    /// The last reference to the process is gone, and the message that drops
+    /// the process is sent to it.
+    const DROPPING: u16 = 0b10_0000_0000;
 
     /// The process is waiting for something, or suspended for a period of time.
-    const WAITING: u8 =
+    const WAITING: u16 =
         Self::WAITING_FOR_CHANNEL | Self::SLEEPING | Self::WAITING_FOR_IO;
 
     pub(crate) fn new() -> Self {
@@ -204,6 +215,30 @@ impl ProcessStatus {
         self.bit_is_set(Self::MAIN)
     }
 
+    fn set_terminate_on_panic(&mut self, enable: bool) {
+        self.update_bits(Self::TERMINATE_ON_PANIC, enable);
+    }
+
+    fn terminates_on_panic(&self) -> bool {
+        self.bit_is_set(Self::TERMINATE_ON_PANIC)
+    }
+
+    fn set_terminated(&mut self) {
+        self.update_bits(Self::TERMINATED, true);
+    }
+
+    fn is_terminated(&self) -> bool {
+        self.bit_is_set(Self::TERMINATED)
+    }
+
+    fn set_dropping(&mut self) {
+        self.update_bits(Self::DROPPING, true);
+    }
+
+    fn is_dropping(&self) -> bool {
+        self.bit_is_set(Self::DROPPING)
+    }
+
     fn set_waiting_for_message(&mut self, enable: bool) {
         self.update_bits(Self::WAITING_FOR_MESSAGE, enable);
     }
@@ -248,11 +283,11 @@ impl ProcessStatus {
         self.bit_is_set(Self::TIMEOUT_EXPIRED)
     }
 
-    fn update_bits(&mut self, mask: u8, enable: bool) {
+    fn update_bits(&mut self, mask: u16, enable: bool) {
         self.bits = if enable { self.bits | mask } else { self.bits & !mask };
     }
 
-    fn bit_is_set(&self, bit: u8) -> bool {
+    fn bit_is_set(&self, bit: u16) -> bool {
         self.bits & bit == bit
     }
 }
@@ -270,11 +305,17 @@ pub(crate) enum RescheduleRights {
     /// The rescheduling rights were obtained, and the process was using a
     /// timeout.
     AcquiredWithTimeout,
+
+    /// The process panicked, so the message sent to it is discarded.
+    Terminated,
 }
 
 impl RescheduleRights {
     pub(crate) fn are_acquired(&self) -> bool {
-        !matches!(self, RescheduleRights::Failed)
+        matches!(
+            self,
+            RescheduleRights::Acquired | RescheduleRights::AcquiredWithTimeout
+        )
     }
 }
 
@@ -293,6 +334,16 @@ pub(crate) struct ProcessState {
     /// If missing and the process is suspended, it means the process is
     /// suspended indefinitely.
     timeout: Option<ArcWithoutWeak<Timeout>>,
+
+    /// The monitors to notify when the process panics.
+    monitors: Vec<ArcWithoutWeak<Monitor>>,
+
+    /// The message of the panic that terminated the process.
+    ///
+    /// This is used to notify monitors added after the process panicked. The
+    /// message is boxed to keep the size of processes down.
+    #[allow(clippy::box_collection)]
+    panic_message: Option<Box<String>>,
 }
 
 impl ProcessState {
@@ -301,6 +352,8 @@ impl ProcessState {
             mailbox: Mailbox::new(),
             status: ProcessStatus::new(),
             timeout: None,
+            monitors: Vec::new(),
+            panic_message: None,
         }
     }
 
@@ -314,6 +367,17 @@ impl ProcessState {
             .unwrap_or(false)
     }
 
+    /// Returns `true` if the process is in the middle of running a message.
+    pub(crate) fn is_running(&self) -> bool {
+        self.status.is_running()
+    }
+
+    /// Returns `true` if the process is waiting for a message, channel, IO
+    /// operation or timeout.
+    pub(crate) fn is_waiting(&self) -> bool {
+        self.status.is_waiting()
+    }
+
     pub(crate) fn suspend(&mut self, timeout: ArcWithoutWeak<Timeout>) {
         self.timeout = Some(timeout);
         self.status.set_sleeping(true);
@@ -398,6 +462,21 @@ impl ProcessState {
     }
 }
 
+/// What to do with a process after it panicked.
+#[derive(Eq, PartialEq, Debug)]
+pub(crate) enum Terminated {
+    /// The given number of references must be released, as the messages
+    /// holding these references won't run.
+    ///
+    /// The process is kept around until its last reference is released, at
+    /// which point it runs its dropper.
+    Release(u32),
+
+    /// The process panicked while running its dropper, and can be deallocated
+    /// right away.
+    Deallocate,
+}
+
 /// A lightweight process.
 #[repr(C)]
 pub struct Process {
@@ -524,6 +603,19 @@ impl Process {
     ) -> RescheduleRights {
         let mut state = self.state.lock().unwrap();
 
+        // Every message holds a reference to the process, except for the
+        // message sent after the last reference is gone, which drops the
+        // process. This message is accepted even if the process panicked, as
+        // otherwise the process would never be released.
+        //
+        // Any other messages sent to a process that panicked are discarded, and
+        // it's up to the sender to release the reference they hold.
+        if self.header.atomic_references() == 0 {
+            state.status.set_dropping();
+        } else if state.status.is_terminated() {
+            return RescheduleRights::Terminated;
+        }
+
         state.mailbox.send(message);
         state.try_reschedule_for_message()
     }
@@ -550,11 +642,25 @@ impl Process {
             slice::from_raw_parts(message.arguments.as_ptr(), len).to_vec()
         };
 
-        self.stack_pointer = self.stack.stack_pointer();
+        // A process that panicked gives up its stack, in which case the thread
+        // running the process assigns it a new one.
+        if !self.stack_pointer.is_null() {
+            self.stack_pointer = self.stack.stack_pointer();
+        }
+
         state.status.set_running(true);
         Task::Start(func, args)
     }
 
+    pub(crate) fn has_stack(&self) -> bool {
+        !self.stack_pointer.is_null()
+    }
+
+    pub(crate) fn set_stack(&mut self, stack: Stack) {
+        self.stack_pointer = stack.stack_pointer();
+        self.stack = ManuallyDrop::new(stack);
+    }
+
     pub(crate) fn take_stack(&mut self) -> Option<Stack> {
         if self.stack_pointer.is_null() {
             None
@@ -584,6 +690,104 @@ impl Process {
         }
     }
 
+    pub(crate) fn set_terminate_on_panic(&self, enable: bool) {
+        self.state.lock().unwrap().status.set_terminate_on_panic(enable);
+    }
+
+    /// Returns `true` if a panic should terminate only this process, instead
+    /// of the entire program.
+    ///
+    /// The program depends on the main process, so a panic in the main process
+    /// always terminates the program.
+    pub(crate) fn terminates_on_panic(&self) -> bool {
+        let state = self.state.lock().unwrap();
+
+        state.status.terminates_on_panic() && !state.status.is_main()
+    }
+
+    /// Adds a monitor to notify when the process panics.
+    ///
+    /// If the process already panicked, the monitor isn't added and the panic
+    /// message is returned instead.
+    pub(crate) fn add_monitor(
+        &self,
+        monitor: ArcWithoutWeak<Monitor>,
+    ) -> Option<String> {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(message) = &state.panic_message {
+            return Some(message.to_string());
+        }
+
+        state.monitors.push(monitor);
+        None
+    }
+
+    /// Marks the process as terminated after it panicked.
+    ///
+    /// The monitors to notify are returned. The process isn't rescheduled
+    /// until `finish_after_panic()` is called.
+    pub(crate) fn terminate_after_panic(
+        &mut self,
+        message: &str,
+    ) -> Vec<ArcWithoutWeak<Monitor>> {
+        let mut state = self.state.lock().unwrap();
+
+        state.status.set_running(false);
+        state.status.set_terminated();
+        state.panic_message = Some(Box::new(message.to_string()));
+        take(&mut state.monitors)
+    }
+
+    /// Discards the pending messages of a process that panicked, and decides
+    /// what to do with the process next.
+    ///
+    /// The process is set to wait for the message that drops it, but as the
+    /// discarded messages still hold references to the process, the process
+    /// isn't rescheduled until these references are released.
+    pub(crate) fn finish_after_panic(&mut self) -> Terminated {
+        let mut state = self.state.lock().unwrap();
+
+        // The message that drops the process is the last message it receives,
+        // so if it's already sent, the panic happened while running it.
+        if state.status.is_dropping() {
+            return Terminated::Deallocate;
+        }
+
+        // The message that panicked also holds a reference.
+        let references = state.mailbox.messages.len() as u32 + 1;
+
+        state.mailbox.messages.clear();
+        state.status.set_waiting_for_message(true);
+        Terminated::Release(references)
+    }
+
+    /// Releases references to a process held by messages that won't run.
+    ///
+    /// If no references remain, the dropper of the process is called, sending
+    /// the process the message that drops it.
+    ///
+    /// If the process may be rescheduled, it must not be used after calling
+    /// this method, as it may already be released by another thread.
+    pub(crate) unsafe fn release_references(
+        state: &State,
+        sender: ProcessPointer,
+        process: ProcessPointer,
+        amount: u32,
+    ) {
+        if !process.header.decrement_atomic(amount) {
+            return;
+        }
+
+        let dropper: unsafe extern "system" fn(
+            *const State,
+            ProcessPointer,
+            ProcessPointer,
+        ) -> *const u8 = transmute(process.header.class.dropper());
+
+        dropper(state, sender, process);
+    }
+
     pub(crate) fn clear_timeout(&self) {
         self.state.lock().unwrap().status.set_timeout_expired(false);
     }
@@ -874,6 +1078,24 @@ impl Channel {
             return SendResult::Full;
         }
 
+        Channel::reschedule_receiver(state)
+    }
+
+    /// Sends a message without suspending the sender if the channel is full.
+    ///
+    /// This is used for sending messages from threads other than process
+    /// threads, such as the thread used for handling signals.
+    pub(crate) fn try_send(&self, message: *mut u8) -> SendResult {
+        let mut state = self.state.lock().unwrap();
+
+        if !state.send(message) {
+            return SendResult::Full;
+        }
+
+        Channel::reschedule_receiver(state)
+    }
+
+    fn reschedule_receiver(mut state: MutexGuard<ChannelState>) -> SendResult {
         if let Some(receiver) = state.waiting_for_message.pop() {
             // We don't need to keep the lock any longer than necessary.
             drop(state);
@@ -883,7 +1105,9 @@ impl Channel {
             // processes try to reschedule the same waiting process, so we have
             // to acquire the rescheduling rights first.
             match receiver.state().try_reschedule_for_channel() {
-                RescheduleRights::Failed => SendResult::Sent,
+                RescheduleRights::Failed | RescheduleRights::Terminated => {
+                    SendResult::Sent
+                }
                 RescheduleRights::Acquired => SendResult::Reschedule(receiver),
                 RescheduleRights::AcquiredWithTimeout => {
                     SendResult::RescheduleWithTimeout(receiver)
@@ -929,6 +1153,37 @@ impl Channel {
     }
 }
 
+/// A monitor that's notified when a process panics.
+///
+/// A process panics at most once, so the channel used for sending the panic
+/// message has a capacity of one. The messages are Rust strings, which are
+/// converted to Inko strings upon receiving them.
+pub(crate) struct Monitor {
+    pub(crate) channel: Channel,
+}
+
+impl Monitor {
+    pub(crate) fn new() -> Monitor {
+        Monitor { channel: Channel::new(1) }
+    }
+
+    pub(crate) fn notify(
+        &self,
+        sender: ProcessPointer,
+        message: String,
+    ) -> SendResult {
+        self.channel.send(sender, Box::into_raw(Box::new(message)) as _)
+    }
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        while let ReceiveResult::Some(message) = self.channel.try_receive() {
+            drop(unsafe { Box::from_raw(message as *mut String) });
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -953,15 +1208,15 @@ mod tests {
 
         if cfg!(any(target_os = "linux", target_os = "freebsd")) {
             assert_eq!(size_of::<UnsafeCell<Mutex<()>>>(), 8);
-            assert_eq!(size_of::<Process>(), 112);
+            assert_eq!(size_of::<Process>(), 144);
             assert_eq!(size_of::<Channel>(), 96);
         } else {
             assert_eq!(size_of::<UnsafeCell<Mutex<()>>>(), 16);
-            assert_eq!(size_of::<Process>(), 128);
+            assert_eq!(size_of::<Process>(), 160);
             assert_eq!(size_of::<Channel>(), 104);
         }
 
-        assert_eq!(size_of::<ProcessState>(), 48);
+        assert_eq!(size_of::<ProcessState>(), 80);
         assert_eq!(size_of::<Option<NonNull<Thread>>>(), 8);
         assert_eq!(size_of::<ChannelState>(), 88);
     }
@@ -976,9 +1231,9 @@ mod tests {
         assert_eq!(
             offset_of!(proc, fields),
             if cfg!(any(target_os = "linux", target_os = "freebsd")) {
-                112
+                144
             } else {
-                128
+                160
             }
         );
     }
@@ -1280,6 +1535,24 @@ mod tests {
         assert_eq!(chan.send(*process, msg as _), SendResult::Full);
     }
 
+    #[test]
+    fn test_channel_try_send() {
+        let process_class = empty_process_class("A");
+        let process =
+            OwnedProcess::new(Process::alloc(*process_class, Stack::new(32)));
+        let chan = Channel::new(1);
+        let msg = 42;
+
+        assert_eq!(chan.try_send(msg as _), SendResult::Sent);
+        assert_eq!(chan.try_send(msg as _), SendResult::Full);
+        assert!(chan.state.lock().unwrap().waiting_for_space.is_empty());
+
+        chan.try_receive();
+        chan.receive(*process, None);
+
+        assert_eq!(chan.try_send(msg as _), SendResult::Reschedule(*process));
+    }
+
     #[test]
     fn test_channel_send_with_waiting() {
         let process_class = empty_process_class("A");
@@ -1395,6 +1668,153 @@ mod tests {
         assert_eq!(process.state().mailbox.messages.len(), 1);
     }
 
+    #[test]
+    fn test_process_terminates_on_panic() {
+        let proc_class = empty_process_class("A");
+        let stack = Stack::new(32);
+        let mut process = OwnedProcess::new(Process::alloc(*proc_class, stack));
+
+        assert!(!process.terminates_on_panic());
+
+        process.set_terminate_on_panic(true);
+        assert!(process.terminates_on_panic());
+
+        process.set_main();
+        assert!(!process.terminates_on_panic());
+    }
+
+    #[test]
+    fn test_process_terminate_after_panic() {
+        let proc_class = empty_process_class("A");
+        let stack = Stack::new(32);
+        let mut process = OwnedProcess::new(Process::alloc(*proc_class, stack));
+
+        process.add_monitor(ArcWithoutWeak::new(Monitor::new()));
+        process.send_message(Message::alloc(method, 0));
+        process.send_message(Message::alloc(method, 0));
+
+        assert!(matches!(process.next_task(), Task::Start(_, _)));
+        assert_eq!(process.terminate_after_panic("oops").len(), 1);
+        assert!(process.state().monitors.is_empty());
+        assert!(process.state().status.is_terminated());
+        assert!(!process.state().status.is_running());
+
+        assert_eq!(
+            process.send_message(Message::alloc(method, 0)),
+            RescheduleRights::Terminated
+        );
+        assert_eq!(process.finish_after_panic(), Terminated::Release(2));
+        assert!(process.state().mailbox.messages.is_empty());
+        assert!(process.state().status.is_waiting_for_message());
+    }
+
+    #[test]
+    fn test_process_send_message_after_panic_without_references() {
+        let proc_class = empty_process_class("A");
+        let stack = Stack::new(32);
+        let mut process = OwnedProcess::new(Process::alloc(*proc_class, stack));
+
+        process.send_message(Message::alloc(method, 0));
+        process.next_task();
+        process.terminate_after_panic("oops");
+        process.finish_after_panic();
+        process.header.references = 0;
+
+        assert_eq!(
+            process.send_message(Message::alloc(method, 0)),
+            RescheduleRights::Acquired
+        );
+        assert!(process.state().status.is_dropping());
+        assert_eq!(process.state().mailbox.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_process_finish_after_panic_in_dropper() {
+        let proc_class = empty_process_class("A");
+        let stack = Stack::new(32);
+        let mut process = OwnedProcess::new(Process::alloc(*proc_class, stack));
+
+        process.header.references = 0;
+        process.send_message(Message::alloc(method, 0));
+        process.next_task();
+        process.terminate_after_panic("oops");
+
+        assert_eq!(process.finish_after_panic(), Terminated::Deallocate);
+    }
+
+    #[test]
+    fn test_process_release_references() {
+        let state = setup();
+        let proc_class = empty_process_class("A");
+        let stack = Stack::new(32);
+        let mut process = OwnedProcess::new(Process::alloc(*proc_class, stack));
+
+        process.header.references = 3;
+
+        // The class doesn't define a dropper, so this only works as long as
+        // references remain.
+        unsafe {
+            Process::release_references(&state, *process, *process, 2);
+        }
+
+        assert_eq!(process.header.atomic_references(), 1);
+    }
+
+    #[test]
+    fn test_process_add_monitor_after_panic() {
+        let proc_class = empty_process_class("A");
+        let stack = Stack::new(32);
+        let mut process = OwnedProcess::new(Process::alloc(*proc_class, stack));
+
+        assert_eq!(
+            process.add_monitor(ArcWithoutWeak::new(Monitor::new())),
+            None
+        );
+
+        process.terminate_after_panic("oops");
+
+        assert_eq!(
+            process.add_monitor(ArcWithoutWeak::new(Monitor::new())),
+            Some("oops".to_string())
+        );
+        assert!(process.state().monitors.is_empty());
+    }
+
+    #[test]
+    fn test_process_set_stack() {
+        let proc_class = empty_process_class("A");
+        let stack = Stack::new(32);
+        let mut process = OwnedProcess::new(Process::alloc(*proc_class, stack));
+        let stack = process.take_stack().unwrap();
+
+        assert!(!process.has_stack());
+
+        process.set_stack(stack);
+
+        assert!(process.has_stack());
+    }
+
+    #[test]
+    fn test_monitor_notify() {
+        let process_class = empty_process_class("A");
+        let process =
+            OwnedProcess::new(Process::alloc(*process_class, Stack::new(32)));
+        let monitor = Monitor::new();
+
+        assert_eq!(
+            monitor.notify(*process, "oops".to_string()),
+            SendResult::Sent
+        );
+
+        if let ReceiveResult::Some(msg) = monitor.channel.try_receive() {
+            let msg = unsafe { Box::from_raw(msg as *mut String) };
+
+            assert_eq!(*msg, "oops");
+        } else {
+            panic!("the monitor should have received a message");
+        }
+    }
+
     #[test]
     fn test_process_next_task_without_messages() {
         let proc_class = empty_process_class("A");
@@ -1,13 +1,16 @@
 mod byte_array;
 mod class;
+mod coverage;
 mod env;
 mod float;
 mod fs;
 mod general;
 mod helpers;
 mod int;
-mod process;
+mod metrics;
+pub(crate) mod process;
 mod random;
+mod signal;
 mod socket;
 mod stdio;
 mod string;
@@ -16,11 +19,15 @@ mod time;
 
 use crate::config::Config;
 use crate::mem::ClassPointer;
+use crate::metrics::write_periodically as write_metrics;
 use crate::network_poller::Worker as NetworkPollerWorker;
 use crate::process::{NativeAsyncMethod, Process};
 use crate::scheduler::{number_of_cores, pin_thread_to_core};
+use crate::signal::{install as install_signals, run as handle_signals};
 use crate::stack::Stack;
+use crate::stack_overflow;
 use crate::state::{MethodCounts, RcState, State};
+use crate::trace;
 use std::ffi::CStr;
 use std::io::{stdout, Write as _};
 use std::process::exit as rust_exit;
@@ -78,8 +85,12 @@ pub unsafe extern "system" fn inko_runtime_start(
     method: NativeAsyncMethod,
 ) {
     signal(SIGPIPE, SIG_IGN);
+    stack_overflow::install();
+    install_signals();
     (*runtime).start(class, method);
     flush_stdout();
+    coverage::write_counters();
+    trace::write_trace();
 }
 
 #[no_mangle]
@@ -97,6 +108,8 @@ fn flush_stdout() {
 
 pub(crate) fn exit(status: i32) -> ! {
     flush_stdout();
+    coverage::write_counters();
+    trace::write_trace();
     rust_exit(status);
 }
 
@@ -125,6 +138,12 @@ impl Runtime {
         let state = self.state.clone();
         let cores = number_of_cores();
 
+        // Tracing must be enabled before starting any threads, otherwise they
+        // won't record any events.
+        if let Some(path) = self.state.config.trace_file.clone() {
+            trace::enable(path);
+        }
+
         thread::Builder::new()
             .name("timeout".to_string())
             .spawn(move || {
@@ -133,6 +152,15 @@ impl Runtime {
             })
             .unwrap();
 
+        {
+            let state = self.state.clone();
+
+            thread::Builder::new()
+                .name("signals".to_string())
+                .spawn(move || handle_signals(&state))
+                .unwrap();
+        }
+
         for id in 0..self.state.network_pollers.len() {
             let state = self.state.clone();
 
@@ -145,6 +173,15 @@ impl Runtime {
                 .unwrap();
         }
 
+        if let Some(path) = self.state.config.metrics_file.clone() {
+            let state = self.state.clone();
+
+            thread::Builder::new()
+                .name("metrics".to_string())
+                .spawn(move || write_metrics(state, path))
+                .unwrap();
+        }
+
         let stack = Stack::new(self.state.config.stack_size as usize);
         let main_proc = Process::main(main_class, main_method, stack);
 
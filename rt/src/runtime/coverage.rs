@@ -0,0 +1,63 @@
+use std::env::var_os;
+use std::fmt::Write as _;
+use std::fs::write;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// The environment variable that specifies the directory to write the coverage
+/// counters to.
+const DIRECTORY_VARIABLE: &str = "INKO_COVERAGE_DIR";
+
+/// The addresses and lengths of the registered coverage counters.
+static COUNTERS: Mutex<Vec<(usize, usize)>> = Mutex::new(Vec::new());
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_coverage_register(
+    counters: *const AtomicU64,
+    length: i64,
+) {
+    COUNTERS.lock().unwrap().push((counters as usize, length as usize));
+}
+
+/// Writes the registered coverage counters to a file.
+///
+/// Each line in the file contains the counters of a single module, separated
+/// by a space. The file is named after the ID of the OS process, such that
+/// multiple processes can write their counters to the same directory.
+///
+/// If no counters are registered or the directory isn't specified, this
+/// function does nothing.
+pub(crate) fn write_counters() {
+    let counters = COUNTERS.lock().unwrap();
+
+    if counters.is_empty() {
+        return;
+    }
+
+    let Some(dir) = var_os(DIRECTORY_VARIABLE) else { return };
+    let mut buf = String::new();
+
+    for &(address, length) in counters.iter() {
+        let values = address as *const AtomicU64;
+
+        for index in 0..length {
+            let value = unsafe { &*values.add(index) }.load(Ordering::Acquire);
+
+            if index > 0 {
+                buf.push(' ');
+            }
+
+            let _ = write!(buf, "{}", value);
+        }
+
+        buf.push('\n');
+    }
+
+    let path = PathBuf::from(dir).join(format!("{}.txt", process::id()));
+
+    // There's nothing we can do at this point if the file can't be written, so
+    // we just ignore the error.
+    let _ = write(path, buf);
+}
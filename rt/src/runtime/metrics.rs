@@ -0,0 +1,31 @@
+use crate::metrics::Metrics;
+use crate::state::State;
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_runtime_metrics(
+    state: *const State,
+) -> *mut Vec<i64> {
+    Box::into_raw(Box::new(Metrics::new(&*state).to_vec()))
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_runtime_metrics_size(
+    metrics: *const Vec<i64>,
+) -> i64 {
+    (*metrics).len() as i64
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_runtime_metrics_get(
+    metrics: *const Vec<i64>,
+    index: i64,
+) -> i64 {
+    *(*metrics).get_unchecked(index as usize)
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_runtime_metrics_drop(
+    metrics: *mut Vec<i64>,
+) {
+    drop(Box::from_raw(metrics));
+}
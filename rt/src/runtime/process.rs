@@ -1,14 +1,16 @@
+use crate::arc_without_weak::ArcWithoutWeak;
 use crate::context;
 use crate::mem::{ClassPointer, String as InkoString};
 use crate::process::{
-    Channel, Message, NativeAsyncMethod, OwnedMessage, Process, ProcessPointer,
-    ReceiveResult, RescheduleRights, SendResult, StackFrame,
+    Channel, Message, Monitor, NativeAsyncMethod, OwnedMessage, Process,
+    ProcessPointer, ReceiveResult, RescheduleRights, SendResult, StackFrame,
 };
 use crate::result::Result as InkoResult;
 use crate::runtime::exit;
 use crate::scheduler::process::Action;
 use crate::scheduler::timeouts::Timeout;
 use crate::state::State;
+use crate::trace::Value;
 use std::cmp::max;
 use std::fmt::Write as _;
 use std::str;
@@ -17,12 +19,35 @@ use std::time::Duration;
 /// Terminates the current program with an Inko panic (opposed to a panic
 /// triggered using the `panic!` macro).
 ///
+/// If the process is set to terminate on a panic, only the process is
+/// terminated and the program keeps running.
+///
 /// This function is marked as cold as we expect it to be called rarely, if ever
 /// (in a correct program). This should also ensure any branches leading to this
 /// function are treated as unlikely.
 #[inline(never)]
 #[cold]
-pub(crate) fn panic(process: ProcessPointer, message: &str) -> ! {
+pub(crate) fn panic(mut process: ProcessPointer, message: &str) -> ! {
+    report_panic(process, message);
+
+    if !process.terminates_on_panic() {
+        // There's no real standard across programs for exit codes. Rust uses
+        // 101 so for the sake of "we don't know a better value", we also use
+        // 101.
+        exit(101);
+    }
+
+    // Safety: terminating the process isn't safe while still running on its
+    // stack, so we defer this until we switch back to the thread's stack.
+    unsafe {
+        process.thread().action = Action::Panic(message.to_string());
+        context::switch(process);
+    }
+
+    unreachable!("a terminated process can't be resumed");
+}
+
+fn report_panic(process: ProcessPointer, message: &str) {
     let mut buffer = String::new();
 
     buffer.push_str("Stack trace (the most recent call comes last):");
@@ -48,10 +73,6 @@ pub(crate) fn panic(process: ProcessPointer, message: &str) -> ! {
     );
 
     eprintln!("{}", buffer);
-
-    // There's no real standard across programs for exit codes. Rust uses 101 so
-    // for the sake of "we don't know a better value", we also use 101.
-    exit(101);
 }
 
 #[no_mangle]
@@ -62,14 +83,20 @@ pub unsafe extern "system" fn inko_process_panic(
     panic(process, (*message).as_slice());
 }
 
+#[no_mangle]
+pub unsafe extern "system" fn inko_process_terminate_on_panic(
+    process: ProcessPointer,
+    enable: bool,
+) {
+    process.set_terminate_on_panic(enable);
+}
+
 #[no_mangle]
 pub unsafe extern "system" fn inko_process_new(
     mut process: ProcessPointer,
     class: ClassPointer,
 ) -> ProcessPointer {
-    let stack = process.thread().stacks.alloc();
-
-    Process::alloc(class, stack)
+    process.thread().spawn(class)
 }
 
 #[no_mangle]
@@ -89,6 +116,18 @@ pub unsafe extern "system" fn inko_process_send_message(
 ) {
     let message = OwnedMessage::from_raw(message);
     let state = &*state;
+
+    if let Some(trace) = &sender.thread().trace {
+        trace.instant(
+            "message",
+            "send",
+            vec![
+                ("sender", Value::Pointer(sender.identifier())),
+                ("receiver", Value::Pointer(receiver.identifier())),
+            ],
+        );
+    }
+
     let reschedule = match receiver.send_message(message) {
         RescheduleRights::AcquiredWithTimeout => {
             state.timeout_worker.increase_expired_timeouts();
@@ -96,6 +135,12 @@ pub unsafe extern "system" fn inko_process_send_message(
         }
         RescheduleRights::Acquired => true,
         RescheduleRights::Failed => false,
+        RescheduleRights::Terminated => {
+            // The discarded message held a reference to the receiver, which
+            // we have to release, as the message never runs.
+            Process::release_references(state, sender, receiver, 1);
+            false
+        }
     };
 
     if reschedule {
@@ -311,3 +356,86 @@ pub unsafe extern "system" fn inko_channel_receive_until(
 pub unsafe extern "system" fn inko_channel_drop(channel: *mut Channel) {
     Channel::drop(channel);
 }
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_process_monitor(
+    state: *const State,
+    process: ProcessPointer,
+    target: ProcessPointer,
+) -> *mut ArcWithoutWeak<Monitor> {
+    let monitor = ArcWithoutWeak::new(Monitor::new());
+
+    // If the process already panicked there's nothing to wait for, so we
+    // notify the monitor right away. Nothing is waiting for the new monitor
+    // yet, so there's no process to reschedule.
+    if let Some(message) = target.add_monitor(monitor.clone()) {
+        monitor.notify(process, message);
+    }
+
+    // The caller passes us a reference to the process, which we don't need
+    // any longer.
+    Process::release_references(&*state, process, target, 1);
+    Box::into_raw(Box::new(monitor))
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_monitor_wait(
+    state: *const State,
+    process: ProcessPointer,
+    monitor: *const ArcWithoutWeak<Monitor>,
+) -> *const InkoString {
+    let message = inko_channel_receive(process, &(*monitor).channel);
+
+    monitor_message(state, message)
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_monitor_try_wait(
+    state: *const State,
+    process: ProcessPointer,
+    monitor: *const ArcWithoutWeak<Monitor>,
+) -> InkoResult {
+    let result = inko_channel_try_receive(process, &(*monitor).channel);
+
+    monitor_result(state, result)
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_monitor_wait_until(
+    state: *const State,
+    process: ProcessPointer,
+    monitor: *const ArcWithoutWeak<Monitor>,
+    nanos: u64,
+) -> InkoResult {
+    let result =
+        inko_channel_receive_until(state, process, &(*monitor).channel, nanos);
+
+    monitor_result(state, result)
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_monitor_drop(
+    monitor: *mut ArcWithoutWeak<Monitor>,
+) {
+    drop(Box::from_raw(monitor));
+}
+
+unsafe fn monitor_message(
+    state: *const State,
+    message: *const u8,
+) -> *const InkoString {
+    let message = Box::from_raw(message as *mut String);
+
+    InkoString::alloc((*state).string_class, *message)
+}
+
+unsafe fn monitor_result(
+    state: *const State,
+    result: InkoResult,
+) -> InkoResult {
+    if result == InkoResult::none() {
+        result
+    } else {
+        InkoResult::ok(monitor_message(state, result.value) as _)
+    }
+}
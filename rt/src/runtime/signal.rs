@@ -0,0 +1,58 @@
+use crate::arc_without_weak::ArcWithoutWeak;
+use crate::process::ProcessPointer;
+use crate::result::Result as InkoResult;
+use crate::runtime::process::{
+    inko_channel_receive, inko_channel_receive_until, inko_channel_try_receive,
+};
+use crate::signal::Subscription;
+use crate::state::State;
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_signals_new(
+) -> *mut ArcWithoutWeak<Subscription> {
+    Box::into_raw(Box::new(ArcWithoutWeak::new(Subscription::new())))
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_signals_add(
+    state: *const State,
+    signals: *const ArcWithoutWeak<Subscription>,
+    signal: i64,
+) {
+    (*state).signals.subscribe(signal as i32, &*signals);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_signals_wait(
+    process: ProcessPointer,
+    signals: *const ArcWithoutWeak<Subscription>,
+) -> i64 {
+    inko_channel_receive(process, &(*signals).channel) as i64
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_signals_try_wait(
+    process: ProcessPointer,
+    signals: *const ArcWithoutWeak<Subscription>,
+) -> InkoResult {
+    inko_channel_try_receive(process, &(*signals).channel)
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_signals_wait_until(
+    state: *const State,
+    process: ProcessPointer,
+    signals: *const ArcWithoutWeak<Subscription>,
+    nanos: u64,
+) -> InkoResult {
+    inko_channel_receive_until(state, process, &(*signals).channel, nanos)
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_signals_drop(
+    state: *const State,
+    signals: *mut ArcWithoutWeak<Subscription>,
+) {
+    (*state).signals.unsubscribe(&*signals);
+    drop(Box::from_raw(signals));
+}
@@ -471,6 +471,10 @@ pub unsafe extern "system" fn inko_socket_try_clone(
 }
 
 #[no_mangle]
-pub unsafe extern "system" fn inko_socket_drop(socket: *mut Socket) {
+pub unsafe extern "system" fn inko_socket_drop(
+    state: *const State,
+    socket: *mut Socket,
+) {
+    (*socket).close(&*state);
     drop_in_place(socket);
 }
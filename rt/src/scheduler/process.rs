@@ -1,10 +1,13 @@
 //! Scheduling and execution of lightweight Inko processes.
 use crate::arc_without_weak::ArcWithoutWeak;
 use crate::context;
-use crate::process::{Process, ProcessPointer, Task};
+use crate::mem::ClassPointer;
+use crate::process::{Process, ProcessPointer, SendResult, Task, Terminated};
 use crate::scheduler::{number_of_cores, pin_thread_to_core};
-use crate::stack::StackPool;
+use crate::stack::{Stack, StackPool};
+use crate::stack_overflow::{self, SignalStack};
 use crate::state::State;
+use crate::trace::{self, Buffer as TraceBuffer, Value};
 use crossbeam_queue::ArrayQueue;
 use crossbeam_utils::atomic::AtomicCell;
 use crossbeam_utils::thread::scope;
@@ -14,7 +17,9 @@ use std::cmp::min;
 use std::collections::VecDeque;
 use std::mem::{size_of, swap};
 use std::ops::Drop;
-use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU64, Ordering};
+use std::sync::atomic::{
+    AtomicBool, AtomicU16, AtomicU64, AtomicUsize, Ordering,
+};
 use std::sync::{Condvar, Mutex};
 use std::time::{Duration, Instant};
 
@@ -46,6 +51,10 @@ pub(crate) enum Action {
 
     /// The thread should terminate the process.
     Terminate,
+
+    /// The process panicked and should be terminated, without terminating the
+    /// program.
+    Panic(String),
 }
 
 impl Action {
@@ -174,6 +183,9 @@ pub struct Thread {
 
     /// The amount of reductions left before a process needs to be suspended.
     pub(crate) reductions: u16,
+
+    /// The buffer to record scheduler events in, if tracing is enabled.
+    pub(crate) trace: Option<TraceBuffer>,
 }
 
 impl Thread {
@@ -192,11 +204,14 @@ impl Thread {
             stacks: StackPool::new(pool.stack_size),
             action: Action::Ignore,
             reductions: REDUCTIONS,
+            trace: None,
             pool,
         }
     }
 
     fn backup(network_poller: usize, pool: ArcWithoutWeak<Pool>) -> Thread {
+        pool.backup.fetch_add(1, Ordering::Relaxed);
+
         Self {
             // For backup threads the ID/queue doesn't matter, because we won't
             // use them until we're turned into a regular thread.
@@ -209,6 +224,7 @@ impl Thread {
             stacks: StackPool::new(pool.stack_size),
             action: Action::Ignore,
             reductions: REDUCTIONS,
+            trace: None,
             pool,
         }
     }
@@ -244,6 +260,11 @@ impl Thread {
         let epoch = self.pool.current_epoch();
         let shared = &self.pool.threads[self.id];
 
+        if let Some(trace) = &self.trace {
+            trace.begin("thread", "blocking");
+        }
+
+        self.pool.blocking.fetch_add(1, Ordering::Relaxed);
         self.blocked_at = epoch;
         shared.blocked_at.store(epoch, Ordering::Release);
 
@@ -282,9 +303,15 @@ impl Thread {
             // The monitor thread determined we took too long and we have to
             // become a backup thread.
             self.backup = true;
+            self.pool.backup.fetch_add(1, Ordering::Relaxed);
         }
 
+        self.pool.blocking.fetch_sub(1, Ordering::Relaxed);
         self.blocked_at = NOT_BLOCKING;
+
+        if let Some(trace) = &self.trace {
+            trace.end("thread", "blocking");
+        }
     }
 
     pub(crate) fn blocking<F, R>(
@@ -338,11 +365,17 @@ impl Thread {
     }
 
     fn run(&mut self, state: &State) {
+        // This is needed so we can report processes overflowing their stacks.
+        let _signal_stack = SignalStack::new();
+
+        self.trace = trace::buffer();
+
         while self.pool.is_alive() {
             if self.backup {
                 let mut blocked = self.pool.blocked_threads.lock().unwrap();
 
                 if let Some(id) = blocked.pop_front() {
+                    self.pool.backup.fetch_sub(1, Ordering::Relaxed);
                     self.backup = false;
                     self.id = id;
                     self.work = self.pool.threads[id].queue.clone();
@@ -374,7 +407,7 @@ impl Thread {
             // prevent the stack pool from ballooning in size. If we did this
             // before going to sleep then in an active system we may never end
             // up shrinking the stack pool.
-            self.stacks.shrink();
+            self.shrink_stacks();
 
             if let Some(process) = self.steal_from_global() {
                 self.run_process(state, process);
@@ -481,6 +514,63 @@ impl Thread {
         self.pool.sleeping.fetch_sub(1, Ordering::AcqRel);
     }
 
+    /// Allocates a new process, reusing a stack from the stack pool if
+    /// possible.
+    pub(crate) fn spawn(&mut self, class: ClassPointer) -> ProcessPointer {
+        let stack = self.allocate_stack();
+
+        self.pool.processes.fetch_add(1, Ordering::Relaxed);
+        Process::alloc(class, stack)
+    }
+
+    fn allocate_stack(&mut self) -> Stack {
+        if !self.stacks.is_empty() {
+            self.pool.stacks.fetch_sub(1, Ordering::Relaxed);
+        }
+
+        self.stacks.alloc()
+    }
+
+    fn release_stack(&mut self, mut process: ProcessPointer) {
+        if let Some(stack) = process.take_stack() {
+            self.stacks.add(stack);
+            self.pool.stacks.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Releases the resources of a process that won't run again, except for
+    /// the memory of the process itself.
+    fn terminated(&mut self, process: ProcessPointer) {
+        self.release_stack(process);
+        self.pool.processes.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    fn shrink_stacks(&mut self) {
+        let removed = self.stacks.shrink();
+
+        self.pool.stacks.fetch_sub(removed, Ordering::Relaxed);
+    }
+
+    /// Returns a description of what a process did after yielding control
+    /// back to the thread.
+    fn run_result(&self, process: ProcessPointer) -> &'static str {
+        match self.action {
+            Action::Terminate => "terminate",
+            Action::Panic(_) => "panic",
+            Action::Ignore => {
+                let state = process.state();
+
+                if !state.is_running() {
+                    "finish"
+                } else if state.is_waiting() {
+                    "suspend"
+                } else {
+                    "yield"
+                }
+            }
+        }
+    }
+
     /// Runs a process by calling back into the native code.
     fn run_process(&mut self, state: &State, mut process: ProcessPointer) {
         {
@@ -496,20 +586,50 @@ impl Thread {
             // automatically when we decide what action to take in response to
             // the yield.
             let _lock = process.acquire_run_lock();
+            let started = Instant::now();
 
             match process.next_task() {
                 Task::Resume => {
                     process.set_thread(self);
+                    stack_overflow::set_current(Some(process));
                     unsafe { context::switch(process) }
                 }
                 Task::Start(func, args) => {
+                    if let Some(trace) = &self.trace {
+                        trace.instant(
+                            "message",
+                            "receive",
+                            vec![(
+                                "process",
+                                Value::Pointer(process.identifier()),
+                            )],
+                        );
+                    }
+
+                    // A process that panicked gives up its stack, but needs a
+                    // new one to run its dropper.
+                    if !process.has_stack() {
+                        process.set_stack(self.allocate_stack());
+                    }
+
                     process.set_thread(self);
+                    stack_overflow::set_current(Some(process));
                     unsafe { context::start(state, process, func, args) }
                 }
                 Task::Wait => return,
             }
 
+            stack_overflow::set_current(None);
             process.unset_thread();
+
+            if let Some(trace) = &self.trace {
+                trace.complete(
+                    "run",
+                    self.run_result(process),
+                    started,
+                    vec![("process", Value::Pointer(process.identifier()))],
+                );
+            }
         }
 
         self.reductions = REDUCTIONS;
@@ -523,9 +643,7 @@ impl Thread {
                     state.terminate();
                 }
 
-                if let Some(stack) = process.take_stack() {
-                    self.stacks.add(stack);
-                }
+                self.terminated(process);
 
                 // Processes drop/free themselves as this must be deferred until
                 // all messages (including any destructors) have finished
@@ -533,6 +651,48 @@ impl Thread {
                 // memory of a process while still using it.
                 Process::drop_and_deallocate(process);
             }
+            Action::Panic(message) => {
+                // The process won't run any regular messages again, so we can
+                // reuse its stack.
+                let monitors = process.terminate_after_panic(&message);
+
+                self.release_stack(process);
+
+                for monitor in monitors {
+                    match monitor.notify(process, message.clone()) {
+                        SendResult::Reschedule(receiver) => {
+                            self.schedule(receiver);
+                        }
+                        SendResult::RescheduleWithTimeout(receiver) => {
+                            state.timeout_worker.increase_expired_timeouts();
+                            self.schedule(receiver);
+                        }
+                        SendResult::Sent | SendResult::Full => {}
+                    }
+                }
+
+                // Other processes may still hold references to the process, in
+                // which case we can't release its memory until the last
+                // reference is dropped and the process runs its dropper.
+                match process.finish_after_panic() {
+                    Terminated::Release(references) => {
+                        // If we release the last reference, the process sends
+                        // itself the message that drops it, which in turn
+                        // requires access to the current thread.
+                        process.set_thread(self);
+
+                        unsafe {
+                            Process::release_references(
+                                state, process, process, references,
+                            );
+                        }
+                    }
+                    Terminated::Deallocate => {
+                        self.terminated(process);
+                        Process::drop_and_deallocate(process);
+                    }
+                }
+            }
             Action::Ignore => {
                 // In this case it's up to the process (or another process) to
                 // reschedule the process we just finished running.
@@ -543,6 +703,8 @@ impl Thread {
 
 impl Drop for Thread {
     fn drop(&mut self) {
+        self.pool.stacks.fetch_sub(self.stacks.len(), Ordering::Relaxed);
+
         while let Some(process) = self.work.pop() {
             Process::drop_and_deallocate(process);
         }
@@ -788,6 +950,18 @@ struct Pool {
 
     /// The size of each stack to allocate for a process.
     stack_size: usize,
+
+    /// The number of processes that are alive.
+    processes: AtomicUsize,
+
+    /// The number of threads performing a blocking operation.
+    blocking: AtomicUsize,
+
+    /// The number of backup threads waiting to replace a blocking thread.
+    backup: AtomicUsize,
+
+    /// The number of reusable stacks, across all threads.
+    stacks: AtomicUsize,
 }
 
 impl Pool {
@@ -880,6 +1054,10 @@ impl Scheduler {
                 cvar: Condvar::new(),
             },
             stack_size,
+            processes: AtomicUsize::new(0),
+            blocking: AtomicUsize::new(0),
+            backup: AtomicUsize::new(0),
+            stacks: AtomicUsize::new(0),
         });
 
         Self { primary: size, backup, pool: shared }
@@ -893,6 +1071,37 @@ impl Scheduler {
         self.pool.schedule_multiple(processes);
     }
 
+    /// Returns the number of processes that are alive.
+    pub(crate) fn processes(&self) -> usize {
+        self.pool.processes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of processes in the local queue of each thread.
+    pub(crate) fn local_queue_sizes(&self) -> Vec<usize> {
+        self.pool.threads.iter().map(|t| t.queue.len()).collect()
+    }
+
+    /// Returns the number of processes in the global queue.
+    pub(crate) fn global_queue_size(&self) -> usize {
+        self.pool.global.lock().unwrap().len()
+    }
+
+    /// Returns the number of threads performing a blocking operation.
+    pub(crate) fn blocking_threads(&self) -> usize {
+        self.pool.blocking.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of backup threads waiting to replace a blocking
+    /// thread.
+    pub(crate) fn backup_threads(&self) -> usize {
+        self.pool.backup.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of reusable stacks.
+    pub(crate) fn pooled_stacks(&self) -> usize {
+        self.pool.stacks.load(Ordering::Relaxed)
+    }
+
     pub(crate) fn terminate(&self) {
         let _global = self.pool.global.lock().unwrap();
         let _blocked = self.pool.blocked_threads.lock().unwrap();
@@ -945,6 +1154,7 @@ impl Scheduler {
                     .unwrap();
             }
 
+            self.pool.processes.fetch_add(1, Ordering::Relaxed);
             self.pool.schedule(process);
         });
     }
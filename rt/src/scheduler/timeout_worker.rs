@@ -4,6 +4,7 @@ use crate::process::ProcessPointer;
 use crate::scheduler::process::Scheduler;
 use crate::scheduler::timeouts::{Timeout, Timeouts};
 use crate::state::State;
+use crate::trace::{self, Buffer as TraceBuffer, Value};
 use std::cell::UnsafeCell;
 use std::collections::VecDeque;
 use std::mem::size_of;
@@ -34,6 +35,9 @@ struct Inner {
 
     /// The messages to process.
     queue: VecDeque<Message>,
+
+    /// The buffer to record expired timeouts in, if tracing is enabled.
+    trace: Option<TraceBuffer>,
 }
 
 /// A TimeoutWorker is tasked with rescheduling processes when their timeouts
@@ -68,6 +72,12 @@ pub(crate) struct TimeoutWorker {
     /// The number of timeouts that have been invalidated by sending a message
     /// to the process, before the timeout expired.
     expired: AtomicUsize,
+
+    /// The number of timeouts in the heap of timeouts.
+    ///
+    /// The heap is only accessible by the worker thread, so the worker updates
+    /// this value after every iteration.
+    timeouts: AtomicUsize,
 }
 
 unsafe impl Sync for TimeoutWorker {}
@@ -77,19 +87,33 @@ impl TimeoutWorker {
         TimeoutWorker {
             inner: UnsafeCell::new(Inner {
                 timeouts: Timeouts::new(),
+                trace: None,
                 queue: VecDeque::with_capacity(QUEUE_START_CAPACITY),
             }),
             queue: Mutex::new(VecDeque::with_capacity(QUEUE_START_CAPACITY)),
             cvar: Condvar::new(),
             expired: AtomicUsize::new(0),
+            timeouts: AtomicUsize::new(0),
         }
     }
 
+    /// Returns the number of processes suspended with a timeout that hasn't
+    /// expired or been invalidated.
+    pub(crate) fn len(&self) -> usize {
+        let queued = self.queue.lock().unwrap().len();
+        let timeouts = self.timeouts.load(Ordering::Acquire);
+        let expired = self.expired.load(Ordering::Acquire);
+
+        timeouts.saturating_sub(expired) + queued
+    }
+
     pub(crate) fn increase_expired_timeouts(&self) {
         self.expired.fetch_add(1, Ordering::AcqRel);
     }
 
     pub(crate) fn run(&self, state: &State) {
+        self.inner_mut().trace = trace::buffer();
+
         while state.scheduler.is_alive() {
             let timeout = self.run_iteration(state);
 
@@ -147,7 +171,16 @@ impl TimeoutWorker {
         let (expired, time_until_expiration) =
             inner.timeouts.processes_to_reschedule(state);
 
+        if let (Some(trace), false) = (&inner.trace, expired.is_empty()) {
+            trace.instant(
+                "timeout",
+                "expired",
+                vec![("processes", Value::Int(expired.len() as _))],
+            );
+        }
+
         state.scheduler.schedule_multiple(expired);
+        self.timeouts.store(inner.timeouts.len(), Ordering::Release);
         time_until_expiration
     }
 
@@ -214,6 +247,30 @@ mod tests {
         assert!(!worker.queue.lock().unwrap().is_empty());
     }
 
+    #[test]
+    fn test_len() {
+        let state = setup();
+        let class = empty_process_class("A");
+        let process = Process::alloc(*class, Stack::new(1024));
+        let worker = TimeoutWorker::new();
+        let timeout = Timeout::duration(&state, Duration::from_secs(10));
+
+        assert_eq!(worker.len(), 0);
+
+        process.state().waiting_for_channel(Some(timeout.clone()));
+        worker.suspend(process, timeout);
+
+        assert_eq!(worker.len(), 1);
+
+        worker.run_iteration(&state);
+
+        assert_eq!(worker.len(), 1);
+
+        worker.increase_expired_timeouts();
+
+        assert_eq!(worker.len(), 0);
+    }
+
     #[test]
     fn test_increase_expired_timeouts() {
         let worker = TimeoutWorker::new();
@@ -0,0 +1,342 @@
+//! Forwarding Unix signals to Inko processes.
+//!
+//! Upon startup we install a handler for the signals Inko processes can
+//! subscribe to. This handler writes the signal number to a pipe, which is read
+//! by a dedicated thread. This thread then sends the signal to every
+//! subscription for that signal.
+//!
+//! If no process subscribed to the signal, the thread restores the signal
+//! handler that was in place before installing ours, and raises the signal
+//! again. This way signals such as SIGINT still terminate the program when no
+//! process is interested in them.
+//!
+//! Signals ignored when the program starts (e.g. SIGHUP when using `nohup`)
+//! remain ignored, and thus can't be received by Inko processes.
+//!
+//! We use a signal handler instead of blocking the signals and using
+//! `sigwait()`, as child processes inherit the signal mask, but not the signal
+//! handlers.
+use crate::arc_without_weak::ArcWithoutWeak;
+use crate::process::{Channel, SendResult};
+use crate::state::State;
+use libc::{
+    c_int, c_void, fcntl, pipe, read, sigaction, sigemptyset, write,
+    FD_CLOEXEC, F_GETFL, F_SETFD, F_SETFL, O_NONBLOCK, SA_ONSTACK, SA_RESTART,
+    SIG_IGN,
+};
+use std::mem::zeroed;
+use std::ptr::{null, null_mut};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Mutex;
+
+#[cfg(target_os = "linux")]
+use libc::__errno_location as errno_location;
+
+#[cfg(any(target_os = "macos", target_os = "freebsd"))]
+use libc::__error as errno_location;
+
+/// The file descriptor the signal handler writes signals to.
+static WRITE_FD: AtomicI32 = AtomicI32::new(-1);
+
+/// The file descriptor the signal thread reads signals from.
+static READ_FD: AtomicI32 = AtomicI32::new(-1);
+
+/// The signal actions that were in place before installing our handler.
+static PREVIOUS: Mutex<Vec<(i32, sigaction)>> = Mutex::new(Vec::new());
+
+/// The signals Inko processes can subscribe to.
+const SIGNALS: [i32; 3] = [libc::SIGHUP, libc::SIGINT, libc::SIGTERM];
+
+/// The number of signals a subscription can buffer.
+///
+/// Signals received while the buffer is full are discarded. Unix signals of the
+/// same type are merged when pending, so there's no point in buffering many of
+/// them.
+const CAPACITY: usize = 8;
+
+/// A channel that one or more signals are sent to.
+///
+/// Signals are sent as their numbers, instead of pointers to heap allocated
+/// values, so there's no need to release any pending messages.
+pub(crate) struct Subscription {
+    pub(crate) channel: Channel,
+}
+
+impl Subscription {
+    pub(crate) fn new() -> Subscription {
+        Subscription { channel: Channel::new(CAPACITY) }
+    }
+}
+
+/// The subscriptions of all processes.
+pub(crate) struct Signals {
+    subscriptions: Mutex<Vec<(i32, ArcWithoutWeak<Subscription>)>>,
+}
+
+impl Signals {
+    pub(crate) fn new() -> Signals {
+        Signals { subscriptions: Mutex::new(Vec::new()) }
+    }
+
+    /// Subscribes to the given signal.
+    ///
+    /// If the signal isn't supported, this method does nothing.
+    pub(crate) fn subscribe(
+        &self,
+        signal: i32,
+        subscription: &ArcWithoutWeak<Subscription>,
+    ) {
+        if !SIGNALS.contains(&signal) {
+            return;
+        }
+
+        let mut subs = self.subscriptions.lock().unwrap();
+        let exists = subs.iter().any(|(sig, sub)| {
+            *sig == signal && sub.as_ptr() == subscription.as_ptr()
+        });
+
+        if !exists {
+            subs.push((signal, subscription.clone()));
+        }
+    }
+
+    /// Removes all signals the subscription is subscribed to.
+    pub(crate) fn unsubscribe(
+        &self,
+        subscription: &ArcWithoutWeak<Subscription>,
+    ) {
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .retain(|(_, sub)| sub.as_ptr() != subscription.as_ptr());
+    }
+
+    fn subscriptions(&self, signal: i32) -> Vec<ArcWithoutWeak<Subscription>> {
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(sig, _)| *sig == signal)
+            .map(|(_, sub)| sub.clone())
+            .collect()
+    }
+}
+
+/// Installs the handler for the signals Inko processes can subscribe to.
+pub(crate) fn install() {
+    let mut fds = [-1, -1];
+
+    unsafe {
+        if pipe(fds.as_mut_ptr()) != 0 {
+            panic!("Failed to create the pipe for handling signals");
+        }
+
+        // The handler must never block, even if the signal thread can't keep
+        // up. In that case we just discard the signals.
+        fcntl(fds[1], F_SETFL, fcntl(fds[1], F_GETFL) | O_NONBLOCK);
+
+        for fd in fds {
+            fcntl(fd, F_SETFD, FD_CLOEXEC);
+        }
+    }
+
+    READ_FD.store(fds[0], Ordering::Release);
+    WRITE_FD.store(fds[1], Ordering::Release);
+
+    for signal in SIGNALS {
+        install_handler(signal);
+    }
+}
+
+/// Installs the signal handler for a single signal, unless the signal is
+/// ignored.
+fn install_handler(signal: i32) {
+    unsafe {
+        let mut previous: sigaction = zeroed();
+
+        sigaction(signal, null(), &mut previous);
+
+        // Signals ignored by the parent process must stay ignored, otherwise
+        // programs started using e.g. `nohup` are terminated by a SIGHUP.
+        if previous.sa_sigaction == SIG_IGN {
+            return;
+        }
+
+        let mut action: sigaction = zeroed();
+
+        action.sa_sigaction = handler as *const () as usize;
+        action.sa_flags = SA_RESTART | SA_ONSTACK;
+        sigemptyset(&mut action.sa_mask);
+        sigaction(signal, &action, null_mut());
+
+        let mut actions = PREVIOUS.lock().unwrap();
+
+        actions.retain(|(sig, _)| *sig != signal);
+        actions.push((signal, previous));
+    }
+}
+
+extern "C" fn handler(signal: c_int) {
+    let fd = WRITE_FD.load(Ordering::Acquire);
+    let byte = signal as u8;
+
+    // Only async-signal-safe functions can be used here, so all we do is write
+    // the signal to the pipe. If the pipe is full the signal is discarded.
+    //
+    // The write may change errno, so we restore it to ensure the code we
+    // interrupted doesn't observe a different value.
+    unsafe {
+        let errno = *errno_location();
+
+        write(fd, &byte as *const u8 as *const c_void, 1);
+        *errno_location() = errno;
+    }
+}
+
+/// Sends a signal to all its subscriptions, returning `false` if there are no
+/// subscriptions.
+fn forward(state: &State, signal: i32) -> bool {
+    let subs = state.signals.subscriptions(signal);
+    let mut reschedule = Vec::new();
+
+    for sub in &subs {
+        match sub.channel.try_send(signal as usize as _) {
+            SendResult::Reschedule(receiver) => reschedule.push(receiver),
+            SendResult::RescheduleWithTimeout(receiver) => {
+                state.timeout_worker.increase_expired_timeouts();
+                reschedule.push(receiver);
+            }
+            SendResult::Sent | SendResult::Full => {}
+        }
+    }
+
+    state.scheduler.schedule_multiple(reschedule);
+    !subs.is_empty()
+}
+
+/// Raises a signal using the handler that was in place before installing ours.
+fn raise_default(signal: i32) {
+    let previous = PREVIOUS
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(sig, _)| *sig == signal)
+        .map(|(_, action)| *action);
+
+    // We only receive signals we installed our handler for, so the previous
+    // action is always present.
+    if let Some(action) = previous {
+        unsafe {
+            sigaction(signal, &action, null_mut());
+            libc::raise(signal);
+        }
+    }
+
+    // If the signal didn't terminate the program, we have to handle it again
+    // when it's received in the future.
+    install_handler(signal);
+}
+
+/// Waits for signals and forwards them to the processes that subscribed to
+/// them.
+pub(crate) fn run(state: &State) {
+    let fd = READ_FD.load(Ordering::Acquire);
+    let mut byte = 0_u8;
+
+    while state.scheduler.is_alive() {
+        let len = unsafe { read(fd, &mut byte as *mut u8 as *mut c_void, 1) };
+
+        if len != 1 {
+            continue;
+        }
+
+        let signal = byte as i32;
+
+        if !forward(state, signal) {
+            raise_default(signal);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::ReceiveResult;
+    use crate::test::setup;
+
+    #[test]
+    fn test_signals_subscribe() {
+        let signals = Signals::new();
+        let sub = ArcWithoutWeak::new(Subscription::new());
+
+        signals.subscribe(libc::SIGTERM, &sub);
+        signals.subscribe(libc::SIGTERM, &sub);
+        signals.subscribe(libc::SIGINT, &sub);
+        signals.subscribe(libc::SIGUSR1, &sub);
+
+        assert_eq!(signals.subscriptions(libc::SIGTERM).len(), 1);
+        assert_eq!(signals.subscriptions(libc::SIGINT).len(), 1);
+        assert_eq!(signals.subscriptions(libc::SIGHUP).len(), 0);
+        assert_eq!(signals.subscriptions(libc::SIGUSR1).len(), 0);
+    }
+
+    #[test]
+    fn test_signals_unsubscribe() {
+        let signals = Signals::new();
+        let sub1 = ArcWithoutWeak::new(Subscription::new());
+        let sub2 = ArcWithoutWeak::new(Subscription::new());
+
+        signals.subscribe(libc::SIGTERM, &sub1);
+        signals.subscribe(libc::SIGINT, &sub1);
+        signals.subscribe(libc::SIGTERM, &sub2);
+        signals.unsubscribe(&sub1);
+
+        assert_eq!(signals.subscriptions(libc::SIGTERM).len(), 1);
+        assert_eq!(signals.subscriptions(libc::SIGINT).len(), 0);
+    }
+
+    #[test]
+    fn test_install_handler() {
+        unsafe {
+            let mut current: sigaction = zeroed();
+
+            libc::signal(libc::SIGHUP, SIG_IGN);
+            install_handler(libc::SIGHUP);
+            sigaction(libc::SIGHUP, null(), &mut current);
+
+            assert_eq!(current.sa_sigaction, SIG_IGN);
+
+            libc::signal(libc::SIGHUP, libc::SIG_DFL);
+            install_handler(libc::SIGHUP);
+            sigaction(libc::SIGHUP, null(), &mut current);
+
+            assert_eq!(current.sa_sigaction, handler as *const () as usize);
+
+            let previous = PREVIOUS
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(sig, _)| *sig == libc::SIGHUP)
+                .map(|(_, action)| action.sa_sigaction);
+
+            assert_eq!(previous, Some(libc::SIG_DFL));
+            libc::signal(libc::SIGHUP, libc::SIG_DFL);
+        }
+    }
+
+    #[test]
+    fn test_forward() {
+        let state = setup();
+        let sub = ArcWithoutWeak::new(Subscription::new());
+
+        assert!(!forward(&state, libc::SIGTERM));
+
+        state.signals.subscribe(libc::SIGTERM, &sub);
+
+        assert!(forward(&state, libc::SIGTERM));
+        assert_eq!(
+            sub.channel.try_receive(),
+            ReceiveResult::Some(libc::SIGTERM as usize as _)
+        );
+    }
+}
@@ -253,8 +253,22 @@ impl Socket {
     }
 
     pub(crate) fn deregister(&mut self, state: &State) {
-        let poller_id = self.registered.load(Ordering::Acquire) as usize;
-        let _ = state.network_pollers[poller_id].delete(&self.inner);
+        let poller_id = self.registered.swap(NOT_REGISTERED, Ordering::AcqRel);
+
+        if poller_id != NOT_REGISTERED {
+            let _ =
+                state.network_pollers[poller_id as usize].delete(&self.inner);
+        }
+    }
+
+    /// Updates the statistics of the network poller the socket is registered
+    /// with, if any, as the socket is about to be closed.
+    pub(crate) fn close(&self, state: &State) {
+        let poller_id = self.registered.load(Ordering::Acquire);
+
+        if poller_id != NOT_REGISTERED {
+            state.network_pollers[poller_id as usize].closed();
+        }
     }
 
     pub(crate) fn accept(&self) -> io::Result<Self> {
@@ -1,6 +1,7 @@
 use crate::memory_map::MemoryMap;
 use crate::page::page_size;
 use std::collections::VecDeque;
+use std::ops::Range;
 
 /// The age of a reusable stack after which we deem it too old to keep around.
 ///
@@ -83,6 +84,14 @@ impl StackPool {
         }
     }
 
+    pub(crate) fn len(&self) -> usize {
+        self.stacks.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.stacks.is_empty()
+    }
+
     pub(crate) fn add(&mut self, stack: Stack) {
         self.stacks.push_back(stack);
         self.epochs.push_back(self.epoch);
@@ -94,9 +103,11 @@ impl StackPool {
     /// Using this method we can keep the number of unused stacks under control.
     /// For example, if we suddenly need many stacks but then never reuse most
     /// of them, this is a waste of memory.
-    pub(crate) fn shrink(&mut self) {
+    ///
+    /// The return value is the number of stacks removed.
+    pub(crate) fn shrink(&mut self) -> usize {
         if self.stacks.len() < MIN_STACKS {
-            return;
+            return 0;
         }
 
         let trim_size = self
@@ -115,6 +126,7 @@ impl StackPool {
         // Update the epochs of the remaining stacks so we don't shrink too soon
         // again.
         self.epochs = VecDeque::from(vec![self.epoch; self.epochs.len()]);
+        max
     }
 }
 
@@ -145,6 +157,26 @@ impl Stack {
     pub(crate) fn stack_pointer(&self) -> *mut u8 {
         unsafe { self.mem.ptr.add(self.mem.len - page_size()) }
     }
+
+    /// Returns the address range of the guard page that a process writes to
+    /// when it overflows the stack.
+    ///
+    /// Stacks grow downwards, so this is the guard page at the start of the
+    /// memory region.
+    pub(crate) fn guard_page(&self) -> Range<usize> {
+        let start = self.mem.ptr as usize;
+
+        start..start + page_size()
+    }
+
+    /// Returns the address range of the memory usable by a process, excluding
+    /// the guard pages.
+    pub(crate) fn usable(&self) -> Range<usize> {
+        let page = page_size();
+        let start = self.mem.ptr as usize;
+
+        start + page..start + self.mem.len - page
+    }
 }
 
 #[cfg(test)]
@@ -160,6 +192,29 @@ mod tests {
         assert_eq!(stack.mem.len, size * 3);
     }
 
+    #[test]
+    fn test_stack_guard_page() {
+        let size = page_size();
+        let stack = Stack::new(size);
+        let start = stack.mem.ptr as usize;
+
+        assert_eq!(stack.guard_page(), start..start + size);
+        assert!(!stack
+            .guard_page()
+            .contains(&(stack.stack_pointer() as usize)));
+    }
+
+    #[test]
+    fn test_stack_usable() {
+        let size = page_size();
+        let stack = Stack::new(size);
+        let start = stack.mem.ptr as usize;
+
+        assert_eq!(stack.usable(), start + size..start + size + size);
+        assert!(!stack.usable().contains(&stack.guard_page().start));
+        assert!(!stack.usable().contains(&(stack.stack_pointer() as usize)));
+    }
+
     #[test]
     fn test_stack_pool_alloc_with_reuse() {
         let size = page_size();
@@ -195,7 +250,7 @@ mod tests {
         pool.epochs[1] = 2;
 
         // Not enough stacks, so no shrinking is performed.
-        pool.shrink();
+        assert_eq!(pool.shrink(), 0);
 
         pool.add(Stack::new(size));
         pool.add(Stack::new(size));
@@ -208,13 +263,13 @@ mod tests {
         pool.epochs[5] = 12;
 
         // This shrinks the pool.
-        pool.shrink();
+        assert_eq!(pool.shrink(), 3);
 
         // This doesn't shrink the pool because we updated the epochs to prevent
         // excessive shrinking.
-        pool.shrink();
+        assert_eq!(pool.shrink(), 0);
 
-        assert_eq!(pool.stacks.len(), 3);
+        assert_eq!(pool.len(), 3);
         assert_eq!(&pool.epochs, &[14, 14, 14]);
     }
 }
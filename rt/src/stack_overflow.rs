@@ -0,0 +1,238 @@
+//! Reporting of processes that overflow their stacks.
+//!
+//! Process stacks are protected by guard pages, and a process that recurses too
+//! deeply ends up writing to the guard page at the end of its stack. This
+//! results in a SIGSEGV or SIGBUS signal, which by default terminates the
+//! program without any information about what went wrong.
+//!
+//! To make such errors easier to debug, we install a handler for these signals.
+//! If the fault is the result of a process overflowing its stack, the handler
+//! writes a message about the process to STDERR and terminates the program with
+//! exit status 101, the same status used for panics. If the fault has a
+//! different cause, the default handler is restored and the faulting
+//! instruction is retried, terminating the program as usual.
+//!
+//! The fault may occur while the process is in the middle of a runtime call
+//! that holds a lock, such as when allocating memory. For this reason the
+//! handler doesn't allocate memory or acquire any locks of the runtime: the
+//! message is formatted into a fixed-size buffer, written using `write()`, and
+//! the program is terminated using `_exit()`. This means the program terminates
+//! without running any exit hooks, such as those that write coverage data or
+//! traces.
+//!
+//! The message includes a stack trace of the most recent frames of the process.
+//! Resolving symbols requires allocating memory, so the stack trace only
+//! contains the addresses of the frames. These can be resolved using tools such
+//! as `addr2line`.
+//!
+//! The handler runs on an alternative stack, as the stack of the process is
+//! full. Each thread that runs processes needs its own alternative stack,
+//! created using `SignalStack::new`.
+use crate::memory_map::MemoryMap;
+use crate::process::ProcessPointer;
+use libc::{
+    _exit, c_int, c_void, sigaction, sigaltstack, sigemptyset, siginfo_t,
+    stack_t, write, SA_ONSTACK, SA_SIGINFO, SIGBUS, SIGSEGV, SIG_DFL,
+    SS_DISABLE, STDERR_FILENO,
+};
+use std::cell::Cell;
+use std::mem::zeroed;
+use std::ptr::null_mut;
+
+/// The size of the stack to run the signal handler on.
+///
+/// The handler itself needs very little stack space, but `sigaltstack()`
+/// requires a minimum size that differs per platform, so we use a size that's
+/// comfortably larger than these minimums.
+const SIGNAL_STACK_SIZE: usize = 64 * 1024;
+
+/// The maximum size of the message written when a stack overflow is detected.
+const MESSAGE_SIZE: usize = 2048;
+
+/// The maximum number of frames to include in the stack trace.
+const MAX_FRAMES: usize = 32;
+
+thread_local! {
+    /// The process that's running on the current thread.
+    static CURRENT: Cell<Option<ProcessPointer>> = const { Cell::new(None) };
+}
+
+/// Sets the process that's running on the current thread.
+pub(crate) fn set_current(process: Option<ProcessPointer>) {
+    CURRENT.with(|current| current.set(process));
+}
+
+/// Installs the signal handler for detecting stack overflows.
+pub(crate) fn install() {
+    unsafe {
+        let mut action: sigaction = zeroed();
+
+        action.sa_sigaction = handler as *const () as usize;
+        action.sa_flags = SA_SIGINFO | SA_ONSTACK;
+        sigemptyset(&mut action.sa_mask);
+        sigaction(SIGSEGV, &action, null_mut());
+        sigaction(SIGBUS, &action, null_mut());
+    }
+}
+
+extern "C" fn handler(signal: c_int, info: *mut siginfo_t, _: *mut c_void) {
+    let address = unsafe { (*info).si_addr() } as usize;
+
+    if let Some(process) = CURRENT.with(|current| current.get()) {
+        if !process.stack_pointer.is_null()
+            && process.stack.guard_page().contains(&address)
+        {
+            let mut frames = [0_usize; MAX_FRAMES];
+            let mut len = 0;
+            let stack = process.stack.usable();
+
+            // Frames of the signal handler run on the alternative stack, so we
+            // skip frames until we reach those on the stack of the process.
+            unsafe {
+                backtrace::trace_unsynchronized(|frame| {
+                    if stack.contains(&(frame.sp() as usize)) {
+                        frames[len] = frame.ip() as usize;
+                        len += 1;
+                    }
+
+                    len < MAX_FRAMES
+                });
+            }
+
+            let mut message = Message::new();
+
+            message.push(b"Stack trace (the most recent call comes last):");
+
+            if len == MAX_FRAMES {
+                message.push(b"\n  ...");
+            }
+
+            for &address in frames[..len].iter().rev() {
+                message.push(b"\n  ");
+                message.push_hex(address);
+            }
+
+            message.push(b"\nProcess '");
+            message.push(process.header.class.name.as_bytes());
+            message.push(b"' (");
+            message.push_hex(process.identifier());
+            message.push(b") panicked: stack overflow\n");
+
+            unsafe {
+                write(STDERR_FILENO, message.buffer.as_ptr() as _, message.len);
+                _exit(101);
+            }
+        }
+    }
+
+    // The fault isn't caused by a stack overflow, so we restore the default
+    // handler. Upon returning the instruction is retried, and the signal is
+    // handled by the default handler.
+    unsafe {
+        let mut action: sigaction = zeroed();
+
+        action.sa_sigaction = SIG_DFL;
+        sigaction(signal, &action, null_mut());
+    }
+}
+
+/// A message formatted without allocating memory, such that it can be used in
+/// a signal handler.
+///
+/// Data that doesn't fit in the buffer is discarded.
+struct Message {
+    buffer: [u8; MESSAGE_SIZE],
+    len: usize,
+}
+
+impl Message {
+    fn new() -> Message {
+        Message { buffer: [0; MESSAGE_SIZE], len: 0 }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        let len = bytes.len().min(MESSAGE_SIZE - self.len);
+
+        self.buffer[self.len..self.len + len].copy_from_slice(&bytes[..len]);
+        self.len += len;
+    }
+
+    fn push_hex(&mut self, mut value: usize) {
+        let mut digits = [0_u8; 16];
+        let mut start = digits.len();
+
+        loop {
+            start -= 1;
+            digits[start] = b"0123456789abcdef"[value & 0xf];
+            value >>= 4;
+
+            if value == 0 {
+                break;
+            }
+        }
+
+        self.push(b"0x");
+        self.push(&digits[start..]);
+    }
+}
+
+/// An alternative stack to run signal handlers on.
+///
+/// The stack is used by the thread that created it, until the stack is
+/// dropped.
+pub(crate) struct SignalStack {
+    mem: MemoryMap,
+}
+
+impl SignalStack {
+    pub(crate) fn new() -> SignalStack {
+        let mem = MemoryMap::new(SIGNAL_STACK_SIZE, true);
+        let stack =
+            stack_t { ss_sp: mem.ptr as _, ss_flags: 0, ss_size: mem.len };
+
+        unsafe { sigaltstack(&stack, null_mut()) };
+        SignalStack { mem }
+    }
+}
+
+impl Drop for SignalStack {
+    fn drop(&mut self) {
+        let stack = stack_t {
+            ss_sp: null_mut(),
+            ss_flags: SS_DISABLE,
+            ss_size: self.mem.len,
+        };
+
+        unsafe { sigaltstack(&stack, null_mut()) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message() {
+        let mut msg = Message::new();
+
+        msg.push(b"Process '");
+        msg.push(b"Main");
+        msg.push(b"' (");
+        msg.push_hex(0x7f12ab);
+        msg.push(b") ");
+        msg.push_hex(0);
+
+        assert_eq!(&msg.buffer[..msg.len], b"Process 'Main' (0x7f12ab) 0x0");
+    }
+
+    #[test]
+    fn test_message_with_too_much_data() {
+        let mut msg = Message::new();
+
+        msg.push(&[b'a'; MESSAGE_SIZE - 2]);
+        msg.push(b"bcd");
+
+        assert_eq!(msg.len, MESSAGE_SIZE);
+        assert_eq!(&msg.buffer[MESSAGE_SIZE - 3..], b"abc");
+    }
+}
@@ -4,6 +4,7 @@ use crate::mem::{ByteArray, Class, ClassPointer, String as InkoString};
 use crate::network_poller::NetworkPoller;
 use crate::scheduler::process::Scheduler;
 use crate::scheduler::timeout_worker::TimeoutWorker;
+use crate::signal::Signals;
 use rand::{thread_rng, Rng};
 use std::collections::HashMap;
 use std::env;
@@ -108,6 +109,9 @@ pub struct State {
 
     /// The network pollers to use for process threads.
     pub(crate) network_pollers: Vec<NetworkPoller>,
+
+    /// The processes subscribed to Unix signals.
+    pub(crate) signals: Signals,
 }
 
 unsafe impl Sync for State {}
@@ -146,6 +150,7 @@ impl State {
             timeout_worker: TimeoutWorker::new(),
             arguments,
             network_pollers,
+            signals: Signals::new(),
             string_class,
             byte_array_class,
         };
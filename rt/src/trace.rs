@@ -0,0 +1,339 @@
+//! Recording scheduler events in the Chrome trace format.
+//!
+//! When tracing is enabled, threads record events such as processes running,
+//! messages being sent and threads blocking. Each OS thread records its events
+//! into its own buffer, such that recording events doesn't result in threads
+//! contending for the same lock. Upon termination, the events of all buffers
+//! are written to a single file using the Chrome trace event format, which can
+//! be viewed using chrome://tracing or https://ui.perfetto.dev/.
+//!
+//! Events are kept in memory until the program terminates, so tracing is meant
+//! to be enabled for short periods of time only.
+//!
+//! For more information about the format, refer to
+//! https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU.
+use crate::arc_without_weak::ArcWithoutWeak;
+use std::fmt::Write as _;
+use std::fs::write;
+use std::process;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+/// The tracer to record events with, if tracing is enabled.
+static TRACER: Mutex<Option<ArcWithoutWeak<Tracer>>> = Mutex::new(None);
+
+/// A value of an event argument.
+#[derive(Copy, Clone)]
+pub(crate) enum Value {
+    Int(u64),
+    Pointer(usize),
+}
+
+/// The phase (= type) of an event.
+#[derive(Copy, Clone)]
+enum Phase {
+    Begin,
+    End,
+    Complete(u64),
+    Instant,
+}
+
+/// A single event recorded by a thread.
+struct Event {
+    /// The name of the event.
+    name: &'static str,
+
+    /// The category the event belongs to.
+    category: &'static str,
+
+    /// The type of event.
+    phase: Phase,
+
+    /// The time at which the event occurred, in nanoseconds since tracing
+    /// started.
+    time: u64,
+
+    /// Additional arguments to include in the trace.
+    arguments: Vec<(&'static str, Value)>,
+}
+
+impl Event {
+    fn write_json(&self, buffer: &mut String, pid: u32, tid: usize) {
+        let _ = write!(
+            buffer,
+            "{{\"name\":\"{}\",\"cat\":\"{}\",\"ph\":\"{}\",\"ts\":{},\
+            \"pid\":{},\"tid\":{}",
+            self.name,
+            self.category,
+            match self.phase {
+                Phase::Begin => "B",
+                Phase::End => "E",
+                Phase::Complete(_) => "X",
+                Phase::Instant => "i",
+            },
+            micros(self.time),
+            pid,
+            tid
+        );
+
+        match self.phase {
+            Phase::Complete(duration) => {
+                let _ = write!(buffer, ",\"dur\":{}", micros(duration));
+            }
+            Phase::Instant => buffer.push_str(",\"s\":\"t\""),
+            _ => {}
+        }
+
+        if !self.arguments.is_empty() {
+            buffer.push_str(",\"args\":{");
+
+            for (index, (key, value)) in self.arguments.iter().enumerate() {
+                if index > 0 {
+                    buffer.push(',');
+                }
+
+                let _ = match value {
+                    Value::Int(v) => write!(buffer, "\"{}\":{}", key, v),
+                    Value::Pointer(v) => {
+                        write!(buffer, "\"{}\":\"{:#x}\"", key, v)
+                    }
+                };
+            }
+
+            buffer.push('}');
+        }
+
+        buffer.push('}');
+    }
+}
+
+/// Formats a number of nanoseconds as a number of microseconds, as used by the
+/// trace format.
+fn micros(nanos: u64) -> String {
+    format!("{}.{:03}", nanos / 1000, nanos % 1000)
+}
+
+/// The events recorded by a single OS thread.
+struct Events {
+    /// The name of the thread.
+    name: String,
+
+    /// The events in the order they are recorded.
+    events: Mutex<Vec<Event>>,
+}
+
+/// A type that collects the events of all threads.
+pub(crate) struct Tracer {
+    /// The path to write the trace to.
+    path: String,
+
+    /// The time at which tracing started.
+    start: Instant,
+
+    /// The events of each thread, in the order the threads are registered.
+    threads: Mutex<Vec<ArcWithoutWeak<Events>>>,
+}
+
+impl Tracer {
+    pub(crate) fn new(path: String) -> Tracer {
+        Tracer { path, start: Instant::now(), threads: Mutex::new(Vec::new()) }
+    }
+
+    /// Registers a new thread with the tracer, returning the buffer to record
+    /// the thread's events in.
+    pub(crate) fn buffer(&self, name: String) -> Buffer {
+        let events = ArcWithoutWeak::new(Events {
+            name,
+            events: Mutex::new(Vec::new()),
+        });
+
+        self.threads.lock().unwrap().push(events.clone());
+        Buffer { start: self.start, events }
+    }
+
+    /// Returns the recorded events as a Chrome trace JSON document.
+    pub(crate) fn to_json(&self) -> String {
+        let pid = process::id();
+        let mut buffer = String::from("{\"traceEvents\":[");
+        let mut first = true;
+
+        for (tid, thread) in self.threads.lock().unwrap().iter().enumerate() {
+            if !first {
+                buffer.push(',');
+            }
+
+            first = false;
+
+            let _ = write!(
+                buffer,
+                "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":{},\
+                \"tid\":{},\"args\":{{\"name\":\"{}\"}}}}",
+                pid, tid, thread.name
+            );
+
+            for event in thread.events.lock().unwrap().iter() {
+                buffer.push(',');
+                event.write_json(&mut buffer, pid, tid);
+            }
+        }
+
+        buffer.push_str("]}");
+        buffer
+    }
+}
+
+/// The buffer a single thread records its events in.
+pub(crate) struct Buffer {
+    /// The time at which tracing started.
+    start: Instant,
+
+    /// The events recorded so far.
+    ///
+    /// The lock is only contended when the trace is written to disk.
+    events: ArcWithoutWeak<Events>,
+}
+
+impl Buffer {
+    /// Records the start of an event that lasts until a matching call to
+    /// `Buffer::end()`.
+    pub(crate) fn begin(&self, category: &'static str, name: &'static str) {
+        self.record(category, name, Phase::Begin, Instant::now(), Vec::new());
+    }
+
+    /// Records the end of an event started using `Buffer::begin()`.
+    pub(crate) fn end(&self, category: &'static str, name: &'static str) {
+        self.record(category, name, Phase::End, Instant::now(), Vec::new());
+    }
+
+    /// Records an event that started at the given time and ends now.
+    pub(crate) fn complete(
+        &self,
+        category: &'static str,
+        name: &'static str,
+        started: Instant,
+        arguments: Vec<(&'static str, Value)>,
+    ) {
+        let duration = started.elapsed().as_nanos() as u64;
+
+        self.record(
+            category,
+            name,
+            Phase::Complete(duration),
+            started,
+            arguments,
+        );
+    }
+
+    /// Records an event without a duration.
+    pub(crate) fn instant(
+        &self,
+        category: &'static str,
+        name: &'static str,
+        arguments: Vec<(&'static str, Value)>,
+    ) {
+        self.record(category, name, Phase::Instant, Instant::now(), arguments);
+    }
+
+    fn record(
+        &self,
+        category: &'static str,
+        name: &'static str,
+        phase: Phase,
+        at: Instant,
+        arguments: Vec<(&'static str, Value)>,
+    ) {
+        let time = at.saturating_duration_since(self.start).as_nanos() as u64;
+        let event = Event { name, category, phase, time, arguments };
+
+        self.events.events.lock().unwrap().push(event);
+    }
+}
+
+/// Enables tracing, writing the trace to the given path upon termination.
+pub(crate) fn enable(path: String) {
+    *TRACER.lock().unwrap() = Some(ArcWithoutWeak::new(Tracer::new(path)));
+}
+
+/// Returns a new buffer for the current thread, if tracing is enabled.
+///
+/// The buffer is named after the current thread.
+pub(crate) fn buffer() -> Option<Buffer> {
+    TRACER.lock().unwrap().as_ref().map(|tracer| {
+        let name = thread::current().name().unwrap_or("unnamed").to_string();
+
+        tracer.buffer(name)
+    })
+}
+
+/// Writes the recorded events to the trace file, if tracing is enabled.
+///
+/// Tracing is disabled afterwards, such that the trace is written only once.
+pub(crate) fn write_trace() {
+    let Some(tracer) = TRACER.lock().unwrap().take() else { return };
+
+    if let Err(err) = write(&tracer.path, tracer.to_json()) {
+        eprintln!("Failed to write the trace to {}: {}", tracer.path, err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_micros() {
+        assert_eq!(micros(0), "0.000");
+        assert_eq!(micros(1_500), "1.500");
+        assert_eq!(micros(12_345_678), "12345.678");
+    }
+
+    #[test]
+    fn test_tracer_to_json() {
+        let tracer = Tracer::new("trace.json".to_string());
+        let buffer = tracer.buffer("proc 0".to_string());
+
+        buffer.instant(
+            "message",
+            "send",
+            vec![("receiver", Value::Pointer(0x10)), ("size", Value::Int(2))],
+        );
+        buffer.begin("thread", "blocking");
+        buffer.end("thread", "blocking");
+        buffer.complete(
+            "run",
+            "yield",
+            Instant::now() - Duration::from_millis(1),
+            Vec::new(),
+        );
+
+        tracer.buffer("timeout".to_string());
+
+        let json = tracer.to_json();
+        let pid = process::id();
+
+        assert!(json.starts_with(&format!(
+            "{{\"traceEvents\":[{{\"name\":\"thread_name\",\"ph\":\"M\",\
+            \"pid\":{},\"tid\":0,\"args\":{{\"name\":\"proc 0\"}}}},\
+            {{\"name\":\"send\",\"cat\":\"message\",\"ph\":\"i\",\"ts\":",
+            pid
+        )));
+        assert!(json.contains(
+            "\"s\":\"t\",\"args\":{\"receiver\":\"0x10\",\"size\":2}}"
+        ));
+        assert!(json
+            .contains("\"name\":\"blocking\",\"cat\":\"thread\",\"ph\":\"B\""));
+        assert!(json
+            .contains("\"name\":\"blocking\",\"cat\":\"thread\",\"ph\":\"E\""));
+        assert!(
+            json.contains("\"name\":\"yield\",\"cat\":\"run\",\"ph\":\"X\"")
+        );
+        assert!(json.contains(",\"dur\":"));
+        assert!(json.ends_with(&format!(
+            "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":{},\"tid\":1,\
+            \"args\":{{\"name\":\"timeout\"}}}}]}}",
+            pid
+        )));
+    }
+}
@@ -585,7 +585,7 @@ impl<'a> TypeChecker<'a> {
                         // One-time subtyping is enabled because we want to
                         // allow passing classes to type parameters with
                         // requirements.
-                        self.check_class_with_trait(
+                        self.check_class_with_requirement(
                             lhs,
                             req,
                             env,
@@ -749,9 +749,9 @@ impl<'a> TypeChecker<'a> {
         // sub-typing through traits.
         let rules = rules.with_one_time_subtyping();
         let res = match left_id {
-            TypeId::ClassInstance(lhs) => reqs
-                .into_iter()
-                .all(|req| self.check_class_with_trait(lhs, req, env, rules)),
+            TypeId::ClassInstance(lhs) => reqs.into_iter().all(|req| {
+                self.check_class_with_requirement(lhs, req, env, rules)
+            }),
             TypeId::TraitInstance(lhs) => reqs
                 .into_iter()
                 .all(|req| self.check_traits(lhs, req, env, rules)),
@@ -788,6 +788,23 @@ impl<'a> TypeChecker<'a> {
         self.check_class_with_trait(left, right, &mut env, rules)
     }
 
+    fn check_class_with_requirement(
+        &mut self,
+        left: ClassInstance,
+        right: TraitInstance,
+        env: &mut Environment,
+        rules: Rules,
+    ) -> bool {
+        // Async classes can't implement traits. Instead, they're compatible
+        // with the `std.process.Process` trait, and only with that trait. This
+        // allows methods to require their arguments to be processes.
+        if left.instance_of.kind(self.db).is_async() {
+            return self.db.process_trait() == Some(right.instance_of);
+        }
+
+        self.check_class_with_trait(left, right, env, rules)
+    }
+
     fn check_class_with_trait(
         &mut self,
         left: ClassInstance,
@@ -864,7 +881,7 @@ impl<'a> TypeChecker<'a> {
             | TypeRef::UniMut(id)
             | TypeRef::Infer(id) => match id {
                 TypeId::ClassInstance(lhs) => {
-                    self.check_class_with_trait(lhs, right, env, rules)
+                    self.check_class_with_requirement(lhs, right, env, rules)
                 }
                 TypeId::TraitInstance(lhs) => {
                     self.check_traits(lhs, right, env, rules)
@@ -1042,16 +1059,18 @@ impl<'a> TypeChecker<'a> {
 mod tests {
     use super::*;
     use crate::format::format_type;
+    use crate::module_name::ModuleName;
     use crate::test::{
         closure, generic_instance_id, generic_trait_instance,
         generic_trait_instance_id, immutable, implement, infer, instance,
-        mutable, new_class, new_extern_class, new_parameter, new_trait, owned,
-        parameter, placeholder, pointer, rigid, trait_instance,
-        trait_instance_id, type_arguments, type_bounds, uni,
+        mutable, new_async_class, new_class, new_extern_class, new_parameter,
+        new_trait, owned, parameter, placeholder, pointer, rigid,
+        trait_instance, trait_instance_id, type_arguments, type_bounds, uni,
     };
     use crate::{
-        Block, Class, ClassId, ClassKind, Closure, ModuleId,
-        TraitImplementation, TypePlaceholder, Visibility,
+        Block, Class, ClassId, ClassKind, Closure, Module, ModuleId, Symbol,
+        TraitImplementation, TypePlaceholder, Visibility, PROCESS_MODULE,
+        PROCESS_TRAIT,
     };
 
     #[track_caller]
@@ -2197,6 +2216,46 @@ mod tests {
         assert!(res);
     }
 
+    #[test]
+    fn test_async_class_with_process_trait() {
+        let mut db = Database::new();
+        let module = Module::alloc(
+            &mut db,
+            ModuleName::new(PROCESS_MODULE),
+            "process.inko".into(),
+        );
+        let process = new_trait(&mut db, PROCESS_TRAIT);
+        let to_string = new_trait(&mut db, "ToString");
+        let worker = new_async_class(&mut db, "Worker");
+        let thing = new_class(&mut db, "Thing");
+        let param1 = new_parameter(&mut db, "A");
+        let param2 = new_parameter(&mut db, "B");
+        let var = TypePlaceholder::alloc(&mut db, Some(param1));
+
+        module.new_symbol(
+            &mut db,
+            PROCESS_TRAIT.to_string(),
+            Symbol::Trait(process),
+        );
+        param1.add_requirements(&mut db, vec![trait_instance(process)]);
+        param2.add_requirements(&mut db, vec![trait_instance(to_string)]);
+
+        check_ok(&db, owned(instance(worker)), owned(parameter(param1)));
+        check_ok(&db, owned(instance(worker)), placeholder(var));
+        check_err(&db, owned(instance(worker)), owned(parameter(param2)));
+        check_err(&db, owned(instance(thing)), owned(parameter(param1)));
+        check_err(
+            &db,
+            owned(instance(worker)),
+            owned(trait_instance_id(process)),
+        );
+        check_err(
+            &db,
+            owned(instance(ClassId::int())),
+            owned(parameter(param1)),
+        );
+    }
+
     #[test]
     fn test_check_argument_with_mut() {
         let mut db = Database::new();
@@ -19,6 +19,7 @@ use crate::resolve::TypeResolver;
 use std::cell::Cell;
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 // The IDs of these built-in types must match the order of the fields in the
 // State type.
@@ -76,6 +77,8 @@ pub const MAIN_METHOD: &str = "main";
 pub const DROP_MODULE: &str = "std.drop";
 pub const DROP_TRAIT: &str = "Drop";
 pub const DROP_METHOD: &str = "drop";
+pub const PROCESS_MODULE: &str = "std.process";
+pub const PROCESS_TRAIT: &str = "Process";
 pub const DROPPER_METHOD: &str = "$dropper";
 pub const ASYNC_DROPPER_METHOD: &str = "$async_dropper";
 pub const OPTION_MODULE: &str = "std.option";
@@ -186,6 +189,13 @@ impl TypePlaceholderId {
             }
         }
 
+        // Placeholders are stored in a Cell, so assigning them while the
+        // database is shared between threads (e.g. during code generation)
+        // would be a data race.
+        assert!(
+            !db.is_frozen(),
+            "type placeholders can't be assigned when the database is frozen"
+        );
         self.get(db).value.set(value);
     }
 
@@ -556,6 +566,24 @@ impl TraitId {
         None
     }
 
+    pub fn method_names(self, db: &Database) -> Vec<String> {
+        let typ = self.get(db);
+        let mut names: Vec<String> = typ
+            .default_methods
+            .keys()
+            .chain(typ.required_methods.keys())
+            .cloned()
+            .collect();
+
+        for &req in &typ.required_traits {
+            names.append(&mut req.instance_of().method_names(db));
+        }
+
+        names.sort();
+        names.dedup();
+        names
+    }
+
     pub fn add_default_method(
         self,
         db: &mut Database,
@@ -1298,6 +1326,14 @@ impl ClassId {
         self.get(db).methods.get(name).is_some()
     }
 
+    pub fn method_names(self, db: &Database) -> Vec<String> {
+        let mut names: Vec<String> =
+            self.get(db).methods.keys().cloned().collect();
+
+        names.sort();
+        names
+    }
+
     pub fn add_method(self, db: &mut Database, name: String, method: MethodId) {
         self.get_mut(db).methods.insert(name, method);
     }
@@ -4097,6 +4133,28 @@ impl TypeId {
         }
     }
 
+    pub fn method_names(self, db: &Database) -> Vec<String> {
+        match self {
+            TypeId::Class(id) => id.method_names(db),
+            TypeId::Trait(id) => id.method_names(db),
+            TypeId::Module(id) => id.get(db).class.method_names(db),
+            TypeId::ClassInstance(id) => id.instance_of().method_names(db),
+            TypeId::TraitInstance(id) => id.instance_of().method_names(db),
+            TypeId::TypeParameter(id) | TypeId::RigidTypeParameter(id) => {
+                let mut names: Vec<String> = id
+                    .requirements(db)
+                    .into_iter()
+                    .flat_map(|req| req.instance_of().method_names(db))
+                    .collect();
+
+                names.sort();
+                names.dedup();
+                names
+            }
+            _ => Vec::new(),
+        }
+    }
+
     pub fn use_dynamic_dispatch(self) -> bool {
         matches!(
             self,
@@ -4160,6 +4218,10 @@ pub struct Database {
     main_module: Option<ModuleName>,
     main_method: Option<MethodId>,
     main_class: Option<ClassId>,
+
+    /// A flag indicating the database is shared between threads, and thus
+    /// type placeholders can't be assigned.
+    frozen: AtomicBool,
 }
 
 impl Database {
@@ -4206,9 +4268,21 @@ impl Database {
             main_module: None,
             main_method: None,
             main_class: None,
+            frozen: AtomicBool::new(false),
         }
     }
 
+    /// Marks the database as (not) being shared between threads.
+    ///
+    /// While frozen, assigning a type placeholder results in a panic.
+    pub fn set_frozen(&self, frozen: bool) {
+        self.frozen.store(frozen, Ordering::Release);
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.load(Ordering::Acquire)
+    }
+
     pub fn builtin_class(&self, name: &str) -> Option<ClassId> {
         match name {
             INT_NAME => Some(ClassId::int()),
@@ -4270,6 +4344,18 @@ impl Database {
         self.trait_in_module(DROP_MODULE, DROP_TRAIT)
     }
 
+    /// Returns the trait that all async classes are compatible with, if the
+    /// module defining it is imported.
+    pub fn process_trait(&self) -> Option<TraitId> {
+        let module = self.module_mapping.get(PROCESS_MODULE).cloned()?;
+
+        if let Some(Symbol::Trait(id)) = module.symbol(self, PROCESS_TRAIT) {
+            Some(id)
+        } else {
+            None
+        }
+    }
+
     pub fn number_of_traits(&self) -> usize {
         self.traits.len()
     }
@@ -4725,6 +4811,59 @@ mod tests {
         assert_eq!(id.0, 0);
     }
 
+    #[test]
+    fn test_type_id_method_names_with_class() {
+        let mut db = Database::new();
+        let class = new_class(&mut db, "A");
+
+        for name in ["b", "a"] {
+            let method = Method::alloc(
+                &mut db,
+                ModuleId(0),
+                name.to_string(),
+                Visibility::Public,
+                MethodKind::Instance,
+            );
+
+            class.add_method(&mut db, name.to_string(), method);
+        }
+
+        assert_eq!(
+            TypeId::ClassInstance(ClassInstance::new(class)).method_names(&db),
+            vec!["a".to_string(), "b".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_type_id_method_names_with_trait() {
+        let mut db = Database::new();
+        let to_a = new_trait(&mut db, "ToA");
+        let to_b = new_trait(&mut db, "ToB");
+        let to_a_method = Method::alloc(
+            &mut db,
+            ModuleId(0),
+            "to_a".to_string(),
+            Visibility::Public,
+            MethodKind::Instance,
+        );
+        let to_b_method = Method::alloc(
+            &mut db,
+            ModuleId(0),
+            "to_b".to_string(),
+            Visibility::Public,
+            MethodKind::Instance,
+        );
+
+        to_a.add_required_method(&mut db, "to_a".to_string(), to_a_method);
+        to_b.add_default_method(&mut db, "to_b".to_string(), to_b_method);
+        to_a.add_required_trait(&mut db, TraitInstance::new(to_b));
+
+        assert_eq!(
+            TypeId::Trait(to_a).method_names(&db),
+            vec!["to_a".to_string(), "to_b".to_string()]
+        );
+    }
+
     #[test]
     fn test_type_id_named_type_with_class() {
         let mut db = Database::new();
@@ -4900,6 +5039,16 @@ mod tests {
         assert!(p2.value(&db).is_none());
     }
 
+    #[test]
+    #[should_panic]
+    fn test_type_placeholder_id_assign_with_frozen_database() {
+        let mut db = Database::new();
+        let p1 = TypePlaceholder::alloc(&mut db, None);
+
+        db.set_frozen(true);
+        p1.assign(&db, TypeRef::int());
+    }
+
     #[test]
     fn test_type_placeholder_id_resolve() {
         let mut db = Database::new();